@@ -0,0 +1,19 @@
+-- Additional phone numbers for a contact. The contact's primary number stays
+-- in contacts.phone_number; every other line (mobile, home, work, ...) lives here
+-- so they share one name and action.
+CREATE TABLE contact_numbers (
+    id BIGSERIAL PRIMARY KEY,
+    contact_id INT8 NOT NULL,
+    phone_number VARCHAR(255) NOT NULL,
+    label VARCHAR(255),
+    inserted_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    FOREIGN KEY (contact_id) REFERENCES contacts(id) ON DELETE CASCADE
+);
+
+CREATE UNIQUE INDEX contact_numbers_phone_number_index
+    ON contact_numbers (phone_number);
+
+-- Covers get_numbers_for_contact: WHERE contact_id = $1
+CREATE INDEX idx_contact_numbers_contact_id
+    ON contact_numbers (contact_id);
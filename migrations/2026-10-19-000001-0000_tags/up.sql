@@ -0,0 +1,25 @@
+-- Free-form tags on contacts ("family", "tradie", "scam", ...). A tag may
+-- carry an action which overrides the action of every contact it is attached
+-- to, so blocking a whole category is a single change.
+CREATE TABLE tags (
+    id BIGSERIAL PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    action VARCHAR(255),
+    inserted_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE UNIQUE INDEX tags_name_index
+    ON tags (name);
+
+CREATE TABLE contact_tags (
+    contact_id INT8 NOT NULL,
+    tag_id INT8 NOT NULL,
+    PRIMARY KEY (contact_id, tag_id),
+    FOREIGN KEY (contact_id) REFERENCES contacts(id) ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+);
+
+-- Covers contacts with a given tag: WHERE tag_id = $1
+CREATE INDEX idx_contact_tags_tag_id
+    ON contact_tags (tag_id);
@@ -0,0 +1,26 @@
+-- Who changed what and when. Rows are written by the service layer in the
+-- same transaction as the change. user_id is NULL for changes made by the
+-- system (incoming calls, command line imports) or by a since deleted user.
+CREATE TABLE audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    user_id INT8,
+    entity_type VARCHAR(255) NOT NULL,
+    entity_id INT8 NOT NULL,
+    operation VARCHAR(255) NOT NULL,
+    before JSONB,
+    after JSONB,
+    inserted_at TIMESTAMPTZ NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE SET NULL
+);
+
+-- Covers the history of one entity: WHERE entity_type = $1 AND entity_id = $2 ORDER BY id DESC
+CREATE INDEX idx_audit_log_entity
+    ON audit_log (entity_type, entity_id, id);
+
+-- Covers the admin view filtered by user: WHERE user_id = $1 ORDER BY id DESC
+CREATE INDEX idx_audit_log_user_id
+    ON audit_log (user_id, id);
+
+-- Covers the admin view filtered by date range
+CREATE INDEX idx_audit_log_inserted_at
+    ON audit_log (inserted_at);
@@ -0,0 +1,10 @@
+DROP INDEX idx_defaults_archived_at;
+DROP INDEX idx_contacts_archived_at;
+
+-- Fails if an archived contact shares its number with an active one.
+DROP INDEX contacts_phone_number_index;
+CREATE UNIQUE INDEX contacts_phone_number_index
+    ON contacts USING btree (phone_number);
+
+ALTER TABLE defaults DROP COLUMN archived_at;
+ALTER TABLE contacts DROP COLUMN archived_at;
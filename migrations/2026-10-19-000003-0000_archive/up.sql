@@ -0,0 +1,23 @@
+-- Archived contacts and defaults are hidden from search and call matching
+-- until restored, and purged once they have been archived for longer than
+-- ARCHIVE_RETENTION_DAYS.
+ALTER TABLE contacts ADD COLUMN archived_at TIMESTAMPTZ;
+ALTER TABLE defaults ADD COLUMN archived_at TIMESTAMPTZ;
+
+-- An archived contact keeps its phone number, so a new contact may be
+-- created for the same number while it is archived.
+DROP INDEX contacts_phone_number_index;
+CREATE UNIQUE INDEX contacts_phone_number_index
+    ON contacts (phone_number)
+    WHERE archived_at IS NULL;
+
+-- Covers the archive view and the purge job:
+--   WHERE archived_at IS NOT NULL ORDER BY archived_at DESC
+--   WHERE archived_at < $1
+CREATE INDEX idx_contacts_archived_at
+    ON contacts (archived_at)
+    WHERE archived_at IS NOT NULL;
+
+CREATE INDEX idx_defaults_archived_at
+    ON defaults (archived_at)
+    WHERE archived_at IS NOT NULL;
@@ -0,0 +1,6 @@
+DROP INDEX idx_contacts_temporary_action_expires_at;
+
+ALTER TABLE contacts DROP CONSTRAINT contacts_temporary_action_check;
+
+ALTER TABLE contacts DROP COLUMN temporary_action_expires_at;
+ALTER TABLE contacts DROP COLUMN temporary_action;
@@ -0,0 +1,13 @@
+-- A temporary action replaces the contact's action until it expires, after
+-- which the permanent action applies again.
+ALTER TABLE contacts ADD COLUMN temporary_action VARCHAR(255);
+ALTER TABLE contacts ADD COLUMN temporary_action_expires_at TIMESTAMPTZ;
+
+ALTER TABLE contacts ADD CONSTRAINT contacts_temporary_action_check
+    CHECK ((temporary_action IS NULL) = (temporary_action_expires_at IS NULL));
+
+-- Covers the expiry job:
+--   WHERE temporary_action_expires_at <= $1
+CREATE INDEX idx_contacts_temporary_action_expires_at
+    ON contacts (temporary_action_expires_at)
+    WHERE temporary_action_expires_at IS NOT NULL;
@@ -0,0 +1,8 @@
+DROP INDEX idx_contact_numbers_phone_number_digits_trgm;
+DROP INDEX idx_contacts_phone_number_digits_trgm;
+DROP INDEX idx_contacts_name_trgm;
+
+ALTER TABLE contact_numbers DROP COLUMN phone_number_digits;
+ALTER TABLE contacts DROP COLUMN phone_number_digits;
+
+DROP EXTENSION IF EXISTS pg_trgm;
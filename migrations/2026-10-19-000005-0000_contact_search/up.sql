@@ -0,0 +1,18 @@
+-- Trigram indexes let ILIKE '%...%' use an index, and give fuzzy name
+-- matching with word_similarity() and the <% operator.
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- Numbers with the formatting removed, so "0412 345 678" finds 0412345678.
+ALTER TABLE contacts ADD COLUMN phone_number_digits TEXT NOT NULL
+    GENERATED ALWAYS AS (regexp_replace(phone_number, '[^0-9]', '', 'g')) STORED;
+ALTER TABLE contact_numbers ADD COLUMN phone_number_digits TEXT NOT NULL
+    GENERATED ALWAYS AS (regexp_replace(phone_number, '[^0-9]', '', 'g')) STORED;
+
+CREATE INDEX idx_contacts_name_trgm
+    ON contacts USING gin (name gin_trgm_ops);
+
+CREATE INDEX idx_contacts_phone_number_digits_trgm
+    ON contacts USING gin (phone_number_digits gin_trgm_ops);
+
+CREATE INDEX idx_contact_numbers_phone_number_digits_trgm
+    ON contact_numbers USING gin (phone_number_digits gin_trgm_ops);
@@ -0,0 +1,6 @@
+DROP INDEX idx_contacts_last_call_at;
+DROP INDEX idx_contacts_call_count;
+
+ALTER TABLE contacts DROP COLUMN last_call_at;
+ALTER TABLE contacts DROP COLUMN first_call_at;
+ALTER TABLE contacts DROP COLUMN call_count;
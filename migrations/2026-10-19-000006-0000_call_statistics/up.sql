@@ -0,0 +1,23 @@
+-- Call statistics kept on each contact, so that listing contacts does not
+-- have to count their calls.  Updated whenever a call is created, moved or
+-- deleted.
+ALTER TABLE contacts ADD COLUMN call_count BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE contacts ADD COLUMN first_call_at TIMESTAMPTZ;
+ALTER TABLE contacts ADD COLUMN last_call_at TIMESTAMPTZ;
+
+UPDATE contacts
+SET call_count = stats.call_count,
+    first_call_at = stats.first_call_at,
+    last_call_at = stats.last_call_at
+FROM (
+    SELECT contact_id,
+           COUNT(*) AS call_count,
+           MIN(inserted_at) AS first_call_at,
+           MAX(inserted_at) AS last_call_at
+    FROM phone_calls
+    GROUP BY contact_id
+) AS stats
+WHERE contacts.id = stats.contact_id;
+
+CREATE INDEX idx_contacts_call_count ON contacts (call_count DESC, id);
+CREATE INDEX idx_contacts_last_call_at ON contacts (last_call_at DESC NULLS LAST, id);
@@ -0,0 +1,3 @@
+DROP TABLE contact_note_revisions;
+
+ALTER TABLE contacts ALTER COLUMN comments TYPE VARCHAR(255) USING left(comments, 255);
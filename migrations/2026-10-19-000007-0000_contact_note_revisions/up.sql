@@ -0,0 +1,25 @@
+-- Comments are Markdown notes of any length.
+ALTER TABLE contacts ALTER COLUMN comments TYPE TEXT;
+
+-- Every version of a contact's notes, written by the service layer in the
+-- same transaction as the change. user_id is NULL for changes made by the
+-- system or by a since deleted user.
+CREATE TABLE contact_note_revisions (
+    id BIGSERIAL PRIMARY KEY,
+    contact_id INT8 NOT NULL,
+    user_id INT8,
+    comments TEXT,
+    inserted_at TIMESTAMPTZ NOT NULL,
+    FOREIGN KEY (contact_id) REFERENCES contacts(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE SET NULL
+);
+
+-- Covers get_note_revisions_for_contact: WHERE contact_id = $1 ORDER BY id DESC
+CREATE INDEX idx_contact_note_revisions_contact_id
+    ON contact_note_revisions (contact_id, id);
+
+-- Existing comments become the first revision.
+INSERT INTO contact_note_revisions (contact_id, user_id, comments, inserted_at)
+SELECT id, NULL, comments, updated_at
+FROM contacts
+WHERE comments IS NOT NULL;
@@ -0,0 +1,5 @@
+DROP INDEX idx_defaults_group_id;
+DROP INDEX idx_contacts_group_id;
+
+ALTER TABLE defaults DROP COLUMN group_id;
+ALTER TABLE contacts DROP COLUMN group_id;
@@ -0,0 +1,5 @@
+ALTER TABLE contacts ADD COLUMN group_id INT8 REFERENCES groups(id) ON DELETE SET NULL;
+ALTER TABLE defaults ADD COLUMN group_id INT8 REFERENCES groups(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_contacts_group_id ON contacts (group_id);
+CREATE INDEX idx_defaults_group_id ON defaults (group_id);
@@ -0,0 +1 @@
+ALTER TABLE phone_calls DROP COLUMN contact_name;
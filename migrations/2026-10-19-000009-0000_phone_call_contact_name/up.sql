@@ -0,0 +1,9 @@
+-- The name of the contact when the call came in, so that exports are not
+-- changed by later renames.  Calls from before this migration get the
+-- current name, the best that is known.
+ALTER TABLE phone_calls ADD COLUMN contact_name VARCHAR(255);
+
+UPDATE phone_calls
+SET contact_name = contacts.name
+FROM contacts
+WHERE contacts.id = phone_calls.contact_id;
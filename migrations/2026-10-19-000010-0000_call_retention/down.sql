@@ -0,0 +1,2 @@
+UPDATE phone_calls SET source_number = '' WHERE source_number IS NULL;
+ALTER TABLE phone_calls ALTER COLUMN source_number SET NOT NULL;
@@ -0,0 +1,2 @@
+-- Anonymised calls keep their action and time but lose their numbers.
+ALTER TABLE phone_calls ALTER COLUMN source_number DROP NOT NULL;
@@ -0,0 +1 @@
+ALTER TABLE phone_calls DROP COLUMN decision_source;
@@ -0,0 +1,3 @@
+-- Why the action of a call was chosen.  Unknown for calls from before this
+-- migration.
+ALTER TABLE phone_calls ADD COLUMN decision_source VARCHAR(255);
@@ -0,0 +1,4 @@
+-- Fails if an archived contact shares an additional number with another one.
+DROP INDEX contact_numbers_phone_number_index;
+CREATE UNIQUE INDEX contact_numbers_phone_number_index
+    ON contact_numbers (phone_number);
@@ -0,0 +1,7 @@
+-- An archived contact keeps its additional numbers, so they may be given to
+-- another contact while it is archived.  Uniqueness among contacts that are
+-- not archived is checked when numbers are added and when a contact is
+-- restored, as it is between contacts.phone_number and these numbers.
+DROP INDEX contact_numbers_phone_number_index;
+CREATE INDEX contact_numbers_phone_number_index
+    ON contact_numbers (phone_number);
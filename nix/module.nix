@@ -61,6 +61,11 @@ in
          - `LDAP_BASE_DN` - The base DN for LDAP.
          - `LDAP_USERNAME` - The username to connect to the LDAP server.
          - `LDAP_PASSWORD` - The password for the LDAP server.
+         - `LDAP_TLS` - `none`, `ldaps` or `starttls`.
+         - `LDAP_TLS_CA_FILE` - PEM bundle of CA certificates for LDAP.
+         - `LDAP_TLS_CERT_FILE` - PEM client certificate for LDAP.
+         - `LDAP_TLS_KEY_FILE` - PKCS#8 private key of the client certificate.
+         - `LDAP_TLS_VERIFY_HOSTNAME` - `false` to skip the host name check.
          - `PHONE_USERNAME` - The username to connect to the API.
          - `PHONE_PASSWORD` - The password to connect to the API.
       '';
@@ -0,0 +1,225 @@
+//! Command line subcommands run instead of the web server.
+//!
+//! ```text
+//! phone_db import-csv FILE [--dry-run]
+//! phone_db export-csv [--query QUERY] [--tag TAG]
+//! phone_db call-retention [--dry-run]
+//! phone_db ldap-reconcile [--fix]
+//! ```
+//!
+//! These use the same `DATABASE_URL` and `LDAP_*` environment variables as
+//! the server, and `call-retention` the same `CALL_RETENTION_*` ones.
+
+use std::ops::Deref;
+use std::process::ExitCode;
+
+use crate::functions::imports::validate_contact_csv;
+use crate::models::imports::ImportReport;
+use crate::models::ldap::LdapReconcileReport;
+use crate::models::search::SearchQuery;
+use crate::server::database::models::groups::GroupScope;
+use crate::server::database::{
+    connection,
+    service::{contacts, imports},
+};
+use crate::server::ldap::connect::connect_ldap;
+use crate::server::retention;
+
+pub enum Command {
+    ImportCsv {
+        path: String,
+        dry_run: bool,
+    },
+    ExportCsv {
+        query: SearchQuery,
+        tag: Option<String>,
+    },
+    CallRetention {
+        dry_run: bool,
+    },
+    LdapReconcile {
+        fix: bool,
+    },
+}
+
+impl Command {
+    /// Returns `None` when no subcommand was given, so the server should run.
+    pub fn from_args() -> Option<Result<Command, String>> {
+        let mut args = std::env::args().skip(1);
+        let command = args.next()?;
+        let args: Vec<String> = args.collect();
+
+        let result = match command.as_str() {
+            "import-csv" => {
+                let dry_run = args.iter().any(|a| a == "--dry-run");
+                let mut paths = args.iter().filter(|a| !a.starts_with("--"));
+                match (paths.next(), paths.next()) {
+                    (Some(path), None) => Ok(Command::ImportCsv {
+                        path: path.clone(),
+                        dry_run,
+                    }),
+                    _ => Err("Usage: import-csv FILE [--dry-run]".to_string()),
+                }
+            }
+            "export-csv" => {
+                let mut query = SearchQuery::default();
+                let mut tag = None;
+                let mut args = args.into_iter();
+                let mut result = Ok(());
+                while let Some(arg) = args.next() {
+                    match (arg.as_str(), args.next()) {
+                        ("--query", Some(value)) => query = SearchQuery::from(value),
+                        ("--tag", Some(value)) => tag = Some(value),
+                        _ => {
+                            result = Err("Usage: export-csv [--query QUERY] [--tag TAG]");
+                            break;
+                        }
+                    }
+                }
+                result
+                    .map(|()| Command::ExportCsv { query, tag })
+                    .map_err(String::from)
+            }
+            "call-retention" => match args.as_slice() {
+                [] => Ok(Command::CallRetention { dry_run: false }),
+                [flag] if flag == "--dry-run" => Ok(Command::CallRetention { dry_run: true }),
+                _ => Err("Usage: call-retention [--dry-run]".to_string()),
+            },
+            "ldap-reconcile" => match args.as_slice() {
+                [] => Ok(Command::LdapReconcile { fix: false }),
+                [flag] if flag == "--fix" => Ok(Command::LdapReconcile { fix: true }),
+                _ => Err("Usage: ldap-reconcile [--fix]".to_string()),
+            },
+            _ => return None,
+        };
+
+        Some(result)
+    }
+}
+
+fn print_report(report: &ImportReport) {
+    for conflict in &report.conflicts {
+        eprintln!("{}: {}", conflict.item, conflict.reason);
+    }
+    println!(
+        "{} created, {} updated, {} unchanged, {} errors.",
+        report.created,
+        report.updated,
+        report.unchanged,
+        report.conflicts.len()
+    );
+    if report.dry_run {
+        println!("Nothing was saved.");
+    }
+}
+
+async fn import_csv(path: String, dry_run: bool) -> Result<ImportReport, String> {
+    let content = std::fs::read_to_string(&path).map_err(|err| format!("{path}: {err}"))?;
+    let (rows, errors) = validate_contact_csv(&content).map_err(|err| err.to_string())?;
+
+    let database = connection::init().await;
+    let ldap = connect_ldap().await;
+
+    let mut conn = database.get().await.map_err(|err| err.to_string())?;
+    let mut ldap_conn = ldap
+        .get()
+        .await
+        .map_err(|err| err.to_string())?
+        .deref()
+        .clone();
+
+    let dry_run = dry_run || !errors.is_empty();
+    // Imports from the command line are recorded as system changes.
+    let mut report = imports::import_contact_rows(
+        &mut conn,
+        &GroupScope::All,
+        ldap.base_dn(),
+        &mut ldap_conn,
+        None,
+        rows,
+        dry_run,
+    )
+    .await
+    .map_err(|err| err.to_string())?;
+
+    report.conflicts.splice(0..0, errors);
+    Ok(report)
+}
+
+async fn export_csv(query: SearchQuery, tag: Option<String>) -> Result<String, String> {
+    let database = connection::init().await;
+    let mut conn = database.get().await.map_err(|err| err.to_string())?;
+
+    imports::export_csv(&mut conn, &GroupScope::All, query, tag)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+async fn call_retention(dry_run: bool) -> Result<retention::Report, String> {
+    let mut config = retention::Config::get_from_env()
+        .ok_or_else(|| "CALL_RETENTION_DAYS is not set".to_string())?;
+    config.dry_run |= dry_run;
+
+    let database = connection::init().await;
+    let ldap = connect_ldap().await;
+
+    retention::apply_retention(&database, &ldap, &config)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+async fn ldap_reconcile(fix: bool) -> Result<LdapReconcileReport, String> {
+    let database = connection::init().await;
+    let ldap = connect_ldap().await;
+
+    let mut conn = database.get().await.map_err(|err| err.to_string())?;
+    let mut ldap_conn = ldap
+        .get()
+        .await
+        .map_err(|err| err.to_string())?
+        .deref()
+        .clone();
+
+    contacts::reconcile_ldap(&mut conn, ldap.base_dn(), &mut ldap_conn, fix)
+        .await
+        .map_err(|err| err.to_string())
+}
+
+pub fn run(command: Command) -> ExitCode {
+    let runtime = tokio::runtime::Runtime::new().expect("Failed to start tokio runtime");
+
+    let result = runtime.block_on(async move {
+        match command {
+            Command::ImportCsv { path, dry_run } => {
+                let report = import_csv(path, dry_run).await?;
+                print_report(&report);
+                Ok(report.conflicts.is_empty())
+            }
+            Command::ExportCsv { query, tag } => {
+                print!("{}", export_csv(query, tag).await?);
+                Ok(true)
+            }
+            Command::CallRetention { dry_run } => {
+                println!("{}.", call_retention(dry_run).await?);
+                Ok(true)
+            }
+            Command::LdapReconcile { fix } => {
+                let report = ldap_reconcile(fix).await?;
+                for drift in &report.drift {
+                    println!("{drift}");
+                }
+                println!("{report}.");
+                Ok(report.fixed || report.drift.is_empty())
+            }
+        }
+    });
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("Error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
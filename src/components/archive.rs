@@ -0,0 +1,128 @@
+use std::{num::ParseIntError, str::FromStr};
+
+use dioxus::prelude::*;
+use dioxus_router::ToQueryArgument;
+use tap::Pipe;
+use thiserror::Error;
+
+use crate::{
+    components::{contacts::ContactDelete, defaults::DefaultDelete},
+    forms::Dialog,
+    models::{
+        contacts::{Contact, ContactId},
+        defaults::{Default, DefaultId},
+    },
+};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ActiveDialog {
+    DeleteContact(Contact),
+    DeleteDefault(Default),
+    Idle,
+}
+
+#[derive(Error, Debug)]
+pub enum ListDialogReferenceError {
+    #[error("Invalid integer")]
+    ParseIntError(#[from] ParseIntError),
+
+    #[error("Invalid reference")]
+    ReferenceError,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ListDialogReference {
+    DeleteContact {
+        contact_id: ContactId,
+    },
+    DeleteDefault {
+        default_id: DefaultId,
+    },
+    #[default]
+    Idle,
+}
+
+impl ToQueryArgument for ListDialogReference {
+    fn display_query_argument(
+        &self,
+        query_name: &str,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{}={}", query_name, self.to_string())
+    }
+}
+
+impl FromStr for ListDialogReference {
+    type Err = ListDialogReferenceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s.split("-").collect::<Vec<_>>();
+        match split[..] {
+            ["delete_contact", id] => {
+                let contact_id = ContactId::new(id.parse()?);
+                Self::DeleteContact { contact_id }
+            }
+            ["delete_default", id] => {
+                let default_id = DefaultId::new(id.parse()?);
+                Self::DeleteDefault { default_id }
+            }
+            [""] | [] => Self::Idle,
+            _ => return Err(ListDialogReferenceError::ReferenceError),
+        }
+        .pipe(Ok)
+    }
+}
+
+#[allow(clippy::to_string_trait_impl)]
+impl ToString for ListDialogReference {
+    fn to_string(&self) -> String {
+        match self {
+            ListDialogReference::DeleteContact { contact_id } => {
+                format!("delete_contact-{contact_id}")
+            }
+            ListDialogReference::DeleteDefault { default_id } => {
+                format!("delete_default-{default_id}")
+            }
+            ListDialogReference::Idle => String::new(),
+        }
+    }
+}
+
+#[component]
+pub fn ArchiveDialog(
+    dialog: ReadSignal<ActiveDialog>,
+    on_delete: Callback<()>,
+    on_close: Callback<()>,
+) -> Element {
+    match dialog() {
+        ActiveDialog::Idle => rsx! {},
+        ActiveDialog::DeleteContact(contact) => {
+            rsx! {
+                Dialog {
+                    ContactDelete {
+                        contact,
+                        on_cancel: on_close,
+                        on_delete: move |_contact| {
+                            on_delete(());
+                            on_close(());
+                        },
+                    }
+                }
+            }
+        }
+        ActiveDialog::DeleteDefault(default) => {
+            rsx! {
+                Dialog {
+                    DefaultDelete {
+                        default,
+                        on_cancel: on_close,
+                        on_delete: move |_default| {
+                            on_delete(());
+                            on_close(());
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::ops::Deref;
+
+use chrono::Local;
+use dioxus::prelude::*;
+use dioxus_fullstack::ServerFnError;
+
+use crate::{
+    components::buttons::NavButton,
+    functions::audit_log::get_audit_log_for_entity,
+    models::audit_log::{AuditEntityRef, AuditLogEntry, AuditLogId},
+};
+
+const PAGE_SIZE: i64 = 20;
+
+fn value_to_string(value: &Option<serde_json::Value>) -> String {
+    match value {
+        None | Some(serde_json::Value::Null) => String::new(),
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(value) => value.to_string(),
+    }
+}
+
+#[component]
+fn AuditLogRow(entry: AuditLogEntry) -> Element {
+    let changes = entry.changes();
+    let username = entry
+        .username
+        .clone()
+        .unwrap_or_else(|| "system".to_string());
+
+    rsx! {
+        tr { class: "border-blue-300 mt-2 mb-2 p-2 border-2 w-full sm:w-auto sm:border-none inline-block sm:table-row align-top",
+            td { class: "block sm:table-cell border-blue-300 sm:border-t-2",
+                {entry.inserted_at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string()}
+            }
+            td { class: "block sm:table-cell border-blue-300 sm:border-t-2",
+                span { class: "sm:hidden", "User: " }
+                {username}
+            }
+            td { class: "block sm:table-cell border-blue-300 sm:border-t-2",
+                span { class: "sm:hidden", "Entity: " }
+                {entry.entity.to_string()}
+            }
+            td { class: "block sm:table-cell border-blue-300 sm:border-t-2",
+                span { class: "sm:hidden", "Operation: " }
+                {entry.operation.to_string()}
+            }
+            td { class: "block sm:table-cell border-blue-300 sm:border-t-2",
+                for (field, before, after) in changes {
+                    div { class: "text-xs",
+                        span { class: "font-bold", {field} ": " }
+                        span { class: "line-through", {value_to_string(&before)} }
+                        " → "
+                        span { {value_to_string(&after)} }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn AuditLogTable(entries: Vec<AuditLogEntry>) -> Element {
+    if entries.is_empty() {
+        return rsx! {
+            p { class: "alert alert-info", "No changes recorded." }
+        };
+    }
+
+    rsx! {
+        table { class: "block sm:table w-full",
+            thead { class: "hidden sm:table-header-group",
+                tr {
+                    th { "Time" }
+                    th { "User" }
+                    th { "Entity" }
+                    th { "Operation" }
+                    th { "Changes" }
+                }
+            }
+            tbody { class: "block sm:table-row-group",
+                for entry in entries {
+                    AuditLogRow { key: "{entry.id}", entry }
+                }
+            }
+        }
+    }
+}
+
+/// Change history of a single entity, shown on its detail page.  Bump
+/// `version` to reload after the entity was changed; give the component a
+/// `key` of the entity so paging restarts for a different one.
+#[component]
+pub fn AuditLogSection(entity: ReadSignal<AuditEntityRef>, version: ReadSignal<u32>) -> Element {
+    let mut before_ids: Signal<Vec<AuditLogId>> = use_signal(Vec::new);
+
+    let entries: Resource<Result<Vec<AuditLogEntry>, ServerFnError>> =
+        use_resource(move || async move {
+            let _ = version();
+            let before_id = before_ids.read().last().copied();
+            get_audit_log_for_entity(entity(), before_id, PAGE_SIZE).await
+        });
+
+    rsx! {
+        div { class: "ml-2 mr-2 mt-4 sm:ml-0 sm:mr-0",
+            h2 { class: "text-lg font-bold mb-2", "History" }
+            match entries.read().deref() {
+                None => rsx! {
+                    p { class: "alert alert-info", "Loading history..." }
+                },
+                Some(Err(err)) => rsx! {
+                    div { class: "alert alert-error", "Error loading history: " {err.to_string()} }
+                },
+                Some(Ok(entries)) => {
+                    let has_next = entries.len() > PAGE_SIZE as usize;
+                    let visible = entries[..entries.len().min(PAGE_SIZE as usize)].to_vec();
+                    let last_id = visible.last().map(|entry| entry.id);
+
+                    rsx! {
+                        AuditLogTable { entries: visible }
+                        div { class: "flex gap-4 mt-4 ml-2",
+                            if !before_ids.read().is_empty() {
+                                NavButton {
+                                    on_click: move |_| {
+                                        before_ids.write().pop();
+                                    },
+                                    "Newer"
+                                }
+                            }
+                            if has_next {
+                                if let Some(last_id) = last_id {
+                                    NavButton {
+                                        on_click: move |_| before_ids.write().push(last_id),
+                                        "Older"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
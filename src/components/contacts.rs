@@ -1,21 +1,39 @@
 use std::{num::ParseIntError, str::FromStr};
 
+use chrono::{DateTime, Local, TimeDelta, Utc};
 use dioxus::prelude::*;
 use dioxus_router::ToQueryArgument;
+use gloo_timers::future::sleep;
 use tap::Pipe;
 use thiserror::Error;
 
 use crate::{
-    components::Markdown,
+    components::{
+        Markdown,
+        groups::{GroupSelect, parse_group},
+        imports::{ContactExport, ContactImport},
+    },
     forms::{
         Dialog, EditError, FieldValue, FormSaveCancelButton, InputString, InputTextArea, Saving,
         ValidationError, validate_action, validate_comments, validate_contact_name,
-        validate_phone_number,
+        validate_contact_number_label, validate_duration, validate_optional_action,
+        validate_phone_number, validate_tags,
+    },
+    functions::contacts::{
+        archive_contact, bulk_edit_contacts, create_contact, create_contact_number, delete_contact,
+        delete_contact_number, merge_contacts, update_contact,
     },
-    functions::contacts::{create_contact, delete_contact, update_contact},
     models::{
         common::MaybeSet,
-        contacts::{ChangeContact, Contact, ContactId, NewContact},
+        contacts::{
+            BulkEditContacts, BulkOperation, ChangeContact, Contact, ContactId, ContactNumber,
+            ContactNumberId, MergeContacts, MergeContactsPreview, NewContact, NewContactNumber,
+            TemporaryAction,
+        },
+        groups::GroupId,
+        imports::ImportReport,
+        search::SearchQuery,
+        tags::Tag,
     },
 };
 
@@ -31,6 +49,21 @@ struct Validate {
     name: Memo<Result<Option<String>, ValidationError>>,
     action: Memo<Result<String, ValidationError>>,
     comments: Memo<Result<Option<String>, ValidationError>>,
+    tags: Memo<Result<Vec<String>, ValidationError>>,
+    temporary_action: Memo<Result<Option<String>, ValidationError>>,
+    temporary_duration: Memo<Result<Option<TimeDelta>, ValidationError>>,
+    group: Memo<Result<Option<GroupId>, ValidationError>>,
+}
+
+/// The expiry of the temporary action the contact had when the form was
+/// opened, kept if no new duration is given.
+fn current_expiry(op: &Operation) -> Option<DateTime<Utc>> {
+    match op {
+        Operation::Create => None,
+        Operation::Update { contact } => contact
+            .active_temporary_action(Utc::now())
+            .map(|temporary| temporary.expires_at),
+    }
 }
 
 async fn do_save(op: &Operation, validate: &Validate) -> Result<Contact, EditError> {
@@ -38,6 +71,24 @@ async fn do_save(op: &Operation, validate: &Validate) -> Result<Contact, EditErr
     let name = validate.name.read().clone()?;
     let action = validate.action.read().clone()?;
     let comments = validate.comments.read().clone()?;
+    let tags = validate.tags.read().clone()?;
+    let temporary_action = validate.temporary_action.read().clone()?;
+    let temporary_duration = validate.temporary_duration.read().clone()?;
+    let group_id = validate.group.read().clone()?;
+
+    let temporary_action = match temporary_action {
+        None => None,
+        Some(action) => {
+            let expires_at = match (temporary_duration, current_expiry(op)) {
+                (Some(duration), _) => Utc::now() + duration,
+                (None, Some(expires_at)) => expires_at,
+                (None, None) => {
+                    return Err(ValidationError("Duration is required".to_string()).into());
+                }
+            };
+            Some(TemporaryAction { action, expires_at })
+        }
+    };
 
     match op {
         Operation::Create => {
@@ -46,6 +97,9 @@ async fn do_save(op: &Operation, validate: &Validate) -> Result<Contact, EditErr
                 name,
                 action,
                 comments,
+                tags,
+                temporary_action,
+                group_id,
             };
             create_contact(updates).await.map_err(EditError::Server)
         }
@@ -55,6 +109,9 @@ async fn do_save(op: &Operation, validate: &Validate) -> Result<Contact, EditErr
                 name: MaybeSet::Set(name),
                 action: MaybeSet::Set(action),
                 comments: MaybeSet::Set(comments),
+                tags: MaybeSet::Set(tags),
+                temporary_action: MaybeSet::Set(temporary_action),
+                group_id: MaybeSet::Set(group_id),
             };
             update_contact(contact.clone(), changes)
                 .await
@@ -85,11 +142,48 @@ pub fn ContactUpdate(op: Operation, on_cancel: Callback, on_save: Callback<Conta
         Operation::Update { contact } => contact.comments.as_raw(),
     });
 
+    let tags = use_signal(|| match &op {
+        Operation::Create => String::new(),
+        Operation::Update { contact } => contact.tag_names().join(", "),
+    });
+
+    let temporary_action = use_signal(|| match &op {
+        Operation::Create => String::new(),
+        Operation::Update { contact } => contact
+            .active_temporary_action(Utc::now())
+            .map(|temporary| temporary.action.clone())
+            .unwrap_or_default(),
+    });
+
+    let mut temporary_duration = use_signal(String::new);
+
+    let group = use_signal(|| match &op {
+        Operation::Create => String::new(),
+        Operation::Update { contact } => contact
+            .group_id
+            .map(|id| id.to_string())
+            .unwrap_or_default(),
+    });
+
+    let expiry = current_expiry(&op);
+
     let validate = Validate {
         phone_number: use_memo(move || validate_phone_number(&phone_number())),
         name: use_memo(move || validate_contact_name(&name())),
         action: use_memo(move || validate_action(&action())),
         comments: use_memo(move || validate_comments(&comments())),
+        tags: use_memo(move || validate_tags(&tags())),
+        temporary_action: use_memo(move || validate_optional_action(&temporary_action())),
+        temporary_duration: use_memo(move || {
+            let duration = validate_duration(&temporary_duration())?;
+            if duration.is_none() && expiry.is_none() && !temporary_action().trim().is_empty() {
+                return Err(ValidationError(
+                    "Duration is required for a temporary action".to_string(),
+                ));
+            }
+            Ok(duration)
+        }),
+        group: use_memo(move || Ok(parse_group(&group()))),
     };
 
     let mut saving = use_signal(|| Saving::No);
@@ -101,6 +195,9 @@ pub fn ContactUpdate(op: Operation, on_cancel: Callback, on_save: Callback<Conta
             || validate.name.read().is_err()
             || validate.action.read().is_err()
             || validate.comments.read().is_err()
+            || validate.tags.read().is_err()
+            || validate.temporary_action.read().is_err()
+            || validate.temporary_duration.read().is_err()
             || disabled()
     });
 
@@ -165,11 +262,51 @@ pub fn ContactUpdate(op: Operation, on_cancel: Callback, on_save: Callback<Conta
             }
             InputTextArea {
                 id: "comments",
-                label: "Comments",
+                label: "Comments (Markdown)",
                 value: comments,
                 validate: validate.comments,
                 disabled,
             }
+            InputString {
+                id: "tags",
+                label: "Tags (comma separated)",
+                value: tags,
+                validate: validate.tags,
+                disabled,
+            }
+            InputString {
+                id: "temporary_action",
+                label: "Temporary Action (optional)",
+                value: temporary_action,
+                validate: validate.temporary_action,
+                disabled,
+            }
+            InputString {
+                id: "temporary_duration",
+                label: "Temporary Action Duration (e.g. 90m, 12h, 3d, 2w)",
+                value: temporary_duration,
+                validate: validate.temporary_duration,
+                disabled,
+            }
+            div { class: "flex gap-2 mb-5",
+                for (title , duration) in [("1 hour", "1h"), ("1 day", "1d"), ("1 week", "1w")] {
+                    button {
+                        r#type: "button",
+                        class: "btn btn-sm btn-outline",
+                        disabled: disabled(),
+                        onclick: move |_e| temporary_duration.set(duration.to_string()),
+                        {title}
+                    }
+                }
+            }
+            if let Some(expires_at) = expiry {
+                p { class: "pb-4",
+                    "The temporary action "
+                    TemporaryActionCountdown { expires_at }
+                    ". Leave the duration empty to keep it, or clear the action to end it now."
+                }
+            }
+            GroupSelect { value: group, disabled }
 
             FormSaveCancelButton {
                 disabled: disabled_save,
@@ -185,6 +322,59 @@ pub fn ContactUpdate(op: Operation, on_cancel: Callback, on_save: Callback<Conta
     }
 }
 
+#[component]
+pub fn ContactArchive(
+    contact: Contact,
+    on_cancel: Callback,
+    on_archive: Callback<Contact>,
+) -> Element {
+    let mut saving = use_signal(|| Saving::No);
+
+    let disabled = use_memo(move || saving.read().is_saving());
+
+    let contact_clone = contact.clone();
+    let on_save = use_callback(move |()| {
+        let contact_clone = contact_clone.clone();
+        spawn(async move {
+            saving.set(Saving::Yes);
+
+            match archive_contact(contact_clone.clone()).await {
+                Ok(contact) => {
+                    saving.set(Saving::Finished(Ok(())));
+                    on_archive(contact);
+                }
+                Err(err) => saving.set(Saving::Finished(Err(EditError::Server(err)))),
+            }
+        });
+    });
+
+    rsx! {
+        h3 { class: "text-lg font-bold",
+            "Archive contact "
+            {contact.name.clone()}
+        }
+        p { class: "py-4", "The contact can be restored from the archive." }
+        ContactSummary { contact: contact.clone() }
+        form {
+            novalidate: true,
+            action: "javascript:void(0)",
+            method: "dialog",
+            onkeyup: move |event| {
+                if event.key() == Key::Escape {
+                    on_cancel(());
+                }
+            },
+            FormSaveCancelButton {
+                disabled,
+                on_save: move |()| on_save(()),
+                on_cancel: move |_| on_cancel(()),
+                title: "Archive",
+                saving,
+            }
+        }
+    }
+}
+
 #[component]
 pub fn ContactDelete(
     contact: Contact,
@@ -213,10 +403,10 @@ pub fn ContactDelete(
 
     rsx! {
         h3 { class: "text-lg font-bold",
-            "Delete contact "
+            "Permanently delete contact "
             {contact.name.clone()}
         }
-        p { class: "py-4", "Press ESC key or click the button below to close" }
+        p { class: "py-4", "The contact and its call history will be deleted. This cannot be undone." }
         ContactSummary { contact: contact.clone() }
         form {
             novalidate: true,
@@ -238,10 +428,516 @@ pub fn ContactDelete(
     }
 }
 
+/// Build the merge request from the per-field choices in [`ContactMerge`].
+///
+/// Each choice is an index into `contacts`; the survivor keeps its phone
+/// number and every other contact is merged into it.
+fn build_merge(
+    contacts: &[Contact],
+    survivor: usize,
+    name_from: usize,
+    action_from: usize,
+    comments_from: usize,
+) -> Option<MergeContacts> {
+    let survivor_id = contacts.get(survivor)?.id;
+    Some(MergeContacts {
+        survivor_id,
+        merged_ids: contacts
+            .iter()
+            .map(|c| c.id)
+            .filter(|id| *id != survivor_id)
+            .collect(),
+        name: contacts.get(name_from)?.name.clone(),
+        action: contacts.get(action_from)?.action.clone(),
+        comments: contacts.get(comments_from)?.comments.clone(),
+    })
+}
+
+#[component]
+pub fn ContactMerge(
+    preview: MergeContactsPreview,
+    on_cancel: Callback,
+    on_merge: Callback<Contact>,
+) -> Element {
+    let contacts = preview.contacts.clone();
+
+    let first_with_name = contacts.iter().position(|c| c.name.is_some());
+    let first_with_comments = contacts.iter().position(|c| c.comments.is_some());
+
+    let mut survivor = use_signal(|| 0usize);
+    let mut name_from = use_signal(|| first_with_name.unwrap_or(0));
+    let mut action_from = use_signal(|| 0usize);
+    let mut comments_from = use_signal(|| first_with_comments.unwrap_or(0));
+
+    let mut saving = use_signal(|| Saving::No);
+
+    let too_few = contacts.len() < 2;
+    let disabled = use_memo(move || saving.read().is_saving());
+    let disabled_save = use_memo(move || too_few || disabled());
+
+    let contacts_clone = contacts.clone();
+    let on_save = use_callback(move |()| {
+        let contacts = contacts_clone.clone();
+        spawn(async move {
+            saving.set(Saving::Yes);
+
+            let Some(merge) = build_merge(
+                &contacts,
+                survivor(),
+                name_from(),
+                action_from(),
+                comments_from(),
+            ) else {
+                saving.set(Saving::Finished(Err(EditError::Validation(
+                    ValidationError("Invalid selection".to_string()),
+                ))));
+                return;
+            };
+
+            match merge_contacts(merge).await {
+                Ok(contact) => {
+                    saving.set(Saving::Finished(Ok(())));
+                    on_merge(contact);
+                }
+                Err(err) => saving.set(Saving::Finished(Err(EditError::Server(err)))),
+            }
+        });
+    });
+
+    rsx! {
+        h3 { class: "text-lg font-bold", "Merge contacts" }
+        p { class: "py-4",
+            "Choose the contact to keep and where each field should come from. "
+            "All calls, numbers and tags are moved to the kept contact and the others are deleted."
+        }
+        if too_few {
+            p { class: "alert alert-error", "Select at least two contacts to merge." }
+        }
+        table { class: "table",
+            thead {
+                tr {
+                    th { "Keep" }
+                    th { "Phone Number" }
+                    th { "Name" }
+                    th { "Action" }
+                    th { "Comments" }
+                    th { "Calls" }
+                }
+            }
+            tbody {
+                for (i, contact) in contacts.iter().enumerate() {
+                    tr {
+                        td {
+                            input {
+                                r#type: "radio",
+                                class: "radio",
+                                name: "survivor",
+                                checked: survivor() == i,
+                                disabled,
+                                onchange: move |_| survivor.set(i),
+                            }
+                        }
+                        td { {contact.phone_number.clone()} }
+                        td {
+                            label { class: "flex gap-2",
+                                input {
+                                    r#type: "radio",
+                                    class: "radio",
+                                    name: "name",
+                                    checked: name_from() == i,
+                                    disabled,
+                                    onchange: move |_| name_from.set(i),
+                                }
+                                {contact.name.clone().unwrap_or_else(|| "(none)".to_string())}
+                            }
+                        }
+                        td {
+                            label { class: "flex gap-2",
+                                input {
+                                    r#type: "radio",
+                                    class: "radio",
+                                    name: "action",
+                                    checked: action_from() == i,
+                                    disabled,
+                                    onchange: move |_| action_from.set(i),
+                                }
+                                {contact.action.clone()}
+                            }
+                        }
+                        td {
+                            label { class: "flex gap-2",
+                                input {
+                                    r#type: "radio",
+                                    class: "radio",
+                                    name: "comments",
+                                    checked: comments_from() == i,
+                                    disabled,
+                                    onchange: move |_| comments_from.set(i),
+                                }
+                                {contact.comments.clone().unwrap_or_else(|| "(none)".to_string())}
+                            }
+                        }
+                        td { {contact.phone_call_count.to_string()} }
+                    }
+                }
+            }
+        }
+        p { class: "py-4",
+            "Combined calls: "
+            {preview.phone_call_count.to_string()}
+        }
+        form {
+            novalidate: true,
+            action: "javascript:void(0)",
+            method: "dialog",
+            onkeyup: move |event| {
+                if event.key() == Key::Escape {
+                    on_cancel(());
+                }
+            },
+            FormSaveCancelButton {
+                disabled: disabled_save,
+                on_save: move |()| on_save(()),
+                on_cancel: move |_| on_cancel(()),
+                title: "Merge",
+                saving,
+            }
+        }
+    }
+}
+
+#[component]
+pub fn ContactNumberCreate(
+    contact: Contact,
+    on_cancel: Callback,
+    on_save: Callback<ContactNumber>,
+) -> Element {
+    let phone_number = use_signal(String::new);
+    let label = use_signal(String::new);
+
+    let validate_number = use_memo(move || validate_phone_number(&phone_number()));
+    let validate_label = use_memo(move || validate_contact_number_label(&label()));
+
+    let mut saving = use_signal(|| Saving::No);
+
+    let disabled = use_memo(move || saving.read().is_saving());
+    let disabled_save = use_memo(move || {
+        validate_number.read().is_err() || validate_label.read().is_err() || disabled()
+    });
+
+    let contact_clone = contact.clone();
+    let on_save = use_callback(move |()| {
+        let contact = contact_clone.clone();
+        spawn(async move {
+            saving.set(Saving::Yes);
+
+            let number = match (validate_number(), validate_label()) {
+                (Ok(phone_number), Ok(label)) => NewContactNumber {
+                    contact_id: contact.id,
+                    phone_number,
+                    label,
+                },
+                (Err(err), _) | (_, Err(err)) => {
+                    saving.set(Saving::Finished(Err(EditError::Validation(err))));
+                    return;
+                }
+            };
+
+            match create_contact_number(number).await {
+                Ok(number) => {
+                    saving.set(Saving::Finished(Ok(())));
+                    on_save(number);
+                }
+                Err(err) => saving.set(Saving::Finished(Err(EditError::Server(err)))),
+            }
+        });
+    });
+
+    rsx! {
+        h3 { class: "text-lg font-bold",
+            {format!("Add Number to {}", contact.as_title())}
+        }
+        p { class: "py-4", "Press ESC key or click the button below to close" }
+        form {
+            novalidate: true,
+            action: "javascript:void(0)",
+            method: "dialog",
+            onkeyup: move |event| {
+                if event.key() == Key::Escape {
+                    on_cancel(());
+                }
+            },
+            InputString {
+                id: "phone_number",
+                label: "Phone Number",
+                value: phone_number,
+                validate: validate_number,
+                disabled,
+            }
+            InputString {
+                id: "label",
+                label: "Label (e.g. mobile, work, home)",
+                value: label,
+                validate: validate_label,
+                disabled,
+            }
+
+            FormSaveCancelButton {
+                disabled: disabled_save,
+                on_save: move |()| on_save(()),
+                on_cancel: move |()| on_cancel(()),
+                title: "Add",
+                saving,
+            }
+        }
+    }
+}
+
+#[component]
+pub fn ContactNumberDelete(
+    contact: Contact,
+    number: ContactNumber,
+    on_cancel: Callback,
+    on_delete: Callback<ContactNumber>,
+) -> Element {
+    let mut saving = use_signal(|| Saving::No);
+
+    let disabled = use_memo(move || saving.read().is_saving());
+
+    let contact_clone = contact.clone();
+    let number_clone = number.clone();
+    let on_save = use_callback(move |()| {
+        let contact = contact_clone.clone();
+        let number = number_clone.clone();
+        spawn(async move {
+            saving.set(Saving::Yes);
+
+            match delete_contact_number(contact.id, number.id).await {
+                Ok(_) => {
+                    saving.set(Saving::Finished(Ok(())));
+                    on_delete(number);
+                }
+                Err(err) => saving.set(Saving::Finished(Err(EditError::Server(err)))),
+            }
+        });
+    });
+
+    rsx! {
+        h3 { class: "text-lg font-bold",
+            {format!("Remove number {} from {}", number.phone_number, contact.as_title())}
+        }
+        p { class: "py-4", "Press ESC key or click the button below to close" }
+        form {
+            novalidate: true,
+            action: "javascript:void(0)",
+            method: "dialog",
+            onkeyup: move |event| {
+                if event.key() == Key::Escape {
+                    on_cancel(());
+                }
+            },
+            FormSaveCancelButton {
+                disabled,
+                on_save: move |()| on_save(()),
+                on_cancel: move |_| on_cancel(()),
+                title: "Remove",
+                saving,
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum BulkKind {
+    SetAction,
+    SetName,
+    ClearName,
+    AddComment,
+    Archive,
+}
+
+impl BulkKind {
+    const ALL: [BulkKind; 5] = [
+        BulkKind::SetAction,
+        BulkKind::SetName,
+        BulkKind::ClearName,
+        BulkKind::AddComment,
+        BulkKind::Archive,
+    ];
+
+    fn key(self) -> &'static str {
+        match self {
+            BulkKind::SetAction => "set_action",
+            BulkKind::SetName => "set_name",
+            BulkKind::ClearName => "clear_name",
+            BulkKind::AddComment => "add_comment",
+            BulkKind::Archive => "archive",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            BulkKind::SetAction => "Set action",
+            BulkKind::SetName => "Set name",
+            BulkKind::ClearName => "Clear name",
+            BulkKind::AddComment => "Add comment",
+            BulkKind::Archive => "Archive",
+        }
+    }
+
+    fn needs_value(self) -> bool {
+        matches!(
+            self,
+            BulkKind::SetAction | BulkKind::SetName | BulkKind::AddComment
+        )
+    }
+}
+
+fn validate_bulk_operation(kind: BulkKind, value: &str) -> Result<BulkOperation, ValidationError> {
+    match kind {
+        BulkKind::SetAction => validate_action(value).map(BulkOperation::SetAction),
+        BulkKind::SetName => match validate_contact_name(value)? {
+            Some(name) => Ok(BulkOperation::SetName(Some(name))),
+            None => Err(ValidationError("Name is required".to_string())),
+        },
+        BulkKind::ClearName => Ok(BulkOperation::SetName(None)),
+        BulkKind::AddComment => match validate_comments(value)? {
+            Some(comment) => Ok(BulkOperation::AddComment(comment)),
+            None => Err(ValidationError("Comment is required".to_string())),
+        },
+        BulkKind::Archive => Ok(BulkOperation::Archive),
+    }
+}
+
+#[component]
+pub fn ContactBulkEdit(
+    contacts: Vec<Contact>,
+    on_cancel: Callback,
+    on_save: Callback<usize>,
+) -> Element {
+    let mut kind = use_signal(|| BulkKind::SetAction);
+    let value = use_signal(String::new);
+
+    let validate = use_memo(move || validate_bulk_operation(kind(), &value()));
+
+    let mut saving = use_signal(|| Saving::No);
+
+    let disabled = use_memo(move || saving.read().is_saving());
+    let disabled_save = use_memo(move || validate.read().is_err() || disabled());
+
+    let contact_ids: Vec<ContactId> = contacts.iter().map(|c| c.id).collect();
+    let on_save = use_callback(move |()| {
+        let contact_ids = contact_ids.clone();
+        spawn(async move {
+            saving.set(Saving::Yes);
+
+            let operation = match validate() {
+                Ok(operation) => operation,
+                Err(err) => {
+                    saving.set(Saving::Finished(Err(EditError::Validation(err))));
+                    return;
+                }
+            };
+
+            let bulk = BulkEditContacts {
+                contact_ids,
+                operation,
+            };
+
+            match bulk_edit_contacts(bulk).await {
+                Ok(count) => {
+                    saving.set(Saving::Finished(Ok(())));
+                    on_save(count);
+                }
+                Err(err) => saving.set(Saving::Finished(Err(EditError::Server(err)))),
+            }
+        });
+    });
+
+    rsx! {
+        h3 { class: "text-lg font-bold",
+            {format!("Edit {} contacts", contacts.len())}
+        }
+        p { class: "py-4", "Press ESC key or click the button below to close" }
+        ul { class: "pb-4 max-h-40 overflow-y-auto",
+            for contact in contacts.iter() {
+                li { {contact.as_title()} }
+            }
+        }
+        form {
+            novalidate: true,
+            action: "javascript:void(0)",
+            method: "dialog",
+            onkeyup: move |event| {
+                if event.key() == Key::Escape {
+                    on_cancel(());
+                }
+            },
+            div { class: "mb-5",
+                select {
+                    class: "select",
+                    disabled,
+                    onchange: move |e| {
+                        if let Some(k) = BulkKind::ALL.into_iter().find(|k| k.key() == e.value()) {
+                            kind.set(k);
+                        }
+                    },
+                    for k in BulkKind::ALL {
+                        option { value: k.key(), selected: kind() == k, {k.title()} }
+                    }
+                }
+            }
+            match kind() {
+                BulkKind::AddComment => rsx! {
+                    InputTextArea {
+                        id: "value",
+                        label: "Comment",
+                        value,
+                        validate,
+                        disabled,
+                    }
+                },
+                k if k.needs_value() => rsx! {
+                    InputString {
+                        id: "value",
+                        label: if k == BulkKind::SetAction { "Action" } else { "Name" },
+                        value,
+                        validate,
+                        disabled,
+                    }
+                },
+                BulkKind::Archive => rsx! {
+                    p { class: "alert alert-warning mb-5",
+                        "The selected contacts will be archived. They can be restored from the archive."
+                    }
+                },
+                _ => rsx! {},
+            }
+
+            FormSaveCancelButton {
+                disabled: disabled_save,
+                on_save: move |()| on_save(()),
+                on_cancel: move |()| on_cancel(()),
+                title: "Apply",
+                saving,
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum ActiveDialog {
     Change(Operation),
-    Delete(Contact),
+    Archive(Contact),
+    Merge(MergeContactsPreview),
+    Bulk(Vec<Contact>),
+    CreateNumber(Contact),
+    DeleteNumber(Contact, ContactNumber),
+    Import,
+    Export {
+        query: SearchQuery,
+        tag: Option<String>,
+    },
     Idle,
 }
 
@@ -260,9 +956,23 @@ pub enum ListDialogReference {
     Update {
         contact_id: ContactId,
     },
-    Delete {
+    Archive {
         contact_id: ContactId,
     },
+    Merge {
+        contact_ids: Vec<ContactId>,
+    },
+    Bulk {
+        contact_ids: Vec<ContactId>,
+    },
+    CreateNumber {
+        contact_id: ContactId,
+    },
+    DeleteNumber {
+        contact_number_id: ContactNumberId,
+    },
+    Import,
+    Export,
     #[default]
     Idle,
 }
@@ -288,10 +998,34 @@ impl FromStr for ListDialogReference {
                 let contact_id = ContactId::new(id.parse()?);
                 Self::Update { contact_id }
             }
-            ["delete", id] => {
+            ["archive", id] => {
                 let contact_id = ContactId::new(id.parse()?);
-                Self::Delete { contact_id }
+                Self::Archive { contact_id }
             }
+            ["merge", ids] => {
+                let contact_ids = ids
+                    .split(',')
+                    .map(|id| id.parse::<i64>().map(ContactId::new))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Self::Merge { contact_ids }
+            }
+            ["bulk", ids] => {
+                let contact_ids = ids
+                    .split(',')
+                    .map(|id| id.parse::<i64>().map(ContactId::new))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Self::Bulk { contact_ids }
+            }
+            ["create_number", id] => {
+                let contact_id = ContactId::new(id.parse()?);
+                Self::CreateNumber { contact_id }
+            }
+            ["delete_number", id] => {
+                let contact_number_id = ContactNumberId::new(id.parse()?);
+                Self::DeleteNumber { contact_number_id }
+            }
+            ["import"] => Self::Import,
+            ["export"] => Self::Export,
             [""] | [] => Self::Idle,
             _ => return Err(ListDialogReferenceError::ReferenceError),
         }
@@ -305,7 +1039,31 @@ impl ToString for ListDialogReference {
         match self {
             ListDialogReference::Create => "create".to_string(),
             ListDialogReference::Update { contact_id } => format!("update-{contact_id}"),
-            ListDialogReference::Delete { contact_id } => format!("delete-{contact_id}"),
+            ListDialogReference::Archive { contact_id } => format!("archive-{contact_id}"),
+            ListDialogReference::Merge { contact_ids } => format!(
+                "merge-{}",
+                contact_ids
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+            ListDialogReference::Bulk { contact_ids } => format!(
+                "bulk-{}",
+                contact_ids
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+            ListDialogReference::CreateNumber { contact_id } => {
+                format!("create_number-{contact_id}")
+            }
+            ListDialogReference::DeleteNumber { contact_number_id } => {
+                format!("delete_number-{contact_number_id}")
+            }
+            ListDialogReference::Import => "import".to_string(),
+            ListDialogReference::Export => "export".to_string(),
             ListDialogReference::Idle => String::new(),
         }
     }
@@ -315,7 +1073,8 @@ impl ToString for ListDialogReference {
 pub fn ContactDialog(
     dialog: ReadSignal<ActiveDialog>,
     on_change: Callback<Contact>,
-    on_delete: Callback<Contact>,
+    on_archive: Callback<Contact>,
+    on_bulk_change: Option<Callback<usize>>,
 
     on_close: Callback<()>,
 ) -> Element {
@@ -335,20 +1094,102 @@ pub fn ContactDialog(
                 }
             }
         }
-        ActiveDialog::Delete(contact) => {
+        ActiveDialog::Archive(contact) => {
+            rsx! {
+                Dialog {
+                    ContactArchive {
+                        contact,
+                        on_cancel: on_close,
+                        on_archive: move |contact| {
+                            on_archive(contact);
+                            on_close(());
+                        },
+                    }
+                }
+            }
+        }
+        ActiveDialog::Merge(preview) => {
+            rsx! {
+                Dialog {
+                    ContactMerge {
+                        preview,
+                        on_cancel: on_close,
+                        on_merge: move |contact: Contact| {
+                            on_change(contact);
+                            on_close(());
+                        },
+                    }
+                }
+            }
+        }
+        ActiveDialog::Bulk(contacts) => {
             rsx! {
                 Dialog {
-                    ContactDelete {
+                    ContactBulkEdit {
+                        contacts,
+                        on_cancel: on_close,
+                        on_save: move |count: usize| {
+                            if let Some(on_bulk_change) = on_bulk_change {
+                                on_bulk_change(count);
+                            }
+                            on_close(());
+                        },
+                    }
+                }
+            }
+        }
+        ActiveDialog::CreateNumber(contact) => {
+            let contact_clone = contact.clone();
+            rsx! {
+                Dialog {
+                    ContactNumberCreate {
                         contact,
                         on_cancel: on_close,
-                        on_delete: move |contact| {
-                            on_delete(contact);
+                        on_save: move |_number| {
+                            on_change(contact_clone.clone());
                             on_close(());
                         },
                     }
                 }
             }
         }
+        ActiveDialog::DeleteNumber(contact, number) => {
+            let contact_clone = contact.clone();
+            rsx! {
+                Dialog {
+                    ContactNumberDelete {
+                        contact,
+                        number,
+                        on_cancel: on_close,
+                        on_delete: move |_number| {
+                            on_change(contact_clone.clone());
+                            on_close(());
+                        },
+                    }
+                }
+            }
+        }
+        ActiveDialog::Import => {
+            rsx! {
+                Dialog {
+                    ContactImport {
+                        on_cancel: on_close,
+                        on_import: move |report: ImportReport| {
+                            if let Some(on_bulk_change) = on_bulk_change {
+                                on_bulk_change(report.created + report.updated);
+                            }
+                        },
+                    }
+                }
+            }
+        }
+        ActiveDialog::Export { query, tag } => {
+            rsx! {
+                Dialog {
+                    ContactExport { query, tag, on_close }
+                }
+            }
+        }
     }
 }
 
@@ -360,12 +1201,29 @@ pub fn ContactSummary(contact: Contact) -> Element {
             span { "Calls: " }
             {contact.phone_call_count.to_string()}
         }
+        if let Some(last_call_at) = contact.last_call_at {
+            div {
+                span { "Last call: " }
+                {last_call_at.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()}
+            }
+        }
+        if let Some(first_call_at) = contact.first_call_at {
+            div {
+                span { "First call: " }
+                {first_call_at.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()}
+            }
+        }
         div {
             if let Some(name) = &contact.name {
                 {name.clone()}
             }
         }
-        div { {contact.action.clone()} }
+        div {
+            ContactAction { contact: contact.clone() }
+        }
+        div {
+            TagBadges { tags: contact.tags.clone() }
+        }
         div {
             if let Some(comments) = &contact.comments {
                 Markdown { content: comments.to_string() }
@@ -374,3 +1232,82 @@ pub fn ContactSummary(contact: Contact) -> Element {
 
     }
 }
+
+fn format_remaining(remaining: TimeDelta) -> String {
+    let total = remaining.num_seconds().max(0);
+    let (days, hours, minutes, seconds) = (
+        total / 86400,
+        total / 3600 % 24,
+        total / 60 % 60,
+        total % 60,
+    );
+    if days > 0 {
+        format!("{days}d {hours}h {minutes}m")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m {seconds}s")
+    } else {
+        format!("{minutes}m {seconds}s")
+    }
+}
+
+/// Live countdown to the expiry of a temporary action.
+#[component]
+pub fn TemporaryActionCountdown(expires_at: DateTime<Utc>) -> Element {
+    let mut now = use_signal(Utc::now);
+
+    // Effects only run in the browser, so the timer is never started while
+    // rendering on the server.
+    use_effect(move || {
+        spawn(async move {
+            loop {
+                sleep(std::time::Duration::from_secs(1)).await;
+                now.set(Utc::now());
+            }
+        });
+    });
+
+    let remaining = expires_at - now();
+
+    rsx! {
+        span {
+            if remaining > TimeDelta::zero() {
+                {format!("expires in {}", format_remaining(remaining))}
+            } else {
+                "has expired"
+            }
+        }
+    }
+}
+
+/// The action applied to calls, with any temporary action and its countdown.
+#[component]
+pub fn ContactAction(contact: Contact) -> Element {
+    let now = Utc::now();
+    let effective_action = contact.effective_action_at(now).to_string();
+    let temporary = contact.active_temporary_action(now).cloned();
+
+    rsx! {
+        {effective_action}
+        if let Some(temporary) = temporary {
+            span { class: "text-sm",
+                " (temporary, "
+                TemporaryActionCountdown { expires_at: temporary.expires_at }
+                {format!(", then {})", contact.effective_action_at(temporary.expires_at))}
+            }
+        }
+    }
+}
+
+#[component]
+pub fn TagBadges(tags: Vec<Tag>) -> Element {
+    rsx! {
+        for tag in tags {
+            span { class: "badge badge-outline mr-1",
+                {tag.name.clone()}
+                if let Some(action) = &tag.action {
+                    {format!(" ({action})")}
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use std::ops::Deref;
+
+use chrono::Local;
+use dioxus::prelude::*;
+use dioxus_router::navigator;
+
+use crate::{
+    Route,
+    components::contacts::{ContactAction, ListDialogReference},
+    functions::phone_calls::{get_call_dashboard, get_call_heatmap},
+    models::{
+        contacts::{Contact, ContactId},
+        dashboard::{ActionCount, CallDashboard, CallHeatmap, total_calls, voicemail_percentage},
+    },
+};
+
+fn action_color(action: &str) -> &'static str {
+    match action {
+        "allow" => "bg-green-500",
+        "voicemail" => "bg-red-500",
+        _ => "bg-gray-400",
+    }
+}
+
+fn view_contact(contact: &Contact) {
+    navigator().push(Route::ContactDetail {
+        contact_id: contact.id,
+        dialog: ListDialogReference::Idle,
+        from_date: None,
+        to_date: None,
+        action: None,
+        dest: None,
+        before_ts: None,
+        before_id: None,
+    });
+}
+
+#[component]
+fn PeriodCard(title: &'static str, counts: Vec<ActionCount>) -> Element {
+    let voicemail = voicemail_percentage(&counts);
+
+    rsx! {
+        div { class: "card bg-base-200 p-4 flex-1 min-w-48",
+            h3 { class: "font-bold", {title} }
+            p { class: "text-3xl", {total_calls(&counts).to_string()} }
+            for count in counts {
+                div { class: "flex items-center gap-2",
+                    span { class: "inline-block w-3 h-3 rounded {action_color(&count.action)}" }
+                    {format!("{}: {}", count.action, count.count)}
+                }
+            }
+            if let Some(voicemail) = voicemail {
+                p { class: "mt-2", {format!("{voicemail:.0}% to voicemail")} }
+            }
+        }
+    }
+}
+
+/// Bar per day, stacked by action.
+#[component]
+fn DailyChart(dashboard: CallDashboard) -> Element {
+    let days = dashboard.days();
+    let max = days
+        .iter()
+        .map(|(_, counts)| total_calls(counts))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    rsx! {
+        div { class: "flex items-end gap-px h-40 w-full border-b border-gray-400",
+            for (date , counts) in days {
+                div {
+                    key: "{date}",
+                    class: "flex flex-col-reverse flex-1 h-full",
+                    title: format!(
+                        "{}: {}",
+                        date.format("%Y-%m-%d"),
+                        counts
+                            .iter()
+                            .map(|c| format!("{} {}", c.count, c.action))
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    ),
+                    for count in counts {
+                        div {
+                            class: action_color(&count.action),
+                            style: format!("height: {}%", count.count as f64 * 100.0 / max as f64),
+                        }
+                    }
+                }
+            }
+        }
+        div { class: "flex justify-between text-sm",
+            span { {dashboard.first_day.format("%Y-%m-%d").to_string()} }
+            span { {dashboard.last_day.format("%Y-%m-%d").to_string()} }
+        }
+    }
+}
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Grid of weekdays by hours for one action, darker for more calls.
+#[component]
+fn HeatmapGrid(heatmap: CallHeatmap, action: String) -> Element {
+    let grid = heatmap.grid(&action);
+    let total: i64 = grid.iter().flatten().sum();
+    let max = grid.iter().flatten().copied().max().unwrap_or(0).max(1);
+
+    rsx! {
+        div { class: "mb-4 overflow-x-auto",
+            h3 { class: "font-bold", {format!("{action}: {total} calls")} }
+            table { class: "text-xs",
+                thead {
+                    tr {
+                        th {}
+                        for hour in 0..24 {
+                            th { class: "font-normal w-6", {format!("{hour:02}")} }
+                        }
+                    }
+                }
+                tbody {
+                    for (weekday , hours) in WEEKDAYS.iter().zip(grid) {
+                        tr { key: "{weekday}",
+                            th { class: "font-normal pr-2 text-left", {weekday.to_string()} }
+                            for (hour , count) in hours.into_iter().enumerate() {
+                                td {
+                                    class: "h-5 border border-base-300 {action_color(&action)}",
+                                    style: format!("opacity: {}", count as f64 / max as f64),
+                                    title: format!("{weekday} {hour:02}:00: {count} {action}"),
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Calls by local hour of the week and action, for one contact or for all
+/// calls.
+#[component]
+pub fn CallHeatmapChart(contact_id: ReadSignal<Option<ContactId>>) -> Element {
+    let heatmap = use_resource(move || async move {
+        get_call_heatmap(contact_id(), Local::now().offset().local_minus_utc()).await
+    });
+
+    match heatmap.read().deref() {
+        None => rsx! {
+            p { class: "alert alert-info", "Loading heatmap..." }
+        },
+        Some(Err(err)) => rsx! {
+            div { class: "alert alert-error", "Error loading heatmap: " {err.to_string()} }
+        },
+        Some(Ok(heatmap)) if heatmap.hourly.is_empty() => rsx! {
+            p { "No calls." }
+        },
+        Some(Ok(heatmap)) => rsx! {
+            for action in heatmap.actions() {
+                HeatmapGrid { key: "{action}", heatmap: heatmap.clone(), action }
+            }
+        },
+    }
+}
+
+#[component]
+fn ContactRow(contact: Contact, detail: String) -> Element {
+    rsx! {
+        tr {
+            class: "hover:bg-gray-500 cursor-pointer",
+            onclick: {
+                let contact = contact.clone();
+                move |_| view_contact(&contact)
+            },
+            td { class: "border-blue-300 border-t-2", {contact.phone_number.clone()} }
+            td { class: "border-blue-300 border-t-2",
+                if let Some(name) = &contact.name {
+                    {name.clone()}
+                }
+            }
+            td { class: "border-blue-300 border-t-2",
+                ContactAction { contact: contact.clone() }
+            }
+            td { class: "border-blue-300 border-t-2", {detail} }
+        }
+    }
+}
+
+/// Call statistics for logged in users.
+#[component]
+pub fn Dashboard() -> Element {
+    let dashboard = use_resource(move || async move {
+        get_call_dashboard(Local::now().offset().local_minus_utc()).await
+    });
+
+    match dashboard.read().deref() {
+        None => rsx! {
+            p { class: "alert alert-info", "Loading statistics..." }
+        },
+        Some(Err(err)) => rsx! {
+            div { class: "alert alert-error", "Error loading statistics: " {err.to_string()} }
+        },
+        Some(Ok(dashboard)) => rsx! {
+            div { class: "flex flex-wrap gap-4 mb-4",
+                PeriodCard { title: "Today", counts: dashboard.today.clone() }
+                PeriodCard { title: "This week", counts: dashboard.this_week.clone() }
+                PeriodCard { title: "This month", counts: dashboard.this_month.clone() }
+            }
+
+            h2 { class: "text-lg font-bold", "Calls per day" }
+            div { class: "mb-4",
+                DailyChart { dashboard: dashboard.clone() }
+            }
+
+            div { class: "flex flex-wrap gap-4",
+                div { class: "flex-1 min-w-72",
+                    h2 { class: "text-lg font-bold", "Top callers, last 30 days" }
+                    if dashboard.top_callers.is_empty() {
+                        p { "No calls." }
+                    } else {
+                        table { class: "w-full",
+                            tbody {
+                                for (contact , count) in dashboard.top_callers.clone() {
+                                    ContactRow {
+                                        key: "{contact.id}",
+                                        contact,
+                                        detail: format!("{count} calls"),
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                div { class: "flex-1 min-w-72",
+                    h2 { class: "text-lg font-bold", "New numbers, last 7 days" }
+                    if dashboard.new_numbers.is_empty() {
+                        p { "No new numbers." }
+                    } else {
+                        table { class: "w-full",
+                            tbody {
+                                for contact in dashboard.new_numbers.clone() {
+                                    ContactRow {
+                                        key: "{contact.id}",
+                                        detail: contact
+                                            .first_call_at
+                                            .map(|at| at.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+                                            .unwrap_or_default(),
+                                        contact,
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        },
+    }
+}
@@ -6,14 +6,16 @@ use tap::Pipe;
 use thiserror::Error;
 
 use crate::{
+    components::groups::{GroupSelect, parse_group},
     forms::{
         Dialog, EditError, FieldValue, FormSaveCancelButton, InputString, Saving, ValidationError,
         validate_action, validate_default_name, validate_regex,
     },
-    functions::defaults::{create_default, delete_default, update_default},
+    functions::defaults::{archive_default, create_default, delete_default, update_default},
     models::{
         common::MaybeSet,
         defaults::{ChangeDefault, Default, DefaultId, NewDefault},
+        groups::GroupId,
     },
 };
 
@@ -33,6 +35,7 @@ struct Validate {
     regexp: Memo<Result<Option<String>, ValidationError>>,
     name: Memo<Result<Option<String>, ValidationError>>,
     action: Memo<Result<String, ValidationError>>,
+    group: Memo<Result<Option<GroupId>, ValidationError>>,
 }
 
 async fn do_save(op: &Operation, validate: &Validate) -> Result<Default, EditError> {
@@ -40,6 +43,7 @@ async fn do_save(op: &Operation, validate: &Validate) -> Result<Default, EditErr
     let regexp = validate.regexp.read().clone()?;
     let name = validate.name.read().clone()?;
     let action = validate.action.read().clone()?;
+    let group_id = validate.group.read().clone()?;
 
     match op {
         Operation::Create => {
@@ -48,6 +52,7 @@ async fn do_save(op: &Operation, validate: &Validate) -> Result<Default, EditErr
                 regexp,
                 name,
                 action,
+                group_id,
             };
             create_default(new_default).await.map_err(EditError::Server)
         }
@@ -58,6 +63,7 @@ async fn do_save(op: &Operation, validate: &Validate) -> Result<Default, EditErr
                 regexp: MaybeSet::Set(regexp),
                 name: MaybeSet::Set(name),
                 action: MaybeSet::Set(action),
+                group_id: MaybeSet::Set(group_id),
             };
             update_default(default.clone(), changes)
                 .await
@@ -88,11 +94,20 @@ pub fn DefaultUpdate(op: Operation, on_cancel: Callback, on_save: Callback<Defau
         Operation::Update { default } => default.action.as_raw(),
     });
 
+    let group = use_signal(|| match &op {
+        Operation::Create => String::new(),
+        Operation::Update { default } => default
+            .group_id
+            .map(|id| id.to_string())
+            .unwrap_or_default(),
+    });
+
     let validate = Validate {
         order: use_memo(move || validate_optional_order(&order())),
         regexp: use_memo(move || validate_regex(&regexp())),
         name: use_memo(move || validate_default_name(&name())),
         action: use_memo(move || validate_action(&action())),
+        group: use_memo(move || Ok(parse_group(&group()))),
     };
 
     let mut saving = use_signal(|| Saving::No);
@@ -171,6 +186,7 @@ pub fn DefaultUpdate(op: Operation, on_cancel: Callback, on_save: Callback<Defau
                 validate: validate.action,
                 disabled,
             }
+            GroupSelect { value: group, disabled }
 
             FormSaveCancelButton {
                 disabled: disabled_save,
@@ -186,6 +202,59 @@ pub fn DefaultUpdate(op: Operation, on_cancel: Callback, on_save: Callback<Defau
     }
 }
 
+#[component]
+pub fn DefaultArchive(
+    default: Default,
+    on_cancel: Callback,
+    on_archive: Callback<Default>,
+) -> Element {
+    let mut saving = use_signal(|| Saving::No);
+
+    let disabled = use_memo(move || saving.read().is_saving());
+
+    let default_clone = default.clone();
+    let on_save = use_callback(move |()| {
+        let default_clone = default_clone.clone();
+        spawn(async move {
+            saving.set(Saving::Yes);
+
+            match archive_default(default_clone.clone()).await {
+                Ok(default) => {
+                    saving.set(Saving::Finished(Ok(())));
+                    on_archive(default);
+                }
+                Err(err) => saving.set(Saving::Finished(Err(EditError::Server(err)))),
+            }
+        });
+    });
+
+    rsx! {
+        h3 { class: "text-lg font-bold",
+            "Archive default "
+            {default.as_title()}
+        }
+        p { class: "py-4", "The default can be restored from the archive." }
+        DefaultSummary { default: default.clone() }
+        form {
+            novalidate: true,
+            action: "javascript:void(0)",
+            method: "dialog",
+            onkeyup: move |event| {
+                if event.key() == Key::Escape {
+                    on_cancel(());
+                }
+            },
+            FormSaveCancelButton {
+                disabled,
+                on_save: move |()| on_save(()),
+                on_cancel: move |_| on_cancel(()),
+                title: "Archive",
+                saving,
+            }
+        }
+    }
+}
+
 #[component]
 pub fn DefaultDelete(
     default: Default,
@@ -214,10 +283,10 @@ pub fn DefaultDelete(
 
     rsx! {
         h3 { class: "text-lg font-bold",
-            "Delete default "
+            "Permanently delete default "
             {default.as_title()}
         }
-        p { class: "py-4", "Press ESC key or click the button below to close" }
+        p { class: "py-4", "This cannot be undone." }
         DefaultSummary { default: default.clone() }
         form {
             novalidate: true,
@@ -242,7 +311,7 @@ pub fn DefaultDelete(
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub enum ActiveDialog {
     Change(Operation),
-    Delete(Default),
+    Archive(Default),
     Idle,
 }
 
@@ -261,7 +330,7 @@ pub enum ListDialogReference {
     Update {
         default_id: DefaultId,
     },
-    Delete {
+    Archive {
         default_id: DefaultId,
     },
     #[default]
@@ -289,9 +358,9 @@ impl FromStr for ListDialogReference {
                 let default_id = DefaultId::new(id.parse()?);
                 Self::Update { default_id }
             }
-            ["delete", id] => {
+            ["archive", id] => {
                 let default_id = DefaultId::new(id.parse()?);
-                Self::Delete { default_id }
+                Self::Archive { default_id }
             }
             [""] | [] => Self::Idle,
             _ => return Err(ListDialogReferenceError::ReferenceError),
@@ -306,7 +375,7 @@ impl ToString for ListDialogReference {
         match self {
             ListDialogReference::Create => "create".to_string(),
             ListDialogReference::Update { default_id } => format!("update-{default_id}"),
-            ListDialogReference::Delete { default_id } => format!("delete-{default_id}"),
+            ListDialogReference::Archive { default_id } => format!("archive-{default_id}"),
             ListDialogReference::Idle => String::new(),
         }
     }
@@ -316,7 +385,7 @@ impl ToString for ListDialogReference {
 pub fn DefaultDialog(
     dialog: ReadSignal<ActiveDialog>,
     on_change: Callback<Default>,
-    on_delete: Callback<Default>,
+    on_archive: Callback<Default>,
     on_close: Callback<()>,
 ) -> Element {
     match dialog() {
@@ -335,14 +404,14 @@ pub fn DefaultDialog(
                 }
             }
         }
-        ActiveDialog::Delete(default) => {
+        ActiveDialog::Archive(default) => {
             rsx! {
                 Dialog {
-                    DefaultDelete {
+                    DefaultArchive {
                         default,
                         on_cancel: on_close,
-                        on_delete: move |default| {
-                            on_delete(default);
+                        on_archive: move |default| {
+                            on_archive(default);
                             on_close(());
                         },
                     }
@@ -0,0 +1,42 @@
+use std::ops::Deref;
+
+use dioxus::prelude::*;
+
+use crate::{forms::InputSelect, functions::groups::get_my_groups, models::groups::GroupId};
+
+/// The group id held by a [`GroupSelect`] value, `None` for no group.
+pub fn parse_group(value: &str) -> Option<GroupId> {
+    value.parse().ok()
+}
+
+/// Pick the group a record belongs to, out of those the user is in.  Not
+/// shown at all when there are no groups to choose from.
+#[component]
+pub fn GroupSelect(value: Signal<String>, disabled: Memo<bool>) -> Element {
+    let groups = use_resource(move || async move { get_my_groups().await });
+
+    match groups.read().deref() {
+        Some(Ok(groups)) if !groups.is_empty() || !value().is_empty() => {
+            let mut options = vec![(String::new(), "(visible to everyone)".to_string())];
+            options.extend(
+                groups
+                    .iter()
+                    .map(|group| (group.id.to_string(), group.name.clone())),
+            );
+
+            rsx! {
+                InputSelect {
+                    id: "group",
+                    label: "Group",
+                    value,
+                    options,
+                    disabled,
+                }
+            }
+        }
+        Some(Err(err)) => rsx! {
+            div { class: "alert alert-error mb-5", "Error loading groups: " {err.to_string()} }
+        },
+        _ => rsx! {},
+    }
+}
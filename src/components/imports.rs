@@ -0,0 +1,272 @@
+use base64::Engine;
+use dioxus::prelude::*;
+use dioxus_fullstack::ServerFnError;
+
+use crate::{
+    forms::{EditError, FormCloseButton, FormSaveCancelButton, Saving},
+    functions::imports::{export_csv, export_vcards, import_csv, import_vcards},
+    models::{imports::ImportReport, search::SearchQuery},
+};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum FileFormat {
+    VCard,
+    Csv,
+}
+
+impl FileFormat {
+    pub const ALL: [FileFormat; 2] = [FileFormat::VCard, FileFormat::Csv];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            FileFormat::VCard => "vcard",
+            FileFormat::Csv => "csv",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            FileFormat::VCard => "vCard",
+            FileFormat::Csv => "CSV",
+        }
+    }
+
+    pub fn mime(self) -> &'static str {
+        match self {
+            FileFormat::VCard => "text/vcard",
+            FileFormat::Csv => "text/csv",
+        }
+    }
+
+    pub fn filename(self) -> &'static str {
+        match self {
+            FileFormat::VCard => "contacts.vcf",
+            FileFormat::Csv => "contacts.csv",
+        }
+    }
+
+    pub fn accept(self) -> &'static str {
+        match self {
+            FileFormat::VCard => ".vcf,text/vcard",
+            FileFormat::Csv => ".csv,text/csv",
+        }
+    }
+}
+
+#[component]
+fn FileFormatSelect(mut format: Signal<FileFormat>, disabled: Memo<bool>) -> Element {
+    rsx! {
+        div { class: "mb-5",
+            select {
+                class: "select",
+                disabled,
+                onchange: move |e| {
+                    if let Some(f) = FileFormat::ALL.into_iter().find(|f| f.key() == e.value()) {
+                        format.set(f);
+                    }
+                },
+                for f in FileFormat::ALL {
+                    option { value: f.key(), selected: format() == f, {f.title()} }
+                }
+            }
+        }
+    }
+}
+
+/// Offer generated file content for download without another round trip.
+#[component]
+pub fn DownloadLink(content: String, mime: String, filename: String) -> Element {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(content.as_bytes());
+
+    rsx! {
+        a {
+            class: "w-full btn btn-primary my-2",
+            href: "data:{mime};charset=utf-8;base64,{encoded}",
+            download: "{filename}",
+            {format!("Download {filename}")}
+        }
+    }
+}
+
+#[component]
+pub fn ImportReportSummary(report: ImportReport) -> Element {
+    rsx! {
+        if report.dry_run {
+            div { class: "alert alert-warning mb-2", "Nothing was saved." }
+        }
+        div { class: "alert alert-success mb-2",
+            {format!(
+                "{} created, {} updated, {} unchanged.",
+                report.created,
+                report.updated,
+                report.unchanged,
+            )}
+        }
+        if !report.conflicts.is_empty() {
+            h4 { class: "font-bold", {format!("{} conflicts", report.conflicts.len())} }
+            table { class: "table table-striped mb-2",
+                tbody {
+                    for conflict in report.conflicts.iter() {
+                        tr {
+                            td { {conflict.item.clone()} }
+                            td { {conflict.reason.clone()} }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn ContactExport(query: SearchQuery, tag: Option<String>, on_close: Callback) -> Element {
+    let format = use_signal(|| FileFormat::VCard);
+    let disabled = use_memo(|| false);
+
+    let export = use_resource(move || {
+        let query = query.clone();
+        let tag = tag.clone();
+        async move {
+            match format() {
+                FileFormat::VCard => export_vcards(query, tag).await,
+                FileFormat::Csv => export_csv(query, tag).await,
+            }
+        }
+    });
+
+    rsx! {
+        h3 { class: "text-lg font-bold", "Export contacts" }
+        p { class: "py-4", "Contacts matching the current search are exported." }
+        FileFormatSelect { format, disabled }
+        match export() {
+            Some(Ok(content)) => rsx! {
+                DownloadLink {
+                    content,
+                    mime: format().mime(),
+                    filename: format().filename(),
+                }
+            },
+            Some(Err(err)) => rsx! {
+                div { class: "alert alert-error",
+                    "Error: "
+                    {err.to_string()}
+                }
+            },
+            None => rsx! {
+                div { class: "alert alert-info", "Exporting..." }
+            },
+        }
+        FormCloseButton { title: "Close", on_close }
+    }
+}
+
+#[component]
+pub fn ContactImport(on_cancel: Callback, on_import: Callback<ImportReport>) -> Element {
+    let format = use_signal(|| FileFormat::VCard);
+    let mut content: Signal<Option<String>> = use_signal(|| None);
+    let mut report: Signal<Option<ImportReport>> = use_signal(|| None);
+    let mut check: Signal<Option<ImportReport>> = use_signal(|| None);
+    let mut saving = use_signal(|| Saving::No);
+
+    let disabled = use_memo(move || saving.read().is_saving());
+    let disabled_save = use_memo(move || content.read().is_none() || disabled());
+
+    let on_save = use_callback(move |dry_run: bool| {
+        let Some(data) = content() else {
+            return;
+        };
+        spawn(async move {
+            saving.set(Saving::Yes);
+            let result = match format() {
+                FileFormat::VCard => import_vcards(data).await,
+                FileFormat::Csv => import_csv(data, dry_run).await,
+            };
+            match result {
+                Ok(result) if result.dry_run => {
+                    saving.set(Saving::No);
+                    check.set(Some(result));
+                }
+                Ok(result) => {
+                    saving.set(Saving::Finished(Ok(())));
+                    report.set(Some(result.clone()));
+                    on_import(result);
+                }
+                Err(err) => saving.set(Saving::Finished(Err(EditError::Server(err)))),
+            }
+        });
+    });
+
+    let description = match format() {
+        FileFormat::VCard => {
+            "Contacts are matched by phone number. New numbers create contacts, known numbers fill in missing details."
+        }
+        FileFormat::Csv => {
+            "Contacts are matched by phone number and updated from the file. Check the file first to see what would change."
+        }
+    };
+
+    if let Some(report) = report() {
+        return rsx! {
+            h3 { class: "text-lg font-bold", "Import contacts" }
+            ImportReportSummary { report }
+            FormCloseButton { title: "Close", on_close: on_cancel }
+        };
+    }
+
+    rsx! {
+        h3 { class: "text-lg font-bold", "Import contacts" }
+        p { class: "py-4", {description} }
+        form {
+            novalidate: true,
+            action: "javascript:void(0)",
+            method: "dialog",
+            onkeyup: move |event| {
+                if event.key() == Key::Escape {
+                    on_cancel(());
+                }
+            },
+            FileFormatSelect { format, disabled }
+            div { class: "mb-5",
+                input {
+                    r#type: "file",
+                    class: "file-input",
+                    accept: format().accept(),
+                    disabled,
+                    onchange: move |e: FormEvent| async move {
+                        content.set(None);
+                        check.set(None);
+                        if let Some(file) = e.files().into_iter().next() {
+                            match file.read_string().await {
+                                Ok(text) => content.set(Some(text)),
+                                Err(err) => {
+                                    saving.set(Saving::Finished(Err(EditError::Server(
+                                        ServerFnError::new(err.to_string()),
+                                    ))))
+                                }
+                            }
+                        }
+                    },
+                }
+            }
+            if let Some(check) = check() {
+                ImportReportSummary { report: check }
+            }
+            if format() == FileFormat::Csv {
+                button {
+                    r#type: "button",
+                    class: "w-full btn btn-secondary my-2",
+                    disabled: disabled_save(),
+                    onclick: move |_e| on_save(true),
+                    "Check"
+                }
+            }
+            FormSaveCancelButton {
+                disabled: disabled_save,
+                on_save: move |()| on_save(false),
+                on_cancel: move |()| on_cancel(()),
+                title: "Import",
+                saving,
+            }
+        }
+    }
+}
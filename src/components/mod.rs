@@ -1,7 +1,15 @@
+pub mod archive;
+pub mod audit_log;
 pub mod buttons;
 pub mod contacts;
+pub mod dashboard;
 pub mod defaults;
+pub mod groups;
+pub mod imports;
 pub mod navbar;
+pub mod notes;
+pub mod phone_calls;
+pub mod tags;
 pub mod users;
 
 use dioxus::prelude::*;
@@ -1,4 +1,8 @@
-use crate::{Route, use_user};
+use crate::{
+    Route,
+    models::{contacts::ContactSort, search::SearchQuery},
+    use_user,
+};
 use dioxus::prelude::*;
 use dioxus_router::{Link, Outlet, use_route};
 
@@ -83,10 +87,15 @@ pub fn Navbar() -> Element {
                             MenuItem {
                                 route: Route::ContactList {
                                     dialog: crate::components::contacts::ListDialogReference::Idle,
-                                    q: String::new(),
+                                    q: SearchQuery::default(),
+                                    tag: String::new(),
+                                    sort: ContactSort::default(),
                                     before_id: None,
                                     before_name: None,
                                     before_name_null: false,
+                                    before_count: None,
+                                    before_ts: None,
+                                    before_phone_number: None,
                                 },
                                 title: "Contacts",
                                 show_menu,
@@ -94,13 +103,22 @@ pub fn Navbar() -> Element {
                             MenuItem {
                                 route: Route::PhoneCallList {
                                     dialog: crate::components::contacts::ListDialogReference::Idle,
-                                    q: String::new(),
+                                    q: SearchQuery::default(),
+                                    from_date: None,
+                                    to_date: None,
+                                    action: None,
+                                    dest: None,
                                     before_ts: None,
                                     before_id: None,
                                 },
                                 title: "Phone Calls",
                                 show_menu,
                             }
+                            MenuItem {
+                                route: Route::Heatmap {},
+                                title: "Heatmap",
+                                show_menu,
+                            }
                             MenuItem {
                                 route: Route::DefaultList {
                                     dialog: crate::components::defaults::ListDialogReference::Idle,
@@ -108,6 +126,20 @@ pub fn Navbar() -> Element {
                                 title: "Defaults",
                                 show_menu,
                             }
+                            MenuItem {
+                                route: Route::TagList {
+                                    dialog: crate::components::tags::ListDialogReference::Idle,
+                                },
+                                title: "Tags",
+                                show_menu,
+                            }
+                            MenuItem {
+                                route: Route::ArchiveList {
+                                    dialog: crate::components::archive::ListDialogReference::Idle,
+                                },
+                                title: "Archive",
+                                show_menu,
+                            }
                             if user.is_admin {
                                 MenuItem {
                                     route: Route::UserList {
@@ -116,6 +148,22 @@ pub fn Navbar() -> Element {
                                     title: "Users",
                                     show_menu,
                                 }
+                                MenuItem {
+                                    route: Route::AuditLogList {
+                                        user_id: None,
+                                        entity: None,
+                                        from_date: None,
+                                        to_date: None,
+                                        before_id: None,
+                                    },
+                                    title: "Audit Log",
+                                    show_menu,
+                                }
+                                MenuItem {
+                                    route: Route::LdapReconcile {},
+                                    title: "LDAP",
+                                    show_menu,
+                                }
                             }
                             MenuItem {
                                 route: Route::Logout {},
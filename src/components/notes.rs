@@ -0,0 +1,130 @@
+use std::ops::Deref;
+
+use chrono::Local;
+use dioxus::prelude::*;
+use dioxus_fullstack::ServerFnError;
+
+use crate::{
+    components::{Markdown, buttons::ChangeButton},
+    functions::contacts::{get_note_revisions, restore_note_revision},
+    models::contacts::{Contact, ContactId, ContactNoteRevision},
+};
+
+#[component]
+fn NoteRevisionRow(
+    revision: ContactNoteRevision,
+    is_current: bool,
+    on_restore: Callback<Contact>,
+    error: Signal<Option<String>>,
+) -> Element {
+    let revision_id = revision.id;
+    let username = revision
+        .username
+        .clone()
+        .unwrap_or_else(|| "system".to_string());
+
+    rsx! {
+        tr { class: "border-blue-300 mt-2 mb-2 p-2 border-2 w-full sm:w-auto sm:border-none inline-block sm:table-row align-top",
+            td { class: "block sm:table-cell border-blue-300 sm:border-t-2",
+                {revision.inserted_at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string()}
+            }
+            td { class: "block sm:table-cell border-blue-300 sm:border-t-2",
+                span { class: "sm:hidden", "User: " }
+                {username}
+            }
+            td { class: "block sm:table-cell border-blue-300 sm:border-t-2",
+                match &revision.comments {
+                    Some(comments) => rsx! {
+                        Markdown { content: comments.clone() }
+                    },
+                    None => rsx! {
+                        span { class: "italic", "(cleared)" }
+                    },
+                }
+            }
+            td { class: "block sm:table-cell border-blue-300 sm:border-t-2",
+                if is_current {
+                    span { class: "badge", "Current" }
+                } else {
+                    ChangeButton {
+                        on_click: move |_| {
+                            spawn(async move {
+                                match restore_note_revision(revision_id).await {
+                                    Ok(contact) => {
+                                        error.set(None);
+                                        on_restore(contact);
+                                    }
+                                    Err(err) => error.set(Some(err.to_string())),
+                                }
+                            });
+                        },
+                        "Restore"
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Earlier versions of a contact's notes, with a button to restore each.
+/// Bump `version` to reload after the contact was changed.
+#[component]
+pub fn NoteRevisionsSection(
+    contact_id: ReadSignal<ContactId>,
+    version: ReadSignal<u32>,
+    on_restore: Callback<Contact>,
+) -> Element {
+    let error: Signal<Option<String>> = use_signal(|| None);
+
+    let mut revisions: Resource<Result<Vec<ContactNoteRevision>, ServerFnError>> =
+        use_resource(move || async move {
+            let _ = version();
+            get_note_revisions(contact_id()).await
+        });
+
+    rsx! {
+        div { class: "ml-2 mr-2 mt-4 sm:ml-0 sm:mr-0",
+            h2 { class: "text-lg font-bold mb-2", "Notes History" }
+            if let Some(err) = error() {
+                div { class: "alert alert-error mb-2", "Error: " {err} }
+            }
+            match revisions.read().deref() {
+                None => rsx! {
+                    p { class: "alert alert-info", "Loading notes history..." }
+                },
+                Some(Err(err)) => rsx! {
+                    div { class: "alert alert-error", "Error loading notes history: " {err.to_string()} }
+                },
+                Some(Ok(list)) if list.is_empty() => rsx! {
+                    p { class: "alert alert-info", "No notes recorded." }
+                },
+                Some(Ok(list)) => rsx! {
+                    table { class: "block sm:table w-full",
+                        thead { class: "hidden sm:table-header-group",
+                            tr {
+                                th { "Time" }
+                                th { "User" }
+                                th { "Notes" }
+                                th {}
+                            }
+                        }
+                        tbody { class: "block sm:table-row-group",
+                            for (i, revision) in list.iter().enumerate() {
+                                NoteRevisionRow {
+                                    key: "{revision.id}",
+                                    revision: revision.clone(),
+                                    is_current: i == 0,
+                                    on_restore: move |contact| {
+                                        revisions.restart();
+                                        on_restore(contact);
+                                    },
+                                    error,
+                                }
+                            }
+                        }
+                    }
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use dioxus::prelude::*;
+
+use crate::{
+    forms::{Dialog, EditError, FormSaveCancelButton, InputString, Saving, validate_phone_number},
+    functions::phone_calls::{delete_phone_call, move_phone_call},
+    models::{
+        phone_calls::{PhoneCall, PhoneCallExportFormat, PhoneCallFilter, export_url},
+        search::SearchQuery,
+    },
+};
+
+/// Admin changes to a single call.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum PhoneCallOperation {
+    Move(PhoneCall),
+    Delete(PhoneCall),
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Date range, action and destination inputs for a list of calls.
+#[component]
+pub fn PhoneCallFilterBar(
+    filter: PhoneCallFilter,
+    on_change: Callback<PhoneCallFilter>,
+) -> Element {
+    let action = filter.action.clone().unwrap_or_default();
+
+    rsx! {
+        div { class: "flex flex-wrap gap-2 mb-2",
+            input {
+                class: "input",
+                r#type: "date",
+                title: "From",
+                value: filter.from_date.map(|d| d.to_string()).unwrap_or_default(),
+                onchange: {
+                    let filter = filter.clone();
+                    move |e: Event<FormData>| {
+                        on_change(PhoneCallFilter {
+                            from_date: e.value().parse().ok(),
+                            ..filter.clone()
+                        });
+                    }
+                },
+            }
+            input {
+                class: "input",
+                r#type: "date",
+                title: "To",
+                value: filter.to_date.map(|d| d.to_string()).unwrap_or_default(),
+                onchange: {
+                    let filter = filter.clone();
+                    move |e: Event<FormData>| {
+                        on_change(PhoneCallFilter {
+                            to_date: e.value().parse().ok(),
+                            ..filter.clone()
+                        });
+                    }
+                },
+            }
+            select {
+                class: "select",
+                onchange: {
+                    let filter = filter.clone();
+                    move |e: Event<FormData>| {
+                        on_change(PhoneCallFilter {
+                            action: non_empty(e.value()),
+                            ..filter.clone()
+                        });
+                    }
+                },
+                option { value: "", selected: action.is_empty(), "All actions" }
+                option { value: "allow", selected: action == "allow", "allow" }
+                option { value: "voicemail", selected: action == "voicemail", "voicemail" }
+            }
+            input {
+                class: "input",
+                r#type: "text",
+                value: filter.dest.clone().unwrap_or_default(),
+                placeholder: "Destination number",
+                onchange: {
+                    let filter = filter.clone();
+                    move |e: Event<FormData>| {
+                        on_change(PhoneCallFilter {
+                            dest: non_empty(e.value()),
+                            ..filter.clone()
+                        });
+                    }
+                },
+            }
+        }
+    }
+}
+
+/// Links to download the calls matching `q` and `filter`.  The server
+/// streams the file, so there is no limit on its size.
+#[component]
+pub fn PhoneCallExportLinks(q: SearchQuery, filter: PhoneCallFilter) -> Element {
+    rsx! {
+        div { class: "flex flex-wrap gap-2 mb-2",
+            for format in [PhoneCallExportFormat::Csv, PhoneCallExportFormat::Ndjson] {
+                a {
+                    class: "btn btn-outline btn-secondary",
+                    href: export_url(format, &q, &filter),
+                    download: format.filename(),
+                    {format!("Export {}", format.as_str().to_uppercase())}
+                }
+            }
+        }
+    }
+}
+
+fn describe(phone_call: &PhoneCall) -> String {
+    let time = phone_call
+        .inserted_at
+        .with_timezone(&chrono::Local)
+        .format("%Y-%m-%d %H:%M:%S");
+    match &phone_call.source_number {
+        Some(source) => format!("call from {source} at {time}"),
+        None => format!("call at {time}"),
+    }
+}
+
+/// Move a call to the contact with another phone number, e.g. when the
+/// number was mistyped during an import.
+#[component]
+pub fn PhoneCallMove(
+    phone_call: PhoneCall,
+    on_cancel: Callback,
+    on_save: Callback<PhoneCall>,
+) -> Element {
+    let phone_number = use_signal(String::new);
+    let validate_number = use_memo(move || validate_phone_number(&phone_number()));
+
+    let mut saving = use_signal(|| Saving::No);
+
+    let disabled = use_memo(move || saving.read().is_saving());
+    let disabled_save = use_memo(move || validate_number.read().is_err() || disabled());
+
+    let id = phone_call.id;
+    let on_save = use_callback(move |()| {
+        spawn(async move {
+            saving.set(Saving::Yes);
+
+            let phone_number = match validate_number() {
+                Ok(phone_number) => phone_number,
+                Err(err) => {
+                    saving.set(Saving::Finished(Err(EditError::Validation(err))));
+                    return;
+                }
+            };
+
+            match move_phone_call(id, phone_number).await {
+                Ok(phone_call) => {
+                    saving.set(Saving::Finished(Ok(())));
+                    on_save(phone_call);
+                }
+                Err(err) => saving.set(Saving::Finished(Err(EditError::Server(err)))),
+            }
+        });
+    });
+
+    rsx! {
+        h3 { class: "text-lg font-bold",
+            {format!("Move {}", describe(&phone_call))}
+        }
+        p { class: "py-4",
+            "The call will be moved to the contact with this phone number. Press ESC key or click the button below to close."
+        }
+        form {
+            novalidate: true,
+            action: "javascript:void(0)",
+            method: "dialog",
+            onkeyup: move |event| {
+                if event.key() == Key::Escape {
+                    on_cancel(());
+                }
+            },
+            InputString {
+                id: "phone_number",
+                label: "Phone Number",
+                value: phone_number,
+                validate: validate_number,
+                disabled,
+            }
+
+            FormSaveCancelButton {
+                disabled: disabled_save,
+                on_save: move |()| on_save(()),
+                on_cancel: move |()| on_cancel(()),
+                title: "Move",
+                saving,
+            }
+        }
+    }
+}
+
+#[component]
+pub fn PhoneCallDelete(
+    phone_call: PhoneCall,
+    on_cancel: Callback,
+    on_delete: Callback<PhoneCall>,
+) -> Element {
+    let mut saving = use_signal(|| Saving::No);
+
+    let disabled = use_memo(move || saving.read().is_saving());
+
+    let phone_call_clone = phone_call.clone();
+    let on_save = use_callback(move |()| {
+        let phone_call_clone = phone_call_clone.clone();
+        spawn(async move {
+            saving.set(Saving::Yes);
+
+            match delete_phone_call(phone_call_clone.id).await {
+                Ok(()) => {
+                    saving.set(Saving::Finished(Ok(())));
+                    on_delete(phone_call_clone.clone());
+                }
+                Err(err) => saving.set(Saving::Finished(Err(EditError::Server(err)))),
+            }
+        });
+    });
+
+    rsx! {
+        h3 { class: "text-lg font-bold",
+            {format!("Delete {}", describe(&phone_call))}
+        }
+        p { class: "py-4", "The call will be removed from the call log. This cannot be undone." }
+        form {
+            novalidate: true,
+            action: "javascript:void(0)",
+            method: "dialog",
+            onkeyup: move |event| {
+                if event.key() == Key::Escape {
+                    on_cancel(());
+                }
+            },
+            FormSaveCancelButton {
+                disabled,
+                on_save: move |()| on_save(()),
+                on_cancel: move |_| on_cancel(()),
+                title: "Delete",
+                saving,
+            }
+        }
+    }
+}
+
+#[component]
+pub fn PhoneCallDialog(
+    operation: ReadSignal<Option<PhoneCallOperation>>,
+    on_change: Callback<()>,
+    on_close: Callback<()>,
+) -> Element {
+    match operation() {
+        None => rsx! {},
+        Some(PhoneCallOperation::Move(phone_call)) => rsx! {
+            Dialog {
+                PhoneCallMove {
+                    phone_call,
+                    on_cancel: on_close,
+                    on_save: move |_phone_call| {
+                        on_change(());
+                        on_close(());
+                    },
+                }
+            }
+        },
+        Some(PhoneCallOperation::Delete(phone_call)) => rsx! {
+            Dialog {
+                PhoneCallDelete {
+                    phone_call,
+                    on_cancel: on_close,
+                    on_delete: move |_phone_call| {
+                        on_change(());
+                        on_close(());
+                    },
+                }
+            }
+        },
+    }
+}
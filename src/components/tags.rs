@@ -0,0 +1,306 @@
+use std::{num::ParseIntError, str::FromStr};
+
+use dioxus::prelude::*;
+use dioxus_router::ToQueryArgument;
+use tap::Pipe;
+use thiserror::Error;
+
+use crate::{
+    forms::{
+        Dialog, EditError, FieldValue, FormSaveCancelButton, InputString, Saving, ValidationError,
+        validate_optional_action, validate_tag_name,
+    },
+    functions::tags::{create_tag, delete_tag, update_tag},
+    models::{
+        common::MaybeSet,
+        tags::{ChangeTag, NewTag, Tag, TagId},
+    },
+};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Operation {
+    Create,
+    Update { tag: Tag },
+}
+
+#[derive(Debug, Clone)]
+struct Validate {
+    name: Memo<Result<String, ValidationError>>,
+    action: Memo<Result<Option<String>, ValidationError>>,
+}
+
+async fn do_save(op: &Operation, validate: &Validate) -> Result<Tag, EditError> {
+    let name = validate.name.read().clone()?;
+    let action = validate.action.read().clone()?;
+
+    match op {
+        Operation::Create => {
+            let new_tag = NewTag { name, action };
+            create_tag(new_tag).await.map_err(EditError::Server)
+        }
+        Operation::Update { tag } => {
+            let changes = ChangeTag {
+                name: MaybeSet::Set(name),
+                action: MaybeSet::Set(action),
+            };
+            update_tag(tag.clone(), changes)
+                .await
+                .map_err(EditError::Server)
+        }
+    }
+}
+
+#[component]
+pub fn TagUpdate(op: Operation, on_cancel: Callback, on_save: Callback<Tag>) -> Element {
+    let name = use_signal(|| match &op {
+        Operation::Create => String::new(),
+        Operation::Update { tag } => tag.name.clone(),
+    });
+
+    let action = use_signal(|| match &op {
+        Operation::Create => String::new(),
+        Operation::Update { tag } => tag.action.as_raw(),
+    });
+
+    let validate = Validate {
+        name: use_memo(move || validate_tag_name(&name())),
+        action: use_memo(move || validate_optional_action(&action())),
+    };
+
+    let mut saving = use_signal(|| Saving::No);
+
+    let disabled = use_memo(move || saving.read().is_saving());
+    let disabled_save = use_memo(move || {
+        validate.name.read().is_err() || validate.action.read().is_err() || disabled()
+    });
+
+    let op_clone = op.clone();
+    let validate_clone = validate.clone();
+    let on_save = use_callback(move |()| {
+        let op = op_clone.clone();
+        let validate = validate_clone.clone();
+        spawn(async move {
+            saving.set(Saving::Yes);
+
+            let result = do_save(&op, &validate).await;
+
+            match result {
+                Ok(tag) => {
+                    saving.set(Saving::Finished(Ok(())));
+                    on_save(tag);
+                }
+                Err(err) => saving.set(Saving::Finished(Err(err))),
+            }
+        });
+    });
+
+    rsx! {
+        h3 { class: "text-lg font-bold",
+            match &op {
+                Operation::Create => "Create Tag".to_string(),
+                Operation::Update { tag } => format!("Edit Tag {}", tag.name),
+            }
+        }
+        p { class: "py-4", "Press ESC key or click the button below to close" }
+        p { class: "pb-4",
+            "If an action is given it overrides the action of every contact with this tag."
+        }
+        form {
+            novalidate: true,
+            action: "javascript:void(0)",
+            method: "dialog",
+            onkeyup: move |event| {
+                if event.key() == Key::Escape {
+                    on_cancel(());
+                }
+            },
+            InputString {
+                id: "name",
+                label: "Name",
+                value: name,
+                validate: validate.name,
+                disabled,
+            }
+            InputString {
+                id: "action",
+                label: "Action (optional)",
+                value: action,
+                validate: validate.action,
+                disabled,
+            }
+
+            FormSaveCancelButton {
+                disabled: disabled_save,
+                on_save: move |()| on_save(()),
+                on_cancel: move |()| on_cancel(()),
+                title: match &op {
+                    Operation::Create => "Create",
+                    Operation::Update { .. } => "Save",
+                },
+                saving,
+            }
+        }
+    }
+}
+
+#[component]
+pub fn TagDelete(tag: Tag, on_cancel: Callback, on_delete: Callback<Tag>) -> Element {
+    let mut saving = use_signal(|| Saving::No);
+
+    let disabled = use_memo(move || saving.read().is_saving());
+
+    let tag_clone = tag.clone();
+    let on_save = use_callback(move |()| {
+        let tag_clone = tag_clone.clone();
+        spawn(async move {
+            saving.set(Saving::Yes);
+
+            match delete_tag(tag_clone.clone()).await {
+                Ok(_) => {
+                    saving.set(Saving::Finished(Ok(())));
+                    on_delete(tag_clone.clone());
+                }
+                Err(err) => saving.set(Saving::Finished(Err(EditError::Server(err)))),
+            }
+        });
+    });
+
+    rsx! {
+        h3 { class: "text-lg font-bold",
+            "Delete tag "
+            {tag.name.clone()}
+        }
+        p { class: "py-4", "Press ESC key or click the button below to close" }
+        p { class: "pb-4", "The tag will be removed from every contact." }
+        form {
+            novalidate: true,
+            action: "javascript:void(0)",
+            method: "dialog",
+            onkeyup: move |event| {
+                if event.key() == Key::Escape {
+                    on_cancel(());
+                }
+            },
+            FormSaveCancelButton {
+                disabled,
+                on_save: move |()| on_save(()),
+                on_cancel: move |_| on_cancel(()),
+                title: "Delete",
+                saving,
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ActiveDialog {
+    Change(Operation),
+    Delete(Tag),
+    Idle,
+}
+
+#[derive(Error, Debug)]
+pub enum ListDialogReferenceError {
+    #[error("Invalid integer")]
+    ParseIntError(#[from] ParseIntError),
+
+    #[error("Invalid reference")]
+    ReferenceError,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ListDialogReference {
+    Create,
+    Update {
+        tag_id: TagId,
+    },
+    Delete {
+        tag_id: TagId,
+    },
+    #[default]
+    Idle,
+}
+
+impl ToQueryArgument for ListDialogReference {
+    fn display_query_argument(
+        &self,
+        query_name: &str,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{}={}", query_name, self.to_string())
+    }
+}
+
+impl FromStr for ListDialogReference {
+    type Err = ListDialogReferenceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s.split("-").collect::<Vec<_>>();
+        match split[..] {
+            ["create"] => Self::Create,
+            ["update", id] => {
+                let tag_id = TagId::new(id.parse()?);
+                Self::Update { tag_id }
+            }
+            ["delete", id] => {
+                let tag_id = TagId::new(id.parse()?);
+                Self::Delete { tag_id }
+            }
+            [""] | [] => Self::Idle,
+            _ => return Err(ListDialogReferenceError::ReferenceError),
+        }
+        .pipe(Ok)
+    }
+}
+
+#[allow(clippy::to_string_trait_impl)]
+impl ToString for ListDialogReference {
+    fn to_string(&self) -> String {
+        match self {
+            ListDialogReference::Create => "create".to_string(),
+            ListDialogReference::Update { tag_id } => format!("update-{tag_id}"),
+            ListDialogReference::Delete { tag_id } => format!("delete-{tag_id}"),
+            ListDialogReference::Idle => String::new(),
+        }
+    }
+}
+
+#[component]
+pub fn TagDialog(
+    dialog: ReadSignal<ActiveDialog>,
+    on_change: Callback<Tag>,
+    on_delete: Callback<Tag>,
+    on_close: Callback<()>,
+) -> Element {
+    match dialog() {
+        ActiveDialog::Idle => rsx! {},
+        ActiveDialog::Change(op) => {
+            rsx! {
+                Dialog {
+                    TagUpdate {
+                        op,
+                        on_cancel: on_close,
+                        on_save: move |tag: Tag| {
+                            on_change(tag.clone());
+                            on_close(());
+                        },
+                    }
+                }
+            }
+        }
+        ActiveDialog::Delete(tag) => {
+            rsx! {
+                Dialog {
+                    TagDelete {
+                        tag,
+                        on_cancel: on_close,
+                        on_delete: move |tag| {
+                            on_delete(tag);
+                            on_close(());
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
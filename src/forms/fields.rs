@@ -356,3 +356,34 @@ pub fn InputBoolean(
         }
     }
 }
+
+/// A choice between `options`, given as `(value, title)` pairs.
+#[component]
+pub fn InputSelect(
+    id: &'static str,
+    label: &'static str,
+    mut value: Signal<String>,
+    options: Vec<(String, String)>,
+    disabled: Memo<bool>,
+) -> Element {
+    rsx! {
+        div { class: "mb-5",
+            label { r#for: id, class: get_label_classes(), "{label}" }
+            select {
+                class: get_input_classes(true, disabled()),
+                id,
+                disabled,
+                onchange: move |e| {
+                    value.set(e.value());
+                },
+                for (option_value , title) in options {
+                    option {
+                        value: "{option_value}",
+                        selected: value() == option_value,
+                        {title}
+                    }
+                }
+            }
+        }
+    }
+}
@@ -8,13 +8,14 @@ mod validation;
 pub use buttons::{FormCancelButton, FormCloseButton, FormSaveCancelButton, FormSubmitButton};
 pub use dialog::Dialog;
 pub use errors::{EditError, ValidationError};
-pub use fields::{InputBoolean, InputPassword, InputString, InputTextArea};
+pub use fields::{InputBoolean, InputPassword, InputSelect, InputString, InputTextArea};
 pub use saving::MyForm;
 pub use saving::Saving;
 pub use validation::{
     validate_1st_password, validate_2nd_password, validate_action, validate_comments,
-    validate_contact_name, validate_default_name, validate_email, validate_full_name,
-    validate_password, validate_phone_number, validate_regex, validate_username,
+    validate_contact_name, validate_contact_number_label, validate_default_name, validate_duration,
+    validate_email, validate_full_name, validate_optional_action, validate_password,
+    validate_phone_number, validate_regex, validate_tag_name, validate_tags, validate_username,
 };
 
 mod values;
@@ -1,4 +1,6 @@
 use super::{FieldValue, errors::ValidationError};
+use crate::models::contacts::ACTIONS;
+use crate::models::tags::parse_tag_names;
 
 pub fn validate_field_value<T: FieldValue<RawValue = String, DerefValue = str>>(
     str: &str,
@@ -13,18 +15,40 @@ pub fn validate_phone_number(str: &str) -> Result<String, ValidationError> {
 pub fn validate_action(str: &str) -> Result<String, ValidationError> {
     match validate_field_value(str) {
         Ok(action) => {
-            if action == "voicemail" || action == "allow" {
+            if ACTIONS.contains(&action.as_str()) {
                 Ok(action)
             } else {
-                Err(ValidationError(
-                    "Action must be 'voicemail' or 'allow'".to_string(),
-                ))
+                let actions = ACTIONS.map(|action| format!("'{action}'"));
+                Err(ValidationError(format!(
+                    "Action must be {}",
+                    actions.join(" or ")
+                )))
             }
         }
         Err(err) => Err(err),
     }
 }
 
+pub fn validate_optional_action(str: &str) -> Result<Option<String>, ValidationError> {
+    if str.trim().is_empty() {
+        Ok(None)
+    } else {
+        validate_action(str).map(Some)
+    }
+}
+
+pub fn validate_tag_name(str: &str) -> Result<String, ValidationError> {
+    let str = validate_field_value::<String>(str)?.to_lowercase();
+    if str.contains(',') {
+        return Err(ValidationError("Tag cannot contain ','".to_string()));
+    }
+    Ok(str)
+}
+
+pub fn validate_tags(str: &str) -> Result<Vec<String>, ValidationError> {
+    Ok(parse_tag_names(str))
+}
+
 pub fn validate_default_name(str: &str) -> Result<Option<String>, ValidationError> {
     validate_field_value(str)
 }
@@ -33,6 +57,10 @@ pub fn validate_contact_name(str: &str) -> Result<Option<String>, ValidationErro
     validate_field_value(str)
 }
 
+pub fn validate_contact_number_label(str: &str) -> Result<Option<String>, ValidationError> {
+    validate_field_value(str)
+}
+
 pub fn validate_username(str: &str) -> Result<String, ValidationError> {
     validate_field_value(str)
 }
@@ -98,3 +126,31 @@ pub fn validate_regex(str: &str) -> Result<Option<String>, ValidationError> {
         Err(err) => Err(err),
     }
 }
+
+/// A duration such as "90m", "12h", "3d" or "2w".  Empty means no duration.
+pub fn validate_duration(str: &str) -> Result<Option<chrono::TimeDelta>, ValidationError> {
+    let str = str.trim();
+    if str.is_empty() {
+        return Ok(None);
+    }
+
+    let invalid = || ValidationError("Duration must be like 90m, 12h, 3d or 2w".to_string());
+    let unit = str.chars().last().ok_or_else(invalid)?;
+    let number: i64 = str[..str.len() - unit.len_utf8()]
+        .trim()
+        .parse()
+        .map_err(|_| invalid())?;
+    if number <= 0 {
+        return Err(invalid());
+    }
+
+    match unit {
+        'm' => chrono::TimeDelta::try_minutes(number),
+        'h' => chrono::TimeDelta::try_hours(number),
+        'd' => chrono::TimeDelta::try_days(number),
+        'w' => chrono::TimeDelta::try_weeks(number),
+        _ => None,
+    }
+    .ok_or_else(invalid)
+    .map(Some)
+}
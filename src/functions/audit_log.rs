@@ -0,0 +1,108 @@
+use crate::models::audit_log as models;
+use dioxus::prelude::*;
+use dioxus_fullstack::{ServerFnError, server};
+
+#[cfg(feature = "server")]
+use super::common::{
+    AppError, assert_is_admin, get_database_connection, get_group_scope, get_user_id,
+};
+#[cfg(feature = "server")]
+use crate::server::database::connection::DatabaseConnection;
+#[cfg(feature = "server")]
+use crate::server::database::models::groups::GroupScope;
+
+/// Fail unless the contact, call or default is visible within `scope`, so
+/// the history of other groups' entities is reported as not found.  Admins
+/// see everything, including entities that have since been deleted.
+#[cfg(feature = "server")]
+async fn check_entity_visible(
+    conn: &mut DatabaseConnection,
+    scope: &GroupScope,
+    entity: models::AuditEntityRef,
+) -> Result<(), AppError> {
+    use crate::server::database::service::{contacts, defaults, phone_calls};
+
+    if matches!(scope, GroupScope::All) {
+        return Ok(());
+    }
+
+    match entity.entity {
+        models::AuditEntity::Contact => {
+            let id = crate::models::contacts::ContactId::new(entity.id);
+            contacts::check_contacts_visible(conn, scope, &[id]).await?;
+        }
+        models::AuditEntity::PhoneCall => {
+            let id = crate::models::phone_calls::PhoneCallId::new(entity.id);
+            phone_calls::get_phone_call_by_id(conn, scope, id)
+                .await?
+                .ok_or(phone_calls::Error::NotFound)?;
+        }
+        models::AuditEntity::Default => {
+            let id = crate::models::defaults::DefaultId::new(entity.id);
+            defaults::check_default_visible(conn, scope, id).await?;
+        }
+        // Only admins, who have every scope, get this far for users.
+        models::AuditEntity::User => {}
+    }
+
+    Ok(())
+}
+
+/// Returns up to `page_size + 1` audit entries for one entity, newest first,
+/// starting after `before_id` if provided.  The history of users is only
+/// visible to admins, and that of other entities only to users who can see
+/// the entity itself.
+#[server]
+pub async fn get_audit_log_for_entity(
+    entity: models::AuditEntityRef,
+    before_id: Option<models::AuditLogId>,
+    page_size: i64,
+) -> Result<Vec<models::AuditLogEntry>, ServerFnError> {
+    let _logged_in_user_id = get_user_id().await?;
+    if entity.entity == models::AuditEntity::User {
+        assert_is_admin().await?;
+    }
+
+    let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
+    check_entity_visible(&mut conn, &scope, entity)
+        .await
+        .map_err(ServerFnError::from)?;
+
+    let filter = models::AuditLogFilter {
+        entity: Some(entity),
+        ..models::AuditLogFilter::default()
+    };
+
+    crate::server::database::service::audit_log::search_audit_log(
+        &mut conn,
+        filter,
+        before_id,
+        page_size + 1,
+    )
+    .await
+    .map_err(AppError::from)
+    .map_err(ServerFnError::from)
+}
+
+/// Returns up to `page_size + 1` audit entries matching `filter`, newest
+/// first, starting after `before_id` if provided.
+#[server]
+pub async fn search_audit_log(
+    filter: models::AuditLogFilter,
+    before_id: Option<models::AuditLogId>,
+    page_size: i64,
+) -> Result<Vec<models::AuditLogEntry>, ServerFnError> {
+    assert_is_admin().await?;
+    let mut conn = get_database_connection().await?;
+
+    crate::server::database::service::audit_log::search_audit_log(
+        &mut conn,
+        filter,
+        before_id,
+        page_size + 1,
+    )
+    .await
+    .map_err(AppError::from)
+    .map_err(ServerFnError::from)
+}
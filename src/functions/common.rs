@@ -11,6 +11,7 @@ use crate::models::users::UserId;
 use crate::server::auth::Session;
 use crate::server::database::connection::DatabaseConnection;
 use crate::server::database::connection::DatabasePool;
+use crate::server::database::models::groups::{GroupScope, get_scope_for_user};
 use crate::server::ldap::connect::LdapConnection;
 use crate::server::ldap::query::Error as LdapError;
 
@@ -36,6 +37,21 @@ pub enum AppError {
 
     #[error("Defaults error: {0}")]
     Defaults(#[from] crate::server::database::service::defaults::Error),
+
+    #[error("Groups error: {0}")]
+    Groups(#[from] crate::server::database::service::groups::Error),
+
+    #[error("Tags error: {0}")]
+    Tags(#[from] crate::server::database::service::tags::Error),
+
+    #[error("Import error: {0}")]
+    Imports(#[from] crate::server::database::service::imports::Error),
+
+    #[error("CSV error: {0}")]
+    Csv(#[from] crate::server::csv::Error),
+
+    #[error("Audit log error: {0}")]
+    AuditLog(#[from] crate::server::database::service::audit_log::Error),
 }
 
 impl From<AppError> for ServerFnError {
@@ -77,10 +93,28 @@ pub async fn get_user_id() -> Result<UserId, ServerFnError> {
 pub async fn assert_is_admin() -> Result<(), ServerFnError> {
     let session: Session = FullstackContext::extract().await?;
     let user_opt = session.user().await;
-    let user = user_opt.as_ref()
+    let user = user_opt
+        .as_ref()
         .ok_or(ServerFnError::new("Not Logged In".to_string()))?;
     user.is_admin
         .then_some(())
         .ok_or(ServerFnError::new("Not Admin".to_string()))?;
     Ok(())
 }
+
+/// The contacts and defaults the logged in user may see: everything for
+/// admins, otherwise those in the user's groups or in no group.
+pub async fn get_group_scope(conn: &mut DatabaseConnection) -> Result<GroupScope, ServerFnError> {
+    let session: Session = FullstackContext::extract().await?;
+    let (user_id, is_admin) = session
+        .user()
+        .await
+        .as_ref()
+        .map(|x| (x.id, x.is_admin))
+        .ok_or(ServerFnError::new("Not Logged In".to_string()))?;
+
+    get_scope_for_user(conn, user_id, is_admin)
+        .await
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
@@ -1,58 +1,76 @@
 use crate::models::contacts as models;
+use crate::models::search::SearchQuery;
 use dioxus::prelude::*;
 use dioxus_fullstack::{ServerFnError, server};
 
 #[cfg(feature = "server")]
-use super::common::{AppError, get_database_connection, get_user_id};
+use super::common::{AppError, get_database_connection, get_group_scope, get_user_id};
+#[cfg(feature = "server")]
+use crate::server::database::connection::DatabaseConnection;
+
+/// Fail unless the logged in user may see every one of the contacts.
+#[cfg(feature = "server")]
+async fn check_visible(
+    conn: &mut DatabaseConnection,
+    contact_ids: &[models::ContactId],
+) -> Result<(), ServerFnError> {
+    let scope = get_group_scope(conn).await?;
+
+    crate::server::database::service::contacts::check_contacts_visible(conn, &scope, contact_ids)
+        .await
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
+
+/// Fail unless the logged in user may put a contact in `group_id`.
+#[cfg(feature = "server")]
+async fn check_group(
+    conn: &mut DatabaseConnection,
+    group_id: Option<crate::models::groups::GroupId>,
+) -> Result<(), ServerFnError> {
+    let scope = get_group_scope(conn).await?;
+
+    crate::server::database::service::contacts::check_group_allowed(&scope, group_id)
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
 
 #[server]
 pub async fn search_contacts(query: String) -> Result<Vec<models::Contact>, ServerFnError> {
     let _logged_in_user_id = get_user_id().await?;
     let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
 
-    crate::server::database::service::contacts::search_contacts(&mut conn, query)
+    crate::server::database::service::contacts::search_contacts(&mut conn, &scope, query)
         .await
         .map_err(AppError::from)
         .map_err(ServerFnError::from)
 }
 
-/// Returns up to `page_size + 1` contacts matching `query`, sorted
-/// `(name ASC NULLS LAST, id ASC)`, starting after the cursor row.
-///
-/// The cursor is split into two params because `Option<Option<String>>` does
-/// not round-trip cleanly as a URL query param:
-///   - `before_id`        — id of the last visible row (`None` = first page)
-///   - `before_name`      — name of the last visible row, if it had one
-///   - `before_name_null` — true when the last visible row's name was NULL
+/// Returns up to `page_size + 1` contacts matching `query` (and carrying
+/// `tag`, if given), sorted by `sort` then id, starting after `cursor`, the
+/// last visible row (`None` = first page).
 ///
 /// Caller uses the extra row to detect whether a next page exists.
 #[server]
 pub async fn search_contacts_paginated(
-    query: String,
-    before_id: Option<models::ContactId>,
-    before_name: Option<String>,
-    before_name_null: bool,
+    query: SearchQuery,
+    tag: Option<String>,
+    sort: models::ContactSort,
+    cursor: Option<models::ContactCursor>,
     page_size: i64,
 ) -> Result<Vec<models::Contact>, ServerFnError> {
     let _logged_in_user_id = get_user_id().await?;
     let mut conn = get_database_connection().await?;
-
-    // Reconstruct Option<Option<String>> from the split params.
-    let cursor_name: Option<Option<String>> = if before_id.is_some() {
-        if before_name_null {
-            Some(None)
-        } else {
-            Some(before_name)
-        }
-    } else {
-        None
-    };
+    let scope = get_group_scope(&mut conn).await?;
 
     crate::server::database::service::contacts::search_contacts_paginated(
         &mut conn,
-        query,
-        cursor_name,
-        before_id,
+        &scope,
+        &query,
+        tag,
+        sort,
+        cursor,
         page_size + 1,
     )
     .await
@@ -67,9 +85,14 @@ pub async fn get_contact_by_id(
     let _logged_in_user_id = get_user_id().await?;
 
     let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
 
+    // Contacts outside the user's groups look the same as missing ones.
     crate::server::database::service::contacts::get_contact_by_id(&mut conn, id)
         .await
+        .map(|contact| {
+            contact.filter(|c| scope.allows(c.group_id.map(|group_id| group_id.as_inner())))
+        })
         .map_err(AppError::from)
         .map_err(ServerFnError::from)
 }
@@ -78,12 +101,13 @@ pub async fn get_contact_by_id(
 pub async fn create_contact(contact: models::NewContact) -> Result<models::Contact, ServerFnError> {
     use crate::{functions::common::get_ldap_connection, server::database::service::contacts};
 
-    let _logged_in_user_id = get_user_id().await?;
+    let user_id = get_user_id().await?;
 
     let mut conn = get_database_connection().await?;
+    check_group(&mut conn, contact.group_id).await?;
     let (mut ldap, base_dn) = get_ldap_connection().await?;
 
-    contacts::create_contact(&mut conn, &base_dn, &mut ldap, contact)
+    contacts::create_contact(&mut conn, &base_dn, &mut ldap, Some(user_id), contact)
         .await
         .map_err(AppError::from)
         .map_err(ServerFnError::from)
@@ -94,34 +118,297 @@ pub async fn update_contact(
     old_contact: models::Contact,
     change_contact: models::ChangeContact,
 ) -> Result<models::Contact, ServerFnError> {
-    use crate::{functions::common::get_ldap_connection, server::database::service::contacts};
+    use crate::{
+        functions::common::get_ldap_connection, models::common::MaybeSet,
+        server::database::service::contacts,
+    };
+
+    let user_id = get_user_id().await?;
+
+    let mut conn = get_database_connection().await?;
+    check_visible(&mut conn, &[old_contact.id]).await?;
+    if let MaybeSet::Set(group_id) = &change_contact.group_id {
+        check_group(&mut conn, *group_id).await?;
+    }
+    let (mut ldap, base_dn) = get_ldap_connection().await?;
 
+    contacts::update_contact(
+        &mut conn,
+        &base_dn,
+        &mut ldap,
+        Some(user_id),
+        old_contact,
+        change_contact,
+    )
+    .await
+    .map_err(AppError::from)
+    .map_err(ServerFnError::from)
+}
+
+/// Every version of a contact's notes, newest first.
+#[server]
+pub async fn get_note_revisions(
+    contact_id: models::ContactId,
+) -> Result<Vec<models::ContactNoteRevision>, ServerFnError> {
     let _logged_in_user_id = get_user_id().await?;
+    let mut conn = get_database_connection().await?;
+    check_visible(&mut conn, &[contact_id]).await?;
+
+    crate::server::database::service::contacts::get_note_revisions(&mut conn, contact_id)
+        .await
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
+
+#[server]
+pub async fn restore_note_revision(
+    revision_id: models::ContactNoteRevisionId,
+) -> Result<models::Contact, ServerFnError> {
+    use crate::functions::common::get_ldap_connection;
+
+    let user_id = get_user_id().await?;
+    let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
+    let (mut ldap, base_dn) = get_ldap_connection().await?;
+
+    crate::server::database::service::contacts::restore_note_revision(
+        &mut conn,
+        &base_dn,
+        &mut ldap,
+        Some(user_id),
+        &scope,
+        revision_id,
+    )
+    .await
+    .map_err(AppError::from)
+    .map_err(ServerFnError::from)
+}
+
+#[server]
+pub async fn archive_contact(
+    old_contact: models::Contact,
+) -> Result<models::Contact, ServerFnError> {
+    use crate::functions::common::get_ldap_connection;
 
+    let user_id = get_user_id().await?;
     let mut conn = get_database_connection().await?;
+    check_visible(&mut conn, &[old_contact.id]).await?;
     let (mut ldap, base_dn) = get_ldap_connection().await?;
 
-    contacts::update_contact(&mut conn, &base_dn, &mut ldap, old_contact, change_contact)
+    crate::server::database::service::contacts::archive_contact(
+        &mut conn,
+        &base_dn,
+        &mut ldap,
+        Some(user_id),
+        old_contact,
+    )
+    .await
+    .map_err(AppError::from)
+    .map_err(ServerFnError::from)
+}
+
+#[server]
+pub async fn restore_contact(
+    old_contact: models::Contact,
+) -> Result<models::Contact, ServerFnError> {
+    use crate::functions::common::get_ldap_connection;
+
+    let user_id = get_user_id().await?;
+    let mut conn = get_database_connection().await?;
+    check_visible(&mut conn, &[old_contact.id]).await?;
+    let (mut ldap, base_dn) = get_ldap_connection().await?;
+
+    crate::server::database::service::contacts::restore_contact(
+        &mut conn,
+        &base_dn,
+        &mut ldap,
+        Some(user_id),
+        old_contact,
+    )
+    .await
+    .map_err(AppError::from)
+    .map_err(ServerFnError::from)
+}
+
+#[server]
+pub async fn get_archived_contacts() -> Result<Vec<models::Contact>, ServerFnError> {
+    let _logged_in_user_id = get_user_id().await?;
+    let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
+
+    crate::server::database::service::contacts::get_archived_contacts(&mut conn, &scope)
         .await
         .map_err(AppError::from)
         .map_err(ServerFnError::from)
 }
 
+/// Permanently delete an archived contact and its call history.
 #[server]
 pub async fn delete_contact(old_contact: models::Contact) -> Result<(), ServerFnError> {
     use crate::functions::common::get_ldap_connection;
 
-    let _logged_in_user_id = get_user_id().await?;
+    let user_id = get_user_id().await?;
     let mut conn = get_database_connection().await?;
+    check_visible(&mut conn, &[old_contact.id]).await?;
     let (mut ldap, base_dn) = get_ldap_connection().await?;
 
     crate::server::database::service::contacts::delete_contact(
         &mut conn,
         &base_dn,
         &mut ldap,
+        Some(user_id),
         old_contact,
     )
     .await
     .map_err(AppError::from)
     .map_err(ServerFnError::from)
 }
+
+#[server]
+pub async fn preview_merge_contacts(
+    contact_ids: Vec<models::ContactId>,
+) -> Result<models::MergeContactsPreview, ServerFnError> {
+    let _logged_in_user_id = get_user_id().await?;
+    let mut conn = get_database_connection().await?;
+    check_visible(&mut conn, &contact_ids).await?;
+
+    crate::server::database::service::contacts::preview_merge_contacts(&mut conn, contact_ids)
+        .await
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
+
+#[server]
+pub async fn merge_contacts(
+    merge: models::MergeContacts,
+) -> Result<models::Contact, ServerFnError> {
+    use crate::{functions::common::get_ldap_connection, server::database::service::contacts};
+
+    let user_id = get_user_id().await?;
+
+    let mut conn = get_database_connection().await?;
+    let mut contact_ids = merge.merged_ids.clone();
+    contact_ids.push(merge.survivor_id);
+    check_visible(&mut conn, &contact_ids).await?;
+    let (mut ldap, base_dn) = get_ldap_connection().await?;
+
+    contacts::merge_contacts(&mut conn, &base_dn, &mut ldap, Some(user_id), merge)
+        .await
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
+
+#[server]
+pub async fn get_contact_numbers(
+    contact_id: models::ContactId,
+) -> Result<Vec<models::ContactNumber>, ServerFnError> {
+    let _logged_in_user_id = get_user_id().await?;
+    let mut conn = get_database_connection().await?;
+    check_visible(&mut conn, &[contact_id]).await?;
+
+    crate::server::database::service::contacts::get_contact_numbers(&mut conn, contact_id)
+        .await
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
+
+#[server]
+pub async fn get_contact_number_by_id(
+    id: models::ContactNumberId,
+) -> Result<Option<models::ContactNumber>, ServerFnError> {
+    let _logged_in_user_id = get_user_id().await?;
+    let mut conn = get_database_connection().await?;
+
+    let number =
+        crate::server::database::service::contacts::get_contact_number_by_id(&mut conn, id)
+            .await
+            .map_err(AppError::from)?;
+
+    if let Some(number) = &number {
+        check_visible(&mut conn, &[number.contact_id]).await?;
+    }
+
+    Ok(number)
+}
+
+#[server]
+pub async fn create_contact_number(
+    number: models::NewContactNumber,
+) -> Result<models::ContactNumber, ServerFnError> {
+    use crate::{functions::common::get_ldap_connection, server::database::service::contacts};
+
+    let user_id = get_user_id().await?;
+
+    let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
+    let (mut ldap, base_dn) = get_ldap_connection().await?;
+
+    contacts::create_contact_number(
+        &mut conn,
+        &base_dn,
+        &mut ldap,
+        Some(user_id),
+        &scope,
+        number,
+    )
+    .await
+    .map_err(AppError::from)
+    .map_err(ServerFnError::from)
+}
+
+#[server]
+pub async fn delete_contact_number(
+    contact_id: models::ContactId,
+    number_id: models::ContactNumberId,
+) -> Result<(), ServerFnError> {
+    use crate::{functions::common::get_ldap_connection, server::database::service::contacts};
+
+    let user_id = get_user_id().await?;
+
+    let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
+    let (mut ldap, base_dn) = get_ldap_connection().await?;
+
+    contacts::delete_contact_number(
+        &mut conn,
+        &base_dn,
+        &mut ldap,
+        Some(user_id),
+        &scope,
+        contact_id,
+        number_id,
+    )
+    .await
+    .map_err(AppError::from)
+    .map_err(ServerFnError::from)
+}
+
+#[server]
+pub async fn get_contacts_by_ids(
+    contact_ids: Vec<models::ContactId>,
+) -> Result<Vec<models::Contact>, ServerFnError> {
+    let _logged_in_user_id = get_user_id().await?;
+    let mut conn = get_database_connection().await?;
+    check_visible(&mut conn, &contact_ids).await?;
+
+    crate::server::database::service::contacts::get_contacts_by_ids(&mut conn, contact_ids)
+        .await
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
+
+#[server]
+pub async fn bulk_edit_contacts(bulk: models::BulkEditContacts) -> Result<usize, ServerFnError> {
+    use crate::{functions::common::get_ldap_connection, server::database::service::contacts};
+
+    let user_id = get_user_id().await?;
+
+    let mut conn = get_database_connection().await?;
+    check_visible(&mut conn, &bulk.contact_ids).await?;
+    let (mut ldap, base_dn) = get_ldap_connection().await?;
+
+    contacts::bulk_edit_contacts(&mut conn, &base_dn, &mut ldap, Some(user_id), bulk)
+        .await
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
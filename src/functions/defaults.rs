@@ -3,14 +3,44 @@ use dioxus::prelude::*;
 use dioxus_fullstack::{ServerFnError, server};
 
 #[cfg(feature = "server")]
-use super::common::{AppError, get_database_connection, get_user_id};
+use super::common::{AppError, get_database_connection, get_group_scope, get_user_id};
+#[cfg(feature = "server")]
+use crate::server::database::connection::DatabaseConnection;
+
+/// Fail unless the logged in user may see the default.
+#[cfg(feature = "server")]
+async fn check_visible(
+    conn: &mut DatabaseConnection,
+    id: models::DefaultId,
+) -> Result<(), ServerFnError> {
+    let scope = get_group_scope(conn).await?;
+
+    crate::server::database::service::defaults::check_default_visible(conn, &scope, id)
+        .await
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
+
+/// Fail unless the logged in user may put a default in `group_id`.
+#[cfg(feature = "server")]
+async fn check_group(
+    conn: &mut DatabaseConnection,
+    group_id: Option<crate::models::groups::GroupId>,
+) -> Result<(), ServerFnError> {
+    let scope = get_group_scope(conn).await?;
+
+    crate::server::database::service::defaults::check_group_allowed(&scope, group_id)
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
 
 #[server]
 pub async fn search_defaults(query: String) -> Result<Vec<models::Default>, ServerFnError> {
     let _logged_in_user_id = get_user_id().await?;
     let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
 
-    crate::server::database::service::defaults::search_defaults(&mut conn, query)
+    crate::server::database::service::defaults::search_defaults(&mut conn, &scope, query)
         .await
         .map_err(AppError::from)
         .map_err(ServerFnError::from)
@@ -23,20 +53,26 @@ pub async fn get_default_by_id(
     let _logged_in_user_id = get_user_id().await?;
 
     let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
 
+    // Defaults outside the user's groups look the same as missing ones.
     crate::server::database::service::defaults::get_default_by_id(&mut conn, id)
         .await
+        .map(|default| {
+            default.filter(|d| scope.allows(d.group_id.map(|group_id| group_id.as_inner())))
+        })
         .map_err(AppError::from)
         .map_err(ServerFnError::from)
 }
 
 #[server]
 pub async fn create_default(default: models::NewDefault) -> Result<models::Default, ServerFnError> {
-    let _logged_in_user_id = get_user_id().await?;
+    let user_id = get_user_id().await?;
 
     let mut conn = get_database_connection().await?;
+    check_group(&mut conn, default.group_id).await?;
 
-    crate::server::database::service::defaults::create_default(&mut conn, default)
+    crate::server::database::service::defaults::create_default(&mut conn, Some(user_id), default)
         .await
         .map_err(AppError::from)
         .map_err(ServerFnError::from)
@@ -47,12 +83,17 @@ pub async fn update_default(
     old_default: models::Default,
     change_default: models::ChangeDefault,
 ) -> Result<models::Default, ServerFnError> {
-    let _logged_in_user_id = get_user_id().await?;
+    let user_id = get_user_id().await?;
 
     let mut conn = get_database_connection().await?;
+    check_visible(&mut conn, old_default.id).await?;
+    if let crate::models::common::MaybeSet::Set(group_id) = &change_default.group_id {
+        check_group(&mut conn, *group_id).await?;
+    }
 
     crate::server::database::service::defaults::update_default(
         &mut conn,
+        Some(user_id),
         old_default,
         change_default,
     )
@@ -61,13 +102,69 @@ pub async fn update_default(
     .map_err(ServerFnError::from)
 }
 
+/// Permanently delete an archived default.
 #[server]
 pub async fn delete_default(old_default: models::Default) -> Result<(), ServerFnError> {
-    let _logged_in_user_id = get_user_id().await?;
+    let user_id = get_user_id().await?;
+
+    let mut conn = get_database_connection().await?;
+    check_visible(&mut conn, old_default.id).await?;
+
+    crate::server::database::service::defaults::delete_default(
+        &mut conn,
+        Some(user_id),
+        old_default,
+    )
+    .await
+    .map_err(AppError::from)
+    .map_err(ServerFnError::from)
+}
+
+#[server]
+pub async fn archive_default(
+    old_default: models::Default,
+) -> Result<models::Default, ServerFnError> {
+    let user_id = get_user_id().await?;
+
+    let mut conn = get_database_connection().await?;
+    check_visible(&mut conn, old_default.id).await?;
 
+    crate::server::database::service::defaults::archive_default(
+        &mut conn,
+        Some(user_id),
+        old_default,
+    )
+    .await
+    .map_err(AppError::from)
+    .map_err(ServerFnError::from)
+}
+
+#[server]
+pub async fn restore_default(
+    old_default: models::Default,
+) -> Result<models::Default, ServerFnError> {
+    let user_id = get_user_id().await?;
+
+    let mut conn = get_database_connection().await?;
+    check_visible(&mut conn, old_default.id).await?;
+
+    crate::server::database::service::defaults::restore_default(
+        &mut conn,
+        Some(user_id),
+        old_default,
+    )
+    .await
+    .map_err(AppError::from)
+    .map_err(ServerFnError::from)
+}
+
+#[server]
+pub async fn get_archived_defaults() -> Result<Vec<models::Default>, ServerFnError> {
+    let _logged_in_user_id = get_user_id().await?;
     let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
 
-    crate::server::database::service::defaults::delete_default(&mut conn, old_default)
+    crate::server::database::service::defaults::get_archived_defaults(&mut conn, &scope)
         .await
         .map_err(AppError::from)
         .map_err(ServerFnError::from)
@@ -0,0 +1,19 @@
+use crate::models::groups as models;
+use dioxus::prelude::*;
+use dioxus_fullstack::{ServerFnError, server};
+
+#[cfg(feature = "server")]
+use super::common::{AppError, get_database_connection, get_group_scope};
+
+/// Groups the logged in user may put contacts and defaults in: all of them
+/// for admins, otherwise the user's own.
+#[server]
+pub async fn get_my_groups() -> Result<Vec<models::Group>, ServerFnError> {
+    let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
+
+    crate::server::database::service::groups::get_groups_in_scope(&mut conn, &scope)
+        .await
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
@@ -0,0 +1,224 @@
+use crate::models::imports as models;
+use crate::models::search::SearchQuery;
+use dioxus::prelude::*;
+use dioxus_fullstack::{ServerFnError, server};
+
+#[cfg(feature = "server")]
+use super::common::{AppError, get_database_connection, get_group_scope, get_user_id};
+
+#[server]
+pub async fn export_vcards(
+    query: SearchQuery,
+    tag: Option<String>,
+) -> Result<String, ServerFnError> {
+    let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
+
+    crate::server::database::service::imports::export_vcards(&mut conn, &scope, query, tag)
+        .await
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
+
+#[server]
+pub async fn import_vcards(content: String) -> Result<models::ImportReport, ServerFnError> {
+    use crate::{functions::common::get_ldap_connection, server::database::service::imports};
+
+    let user_id = get_user_id().await?;
+
+    let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
+    let (mut ldap, base_dn) = get_ldap_connection().await?;
+
+    imports::import_vcards(
+        &mut conn,
+        &scope,
+        &base_dn,
+        &mut ldap,
+        Some(user_id),
+        content,
+    )
+    .await
+    .map_err(AppError::from)
+    .map_err(ServerFnError::from)
+}
+
+#[cfg(feature = "server")]
+fn validate_contact_record(
+    line: usize,
+    phone_number: &str,
+    name: &str,
+    action: Option<&str>,
+    comments: &str,
+) -> Result<models::ContactRow, String> {
+    use crate::forms::{
+        validate_action, validate_comments, validate_contact_name, validate_phone_number,
+    };
+    use crate::models::contacts::DEFAULT_ACTION;
+
+    use crate::server::database::service::imports::normalize_phone_number;
+
+    // Stored and matched as the vCard import does, so both find the same
+    // contact.
+    let phone_number = validate_phone_number(&normalize_phone_number(phone_number))
+        .map_err(|err| format!("phone_number: {err}"))?;
+    let name = validate_contact_name(name).map_err(|err| format!("name: {err}"))?;
+    let action = match action {
+        Some(action) => validate_action(action).map_err(|err| format!("action: {err}"))?,
+        None => DEFAULT_ACTION.to_string(),
+    };
+    let comments = validate_comments(comments).map_err(|err| format!("comments: {err}"))?;
+
+    Ok(models::ContactRow {
+        line,
+        phone_number,
+        name,
+        action,
+        comments,
+    })
+}
+
+/// Parse and validate a contact CSV file with the same rules as the contact
+/// form.  Returns the valid rows and an error for every invalid one.
+#[cfg(feature = "server")]
+pub fn validate_contact_csv(
+    content: &str,
+) -> Result<(Vec<models::ContactRow>, Vec<models::ImportConflict>), crate::server::csv::Error> {
+    let mut records = crate::server::csv::decode(content)?.into_iter();
+    let mut rows: Vec<models::ContactRow> = vec![];
+    let mut errors: Vec<models::ImportConflict> = vec![];
+
+    let Some((_, header)) = records.next() else {
+        return Ok((rows, errors));
+    };
+    let column = |name: &str| header.iter().position(|h| h.trim() == name);
+    let Some(phone_number_column) = column("phone_number") else {
+        errors.push(models::ImportConflict {
+            item: "Line 1".to_string(),
+            reason: "Missing phone_number column".to_string(),
+        });
+        return Ok((rows, errors));
+    };
+    let name_column = column("name");
+    let action_column = column("action");
+    let comments_column = column("comments");
+
+    for (line, record) in records {
+        let field = |column: Option<usize>| {
+            column
+                .and_then(|c| record.get(c))
+                .map(String::as_str)
+                .unwrap_or_default()
+        };
+        let item = format!("Line {line}");
+
+        let row = validate_contact_record(
+            line,
+            field(Some(phone_number_column)),
+            field(name_column),
+            action_column.map(|c| field(Some(c))),
+            field(comments_column),
+        );
+
+        match row {
+            Ok(row) => {
+                if let Some(first) = rows.iter().find(|r| r.phone_number == row.phone_number) {
+                    errors.push(models::ImportConflict {
+                        item,
+                        reason: format!("Duplicate of line {}", first.line),
+                    });
+                } else {
+                    rows.push(row);
+                }
+            }
+            Err(reason) => errors.push(models::ImportConflict { item, reason }),
+        }
+    }
+
+    Ok((rows, errors))
+}
+
+#[server]
+pub async fn export_csv(query: SearchQuery, tag: Option<String>) -> Result<String, ServerFnError> {
+    let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
+
+    crate::server::database::service::imports::export_csv(&mut conn, &scope, query, tag)
+        .await
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
+
+/// Validate and import a contact CSV.  Nothing is written if any row is
+/// invalid, or if `dry_run` is set.
+#[server]
+pub async fn import_csv(
+    content: String,
+    dry_run: bool,
+) -> Result<models::ImportReport, ServerFnError> {
+    use crate::{functions::common::get_ldap_connection, server::database::service::imports};
+
+    let user_id = get_user_id().await?;
+
+    let (rows, errors) = validate_contact_csv(&content).map_err(AppError::from)?;
+
+    let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
+    let (mut ldap, base_dn) = get_ldap_connection().await?;
+
+    let dry_run = dry_run || !errors.is_empty();
+    let mut report = imports::import_contact_rows(
+        &mut conn,
+        &scope,
+        &base_dn,
+        &mut ldap,
+        Some(user_id),
+        rows,
+        dry_run,
+    )
+    .await
+    .map_err(AppError::from)?;
+
+    report.conflicts.splice(0..0, errors);
+    Ok(report)
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+    use crate::server::database::service::imports::card_numbers;
+    use crate::server::vcard;
+
+    #[test]
+    fn test_same_number_from_vcard_and_csv() {
+        let cards = vcard::decode(
+            "BEGIN:VCARD\r\n\
+             VERSION:4.0\r\n\
+             FN:Jane Citizen\r\n\
+             TEL:+61 (3) 9000-0000\r\n\
+             TEL;TYPE=cell:+61 400 000 000\r\n\
+             TEL:+61390000000\r\n\
+             END:VCARD\r\n",
+        )
+        .unwrap();
+        let numbers = card_numbers(&cards[0]);
+        assert_eq!(
+            numbers,
+            vec![
+                ("+61390000000".to_string(), None),
+                ("+61400000000".to_string(), Some("mobile".to_string())),
+            ]
+        );
+
+        let (rows, errors) = validate_contact_csv(
+            "phone_number,name\n\
+             +61 (3) 9000-0000,Jane Citizen\n\
+             +61390000000,Jane Citizen\n",
+        )
+        .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].phone_number, numbers[0].0);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].reason, "Duplicate of line 2");
+    }
+}
@@ -0,0 +1,20 @@
+use crate::models::ldap as models;
+use dioxus::prelude::*;
+use dioxus_fullstack::{ServerFnError, server};
+
+#[cfg(feature = "server")]
+use super::common::{AppError, assert_is_admin, get_database_connection, get_ldap_connection};
+
+/// Compare the contacts with LDAP, and with `fix` make LDAP match them.
+/// Admins only.
+#[server]
+pub async fn reconcile_ldap(fix: bool) -> Result<models::LdapReconcileReport, ServerFnError> {
+    assert_is_admin().await?;
+    let mut conn = get_database_connection().await?;
+    let (mut ldap, base_dn) = get_ldap_connection().await?;
+
+    crate::server::database::service::contacts::reconcile_ldap(&mut conn, &base_dn, &mut ldap, fix)
+        .await
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
@@ -1,7 +1,12 @@
 #[cfg(feature = "server")]
 mod common;
 
+pub mod audit_log;
 pub mod contacts;
 pub mod defaults;
+pub mod groups;
+pub mod imports;
+pub mod ldap;
 pub mod phone_calls;
+pub mod tags;
 pub mod users;
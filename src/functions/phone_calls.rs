@@ -1,12 +1,14 @@
 use crate::models::contacts as contact_models;
+use crate::models::dashboard::{CallDashboard, CallHeatmap};
 use crate::models::phone_calls as models;
+use crate::models::search::SearchQuery;
 use dioxus::prelude::*;
 use dioxus_fullstack::{ServerFnError, server};
 
 #[cfg(feature = "server")]
 use super::common::get_user_id;
 #[cfg(feature = "server")]
-use super::common::{AppError, get_database_connection};
+use super::common::{AppError, assert_is_admin, get_database_connection, get_group_scope};
 
 #[server]
 pub async fn search_phone_calls(
@@ -14,25 +16,28 @@ pub async fn search_phone_calls(
 ) -> Result<Vec<(models::PhoneCall, contact_models::Contact)>, ServerFnError> {
     let _logged_in_user_id = get_user_id().await?;
     let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
 
-    crate::server::database::service::phone_calls::search_phone_calls(&mut conn, query)
+    crate::server::database::service::phone_calls::search_phone_calls(&mut conn, &scope, query)
         .await
         .map_err(AppError::from)
         .map_err(ServerFnError::from)
 }
 
-/// Returns up to `page_size + 1` results matching `query`, starting after the
-/// cursor `(before_ts, before_id)` if provided.  The caller uses the extra
-/// entry to detect whether a next page exists.
+/// Returns up to `page_size + 1` results matching `query` and `filter`,
+/// starting after the cursor `(before_ts, before_id)` if provided.  The caller
+/// uses the extra entry to detect whether a next page exists.
 #[server]
 pub async fn search_phone_calls_paginated(
-    query: String,
+    query: SearchQuery,
+    filter: models::PhoneCallFilter,
     before_ts: Option<chrono::DateTime<chrono::Utc>>,
     before_id: Option<models::PhoneCallId>,
     page_size: i64,
 ) -> Result<Vec<(models::PhoneCall, contact_models::Contact)>, ServerFnError> {
     let _logged_in_user_id = get_user_id().await?;
     let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
 
     let before = match (before_ts, before_id) {
         (Some(ts), Some(id)) => Some((ts, id)),
@@ -41,7 +46,9 @@ pub async fn search_phone_calls_paginated(
 
     crate::server::database::service::phone_calls::search_phone_calls_paginated(
         &mut conn,
+        &scope,
         query,
+        filter,
         before,
         page_size + 1,
     )
@@ -50,19 +57,22 @@ pub async fn search_phone_calls_paginated(
     .map_err(ServerFnError::from)
 }
 
-/// Returns up to `page_size + 1` calls for a contact, starting after the
-/// cursor `(before_ts, before_id)` if provided.  The caller uses the extra
-/// entry to detect whether a next page exists (fetch `page_size + 1`, render
-/// only `page_size`, next-page cursor = last rendered row).
+/// Returns up to `page_size + 1` calls for a contact matching `filter`,
+/// starting after the cursor `(before_ts, before_id)` if provided.  The
+/// caller uses the extra entry to detect whether a next page exists (fetch
+/// `page_size + 1`, render only `page_size`, next-page cursor = last rendered
+/// row).
 #[server]
 pub async fn get_phone_calls_for_contact(
     contact_id: contact_models::ContactId,
+    filter: models::PhoneCallFilter,
     before_ts: Option<chrono::DateTime<chrono::Utc>>,
     before_id: Option<models::PhoneCallId>,
     page_size: i64,
 ) -> Result<Vec<models::PhoneCall>, ServerFnError> {
     let _logged_in_user_id = get_user_id().await?;
     let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
 
     let before = match (before_ts, before_id) {
         (Some(ts), Some(id)) => Some((ts, id)),
@@ -71,7 +81,9 @@ pub async fn get_phone_calls_for_contact(
 
     crate::server::database::service::phone_calls::get_phone_calls_for_contact(
         &mut conn,
+        &scope,
         contact_id,
+        filter,
         before,
         page_size + 1,
     )
@@ -87,9 +99,115 @@ pub async fn get_phone_call_by_id(
     let _logged_in_user_id = get_user_id().await?;
 
     let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
+
+    crate::server::database::service::phone_calls::get_phone_call_by_id(&mut conn, &scope, id)
+        .await
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
+
+/// A call with its contact and the calls from the same number around it,
+/// for the call detail page.
+#[server]
+pub async fn get_phone_call_record(
+    id: models::PhoneCallId,
+) -> Result<Option<models::PhoneCallRecord>, ServerFnError> {
+    let _logged_in_user_id = get_user_id().await?;
+    let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
 
-    crate::server::database::service::phone_calls::get_phone_call_by_id(&mut conn, id)
+    crate::server::database::service::phone_calls::get_phone_call_record(&mut conn, &scope, id)
         .await
         .map_err(AppError::from)
         .map_err(ServerFnError::from)
 }
+
+/// Move a call onto the contact with `phone_number`.  Admins only.
+#[server]
+pub async fn move_phone_call(
+    id: models::PhoneCallId,
+    phone_number: String,
+) -> Result<models::PhoneCall, ServerFnError> {
+    assert_is_admin().await?;
+    let user_id = get_user_id().await?;
+    let mut conn = get_database_connection().await?;
+
+    crate::server::database::service::phone_calls::move_phone_call(
+        &mut conn,
+        Some(user_id),
+        id,
+        phone_number,
+    )
+    .await
+    .map_err(AppError::from)
+    .map_err(ServerFnError::from)
+}
+
+/// Delete a call, e.g. one made while testing.  Admins only.
+#[server]
+pub async fn delete_phone_call(id: models::PhoneCallId) -> Result<(), ServerFnError> {
+    assert_is_admin().await?;
+    let user_id = get_user_id().await?;
+    let mut conn = get_database_connection().await?;
+
+    crate::server::database::service::phone_calls::delete_phone_call(&mut conn, Some(user_id), id)
+        .await
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
+
+/// Call statistics for the home page.  `utc_offset_seconds` is the
+/// browser's offset from UTC, so that days start at local midnight.
+#[server]
+pub async fn get_call_dashboard(utc_offset_seconds: i32) -> Result<CallDashboard, ServerFnError> {
+    let _logged_in_user_id = get_user_id().await?;
+    let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
+
+    let offset = chrono::FixedOffset::east_opt(utc_offset_seconds)
+        .ok_or(ServerFnError::new("Invalid UTC offset".to_string()))?;
+
+    crate::server::database::service::phone_calls::get_dashboard(
+        &mut conn,
+        &scope,
+        chrono::Utc::now(),
+        offset,
+    )
+    .await
+    .map_err(AppError::from)
+    .map_err(ServerFnError::from)
+}
+
+/// Calls by hour of the week for one contact, or for all visible calls if
+/// `contact_id` is `None`.  `utc_offset_seconds` is the browser's offset from
+/// UTC, so that hours are local.
+#[server]
+pub async fn get_call_heatmap(
+    contact_id: Option<contact_models::ContactId>,
+    utc_offset_seconds: i32,
+) -> Result<CallHeatmap, ServerFnError> {
+    let _logged_in_user_id = get_user_id().await?;
+    let mut conn = get_database_connection().await?;
+    let scope = get_group_scope(&mut conn).await?;
+
+    if let Some(contact_id) = contact_id {
+        crate::server::database::service::contacts::check_contacts_visible(
+            &mut conn,
+            &scope,
+            &[contact_id],
+        )
+        .await
+        .map_err(AppError::from)?;
+    }
+
+    let offset = chrono::FixedOffset::east_opt(utc_offset_seconds)
+        .ok_or(ServerFnError::new("Invalid UTC offset".to_string()))?;
+
+    crate::server::database::service::phone_calls::get_call_heatmap(
+        &mut conn, &scope, contact_id, offset,
+    )
+    .await
+    .map_err(AppError::from)
+    .map_err(ServerFnError::from)
+}
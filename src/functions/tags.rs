@@ -0,0 +1,76 @@
+use crate::models::tags as models;
+use dioxus::prelude::*;
+use dioxus_fullstack::{ServerFnError, server};
+
+#[cfg(feature = "server")]
+use super::common::{AppError, assert_is_admin, get_database_connection, get_user_id};
+
+#[server]
+pub async fn get_all_tags() -> Result<Vec<models::Tag>, ServerFnError> {
+    let _logged_in_user_id = get_user_id().await?;
+    let mut conn = get_database_connection().await?;
+
+    crate::server::database::service::tags::get_all_tags(&mut conn)
+        .await
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
+
+#[server]
+pub async fn get_tag_by_id(id: models::TagId) -> Result<Option<models::Tag>, ServerFnError> {
+    let _logged_in_user_id = get_user_id().await?;
+
+    let mut conn = get_database_connection().await?;
+
+    crate::server::database::service::tags::get_tag_by_id(&mut conn, id)
+        .await
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
+
+/// Tag actions apply to contacts in every group, so only admins may change
+/// tags.
+#[server]
+pub async fn create_tag(tag: models::NewTag) -> Result<models::Tag, ServerFnError> {
+    assert_is_admin().await?;
+
+    let mut conn = get_database_connection().await?;
+
+    crate::server::database::service::tags::create_tag(&mut conn, tag)
+        .await
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
+
+#[server]
+pub async fn update_tag(
+    old_tag: models::Tag,
+    change_tag: models::ChangeTag,
+) -> Result<models::Tag, ServerFnError> {
+    use crate::{functions::common::get_ldap_connection, server::database::service::tags};
+
+    assert_is_admin().await?;
+
+    let mut conn = get_database_connection().await?;
+    let (mut ldap, base_dn) = get_ldap_connection().await?;
+
+    tags::update_tag(&mut conn, &base_dn, &mut ldap, old_tag, change_tag)
+        .await
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
+
+#[server]
+pub async fn delete_tag(old_tag: models::Tag) -> Result<(), ServerFnError> {
+    use crate::{functions::common::get_ldap_connection, server::database::service::tags};
+
+    assert_is_admin().await?;
+
+    let mut conn = get_database_connection().await?;
+    let (mut ldap, base_dn) = get_ldap_connection().await?;
+
+    tags::delete_tag(&mut conn, &base_dn, &mut ldap, old_tag)
+        .await
+        .map_err(AppError::from)
+        .map_err(ServerFnError::from)
+}
@@ -4,7 +4,7 @@ use dioxus_fullstack::{ServerFnError, server};
 use crate::models::users as models;
 
 #[cfg(feature = "server")]
-use super::common::{AppError, assert_is_admin, get_database_connection};
+use super::common::{AppError, assert_is_admin, get_database_connection, get_user_id};
 
 #[server]
 pub async fn get_users() -> Result<Vec<models::User>, ServerFnError> {
@@ -31,14 +31,20 @@ pub async fn get_user_by_id(id: models::UserId) -> Result<Option<models::User>,
 #[server]
 pub async fn create_user(user: models::NewUser) -> Result<models::User, ServerFnError> {
     assert_is_admin().await?;
+    let user_id = get_user_id().await?;
     let mut conn = get_database_connection().await?;
 
     let hashed_password = password_auth::generate_hash(&user.password);
 
-    crate::server::database::service::users::create_user(&mut conn, user, &hashed_password)
-        .await
-        .map_err(AppError::from)
-        .map_err(ServerFnError::from)
+    crate::server::database::service::users::create_user(
+        &mut conn,
+        Some(user_id),
+        user,
+        &hashed_password,
+    )
+    .await
+    .map_err(AppError::from)
+    .map_err(ServerFnError::from)
 }
 
 #[server]
@@ -50,14 +56,21 @@ pub async fn update_user(
     use crate::server::database::service::users;
 
     assert_is_admin().await?;
+    let user_id = get_user_id().await?;
     let mut conn = get_database_connection().await?;
 
     let hashed_password = password.as_ref().map(password_auth::generate_hash);
 
-    users::update_user(&mut conn, old_user, change_user, hashed_password)
-        .await
-        .map_err(AppError::from)
-        .map_err(ServerFnError::from)
+    users::update_user(
+        &mut conn,
+        Some(user_id),
+        old_user,
+        change_user,
+        hashed_password,
+    )
+    .await
+    .map_err(AppError::from)
+    .map_err(ServerFnError::from)
 }
 
 #[server]
@@ -65,9 +78,10 @@ pub async fn delete_user(id: models::User) -> Result<(), ServerFnError> {
     use crate::server::database::service::users;
 
     assert_is_admin().await?;
+    let user_id = get_user_id().await?;
     let mut conn = get_database_connection().await?;
 
-    users::delete_user(&mut conn, id)
+    users::delete_user(&mut conn, Some(user_id), id)
         .await
         .map_err(AppError::from)
         .map_err(ServerFnError::from)
@@ -9,8 +9,9 @@ use dioxus_router::{Routable, Router};
 use models::contacts::ContactId;
 use models::users::{User, UserId};
 use views::{
-    ContactDetail, ContactList, DefaultList, Home, Login, Logout, PhoneCallList, UserDetail,
-    UserList, get_user,
+    ArchiveList, AuditLogList, ContactDetail, ContactList, DefaultList, Heatmap, Home,
+    LdapReconcile, Login, Logout, PhoneCallDetail, PhoneCallList, TagList, UserDetail, UserList,
+    get_user,
 };
 
 mod components;
@@ -20,6 +21,8 @@ mod models;
 mod version;
 mod views;
 
+#[cfg(feature = "server")]
+mod cli;
 #[cfg(feature = "server")]
 mod server;
 
@@ -39,14 +42,26 @@ enum Route {
     UserDetail { user_id: UserId, dialog: users::DetailsDialogReference },
     #[route("/:..segments")]
     NotFound { segments: Vec<String> },
-    #[route("/contacts?:dialog&:q&:before_id&:before_name&:before_name_null")]
-    ContactList { dialog: ListDialogReference, q: String, before_id: Option<models::contacts::ContactId>, before_name: Option<String>, before_name_null: bool },
-    #[route("/contacts/:contact_id?:dialog&:before_ts&:before_id")]
-    ContactDetail { contact_id: ContactId, dialog: ListDialogReference, before_ts: Option<chrono::DateTime<chrono::Utc>>, before_id: Option<models::phone_calls::PhoneCallId> },
-    #[route("/phone_calls?:dialog&:q&:before_ts&:before_id")]
-    PhoneCallList { dialog: ListDialogReference, q: String, before_ts: Option<chrono::DateTime<chrono::Utc>>, before_id: Option<models::phone_calls::PhoneCallId> },
+    #[route("/contacts?:dialog&:q&:tag&:sort&:before_id&:before_name&:before_name_null&:before_count&:before_ts&:before_phone_number")]
+    ContactList { dialog: ListDialogReference, q: models::search::SearchQuery, tag: String, sort: models::contacts::ContactSort, before_id: Option<models::contacts::ContactId>, before_name: Option<String>, before_name_null: bool, before_count: Option<i64>, before_ts: Option<chrono::DateTime<chrono::Utc>>, before_phone_number: Option<String> },
+    #[route("/contacts/:contact_id?:dialog&:from_date&:to_date&:action&:dest&:before_ts&:before_id")]
+    ContactDetail { contact_id: ContactId, dialog: ListDialogReference, from_date: Option<chrono::NaiveDate>, to_date: Option<chrono::NaiveDate>, action: Option<String>, dest: Option<String>, before_ts: Option<chrono::DateTime<chrono::Utc>>, before_id: Option<models::phone_calls::PhoneCallId> },
+    #[route("/phone_calls?:dialog&:q&:from_date&:to_date&:action&:dest&:before_ts&:before_id")]
+    PhoneCallList { dialog: ListDialogReference, q: models::search::SearchQuery, from_date: Option<chrono::NaiveDate>, to_date: Option<chrono::NaiveDate>, action: Option<String>, dest: Option<String>, before_ts: Option<chrono::DateTime<chrono::Utc>>, before_id: Option<models::phone_calls::PhoneCallId> },
+    #[route("/phone_calls/:phone_call_id")]
+    PhoneCallDetail { phone_call_id: models::phone_calls::PhoneCallId },
+    #[route("/heatmap")]
+    Heatmap {},
     #[route("/defaults?:dialog")]
     DefaultList { dialog: components::defaults::ListDialogReference },
+    #[route("/tags?:dialog")]
+    TagList { dialog: components::tags::ListDialogReference },
+    #[route("/archive?:dialog")]
+    ArchiveList { dialog: components::archive::ListDialogReference },
+    #[route("/audit_log?:user_id&:entity&:from_date&:to_date&:before_id")]
+    AuditLogList { user_id: Option<UserId>, entity: Option<models::audit_log::AuditEntityRef>, from_date: Option<chrono::NaiveDate>, to_date: Option<chrono::NaiveDate>, before_id: Option<models::audit_log::AuditLogId> },
+    #[route("/ldap")]
+    LdapReconcile {},
 }
 
 const FAVICON_SVG: Asset = asset!("/assets/favicon.svg");
@@ -58,8 +73,18 @@ fn main() {
 }
 
 #[cfg(feature = "server")]
-fn main() {
-    server::init(App);
+fn main() -> std::process::ExitCode {
+    match cli::Command::from_args() {
+        None => {
+            server::init(App);
+            std::process::ExitCode::SUCCESS
+        }
+        Some(Ok(command)) => cli::run(command),
+        Some(Err(usage)) => {
+            eprintln!("{usage}");
+            std::process::ExitCode::FAILURE
+        }
+    }
 }
 
 fn reload_user() {
@@ -0,0 +1,216 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::users::UserId;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AuditLogId(i64);
+
+impl AuditLogId {
+    pub fn new(id: i64) -> Self {
+        Self(id)
+    }
+    #[cfg(feature = "server")]
+    pub fn as_inner(self) -> i64 {
+        self.0
+    }
+}
+
+impl FromStr for AuditLogId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.parse()?))
+    }
+}
+
+impl std::fmt::Display for AuditLogId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum AuditParseError {
+    #[error("Invalid integer")]
+    ParseIntError(#[from] std::num::ParseIntError),
+
+    #[error("Invalid audit value: {0}")]
+    Invalid(String),
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum AuditEntity {
+    Contact,
+    Default,
+    PhoneCall,
+    User,
+}
+
+impl AuditEntity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditEntity::Contact => "contact",
+            AuditEntity::Default => "default",
+            AuditEntity::PhoneCall => "phone_call",
+            AuditEntity::User => "user",
+        }
+    }
+}
+
+impl FromStr for AuditEntity {
+    type Err = AuditParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "contact" => Ok(AuditEntity::Contact),
+            "default" => Ok(AuditEntity::Default),
+            "phone_call" => Ok(AuditEntity::PhoneCall),
+            "user" => Ok(AuditEntity::User),
+            _ => Err(AuditParseError::Invalid(s.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for AuditEntity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single audited entity, e.g. "contact-12" in URLs.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntityRef {
+    pub entity: AuditEntity,
+    pub id: i64,
+}
+
+impl FromStr for AuditEntityRef {
+    type Err = AuditParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (entity, id) = s
+            .split_once('-')
+            .ok_or_else(|| AuditParseError::Invalid(s.to_string()))?;
+        Ok(Self {
+            entity: entity.parse()?,
+            id: id.parse()?,
+        })
+    }
+}
+
+impl std::fmt::Display for AuditEntityRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.entity, self.id)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum AuditOperation {
+    Create,
+    Update,
+    Delete,
+    Merge,
+    AddNumber,
+    RemoveNumber,
+    Archive,
+    Restore,
+}
+
+impl AuditOperation {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuditOperation::Create => "create",
+            AuditOperation::Update => "update",
+            AuditOperation::Delete => "delete",
+            AuditOperation::Merge => "merge",
+            AuditOperation::AddNumber => "add_number",
+            AuditOperation::RemoveNumber => "remove_number",
+            AuditOperation::Archive => "archive",
+            AuditOperation::Restore => "restore",
+        }
+    }
+}
+
+impl FromStr for AuditOperation {
+    type Err = AuditParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "create" => Ok(AuditOperation::Create),
+            "update" => Ok(AuditOperation::Update),
+            "delete" => Ok(AuditOperation::Delete),
+            "merge" => Ok(AuditOperation::Merge),
+            "add_number" => Ok(AuditOperation::AddNumber),
+            "remove_number" => Ok(AuditOperation::RemoveNumber),
+            "archive" => Ok(AuditOperation::Archive),
+            "restore" => Ok(AuditOperation::Restore),
+            _ => Err(AuditParseError::Invalid(s.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for AuditOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct AuditLogEntry {
+    pub id: AuditLogId,
+    pub user_id: Option<UserId>,
+    pub username: Option<String>,
+    pub entity: AuditEntityRef,
+    pub operation: AuditOperation,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub inserted_at: DateTime<Utc>,
+}
+
+impl AuditLogEntry {
+    /// Top level fields that differ between before and after, as
+    /// `(field, before, after)`.  Timestamps are left out as they change on
+    /// every update.
+    pub fn changes(&self) -> Vec<(String, Option<serde_json::Value>, Option<serde_json::Value>)> {
+        let empty = serde_json::Map::new();
+        let before = self
+            .before
+            .as_ref()
+            .and_then(|v| v.as_object())
+            .unwrap_or(&empty);
+        let after = self
+            .after
+            .as_ref()
+            .and_then(|v| v.as_object())
+            .unwrap_or(&empty);
+
+        let mut keys: Vec<&String> = before.keys().chain(after.keys()).collect();
+        keys.sort();
+        keys.dedup();
+
+        keys.into_iter()
+            .filter(|key| !matches!(key.as_str(), "updated_at" | "inserted_at"))
+            .filter(|key| before.get(*key) != after.get(*key))
+            .map(|key| {
+                (
+                    key.clone(),
+                    before.get(key).cloned(),
+                    after.get(key).cloned(),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Filters for the admin audit log view.  Dates are inclusive and in UTC.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct AuditLogFilter {
+    pub user_id: Option<UserId>,
+    pub entity: Option<AuditEntityRef>,
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+}
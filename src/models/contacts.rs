@@ -2,8 +2,21 @@ use std::str::FromStr;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
+use super::audit_log::{AuditEntity, AuditEntityRef};
 use super::common::MaybeSet;
+use super::groups::GroupId;
+use super::phone_calls::DecisionSource;
+use super::tags::Tag;
+use super::users::UserId;
+
+/// The actions a contact can have.
+pub const ACTIONS: [&str; 2] = ["allow", "voicemail"];
+
+/// The action of contacts created without one, e.g. by an import or for an
+/// unknown caller.
+pub const DEFAULT_ACTION: &str = "allow";
 
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
 pub struct ContactId(i64);
@@ -32,6 +45,15 @@ impl std::fmt::Display for ContactId {
     }
 }
 
+impl From<ContactId> for AuditEntityRef {
+    fn from(id: ContactId) -> Self {
+        AuditEntityRef {
+            entity: AuditEntity::Contact,
+            id: id.0,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub struct Contact {
     pub id: ContactId,
@@ -39,12 +61,89 @@ pub struct Contact {
     pub name: Option<String>,
     pub action: String,
     pub comments: Option<String>,
+    pub tags: Vec<Tag>,
     pub phone_call_count: i64,
+    pub first_call_at: Option<DateTime<Utc>>,
+    pub last_call_at: Option<DateTime<Utc>>,
     pub inserted_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub archived_at: Option<DateTime<Utc>>,
+    pub temporary_action: Option<TemporaryAction>,
+    pub group_id: Option<GroupId>,
+}
+
+/// An action that replaces the contact's own action until it expires.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct TemporaryAction {
+    pub action: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl TemporaryAction {
+    pub fn is_active_at(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at > now
+    }
 }
 
 impl Contact {
+    /// The temporary action, unless it has expired by `now`.
+    pub fn active_temporary_action(&self, now: DateTime<Utc>) -> Option<&TemporaryAction> {
+        self.temporary_action
+            .as_ref()
+            .filter(|temporary| temporary.is_active_at(now))
+    }
+
+    /// The contact's own action at `now`, ignoring tags.
+    pub fn current_action_at(&self, now: DateTime<Utc>) -> &str {
+        match self.active_temporary_action(now) {
+            Some(temporary) => &temporary.action,
+            None => &self.action,
+        }
+    }
+
+    /// The action applied to incoming calls.
+    ///
+    /// An active temporary action comes first, as it was set for this
+    /// contact on purpose.  Otherwise a tag with an action overrides the
+    /// contact's own action; if several tags disagree, "voicemail" wins.
+    pub fn effective_action(&self) -> &str {
+        self.effective_action_at(Utc::now())
+    }
+
+    /// As [`Contact::effective_action`], at a given time.
+    pub fn effective_action_at(&self, now: DateTime<Utc>) -> &str {
+        if let Some(temporary) = self.active_temporary_action(now) {
+            return &temporary.action;
+        }
+
+        let mut tag_actions = self.tags.iter().filter_map(|t| t.action.as_deref());
+        match tag_actions.next() {
+            None => &self.action,
+            Some(first) => {
+                if first == "voicemail" || tag_actions.any(|a| a == "voicemail") {
+                    "voicemail"
+                } else {
+                    first
+                }
+            }
+        }
+    }
+
+    /// What decides [`Contact::effective_action_at`] at `now`.
+    pub fn decision_source_at(&self, now: DateTime<Utc>) -> DecisionSource {
+        if self.active_temporary_action(now).is_some() {
+            DecisionSource::TemporaryAction
+        } else if self.tags.iter().any(|t| t.action.is_some()) {
+            DecisionSource::Tag
+        } else {
+            DecisionSource::Contact
+        }
+    }
+
+    pub fn tag_names(&self) -> Vec<String> {
+        self.tags.iter().map(|t| t.name.clone()).collect()
+    }
+
     pub fn as_title(&self) -> String {
         if let Some(name) = &self.name {
             format!("{} ({})", name, self.phone_number)
@@ -54,12 +153,165 @@ impl Contact {
     }
 }
 
+/// Orders for the contact list.  Ties are broken by id.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ContactSort {
+    /// Name A-Z, contacts without a name last.
+    #[default]
+    Name,
+    /// Most calls first.
+    CallCount,
+    /// Most recently called first, never called last.
+    LastCall,
+    /// Most recently updated first.
+    Updated,
+    /// Phone number, ascending.
+    PhoneNumber,
+}
+
+impl ContactSort {
+    pub const ALL: [ContactSort; 5] = [
+        ContactSort::Name,
+        ContactSort::CallCount,
+        ContactSort::LastCall,
+        ContactSort::Updated,
+        ContactSort::PhoneNumber,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ContactSort::Name => "name",
+            ContactSort::CallCount => "call_count",
+            ContactSort::LastCall => "last_call",
+            ContactSort::Updated => "updated",
+            ContactSort::PhoneNumber => "phone_number",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            ContactSort::Name => "Name",
+            ContactSort::CallCount => "Most calls",
+            ContactSort::LastCall => "Most recent call",
+            ContactSort::Updated => "Recently updated",
+            ContactSort::PhoneNumber => "Phone number",
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("Invalid sort order '{0}'")]
+pub struct ContactSortError(String);
+
+impl FromStr for ContactSort {
+    type Err = ContactSortError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Ok(ContactSort::default());
+        }
+        ContactSort::ALL
+            .into_iter()
+            .find(|sort| sort.as_str() == s)
+            .ok_or_else(|| ContactSortError(s.to_string()))
+    }
+}
+
+impl std::fmt::Display for ContactSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The last row of a page of contacts and its value of the sort key.  The
+/// key travels with the id, so the next page starts in the right place even
+/// if that row has since changed, been archived or been deleted.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ContactCursor {
+    /// `None` if the row had no name.
+    Name(Option<String>, ContactId),
+    CallCount(i64, ContactId),
+    /// `None` if the row was never called.
+    LastCall(Option<DateTime<Utc>>, ContactId),
+    Updated(DateTime<Utc>, ContactId),
+    PhoneNumber(String, ContactId),
+}
+
+#[derive(Error, Debug, PartialEq)]
+#[error("Invalid page cursor: {0}")]
+pub struct ContactCursorError(&'static str);
+
+impl ContactCursor {
+    /// The cursor after `contact` when sorting by `sort`.
+    pub fn after(sort: ContactSort, contact: &Contact) -> Self {
+        let id = contact.id;
+        match sort {
+            ContactSort::Name => ContactCursor::Name(contact.name.clone(), id),
+            ContactSort::CallCount => ContactCursor::CallCount(contact.phone_call_count, id),
+            ContactSort::LastCall => ContactCursor::LastCall(contact.last_call_at, id),
+            ContactSort::Updated => ContactCursor::Updated(contact.updated_at, id),
+            ContactSort::PhoneNumber => {
+                ContactCursor::PhoneNumber(contact.phone_number.clone(), id)
+            }
+        }
+    }
+
+    /// Rebuild the cursor from the contact list route.  `None` for the first
+    /// page; an error if `before_id` is given without the key `sort` needs.
+    pub fn from_route(
+        sort: ContactSort,
+        before_id: Option<ContactId>,
+        before_name: Option<String>,
+        before_name_null: bool,
+        before_count: Option<i64>,
+        before_ts: Option<DateTime<Utc>>,
+        before_phone_number: Option<String>,
+    ) -> Result<Option<Self>, ContactCursorError> {
+        let Some(id) = before_id else {
+            return Ok(None);
+        };
+
+        let missing = ContactCursorError;
+        let cursor = match sort {
+            ContactSort::Name if before_name_null => ContactCursor::Name(None, id),
+            ContactSort::Name => {
+                ContactCursor::Name(Some(before_name.ok_or(missing("before_name"))?), id)
+            }
+            ContactSort::CallCount => {
+                ContactCursor::CallCount(before_count.ok_or(missing("before_count"))?, id)
+            }
+            ContactSort::LastCall => ContactCursor::LastCall(before_ts, id),
+            ContactSort::Updated => {
+                ContactCursor::Updated(before_ts.ok_or(missing("before_ts"))?, id)
+            }
+            ContactSort::PhoneNumber => ContactCursor::PhoneNumber(
+                before_phone_number.ok_or(missing("before_phone_number"))?,
+                id,
+            ),
+        };
+        Ok(Some(cursor))
+    }
+
+    pub fn sort(&self) -> ContactSort {
+        match self {
+            ContactCursor::Name(..) => ContactSort::Name,
+            ContactCursor::CallCount(..) => ContactSort::CallCount,
+            ContactCursor::LastCall(..) => ContactSort::LastCall,
+            ContactCursor::Updated(..) => ContactSort::Updated,
+            ContactCursor::PhoneNumber(..) => ContactSort::PhoneNumber,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct NewContact {
     pub phone_number: String,
     pub name: Option<String>,
     pub action: String,
     pub comments: Option<String>,
+    pub tags: Vec<String>,
+    pub temporary_action: Option<TemporaryAction>,
+    pub group_id: Option<GroupId>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -68,4 +320,242 @@ pub struct ChangeContact {
     pub name: MaybeSet<Option<String>>,
     pub action: MaybeSet<String>,
     pub comments: MaybeSet<Option<String>>,
+    pub tags: MaybeSet<Vec<String>>,
+    pub temporary_action: MaybeSet<Option<TemporaryAction>>,
+    pub group_id: MaybeSet<Option<GroupId>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MergeContacts {
+    pub survivor_id: ContactId,
+    pub merged_ids: Vec<ContactId>,
+    pub name: Option<String>,
+    pub action: String,
+    pub comments: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct MergeContactsPreview {
+    pub contacts: Vec<Contact>,
+    pub phone_call_count: i64,
+}
+
+/// A change applied to every selected contact in one go.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub enum BulkOperation {
+    SetAction(String),
+    SetName(Option<String>),
+    AddComment(String),
+    Archive,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct BulkEditContacts {
+    pub contact_ids: Vec<ContactId>,
+    pub operation: BulkOperation,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ContactNumberId(i64);
+
+impl ContactNumberId {
+    pub fn new(id: i64) -> Self {
+        Self(id)
+    }
+    #[cfg(feature = "server")]
+    pub fn as_inner(self) -> i64 {
+        self.0
+    }
+}
+
+impl FromStr for ContactNumberId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.parse()?))
+    }
+}
+
+impl std::fmt::Display for ContactNumberId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// An additional phone number belonging to a contact.
+///
+/// The primary number is [`Contact::phone_number`]; these share the contact's
+/// name and action.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct ContactNumber {
+    pub id: ContactNumberId,
+    pub contact_id: ContactId,
+    pub phone_number: String,
+    pub label: Option<String>,
+    pub inserted_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ContactNumber {
+    /// Numbers labelled "mobile" are published as LDAP `mobile`, everything
+    /// else as `telephoneNumber`.
+    pub fn is_mobile(&self) -> bool {
+        self.label
+            .as_deref()
+            .is_some_and(|label| label.eq_ignore_ascii_case("mobile"))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NewContactNumber {
+    pub contact_id: ContactId,
+    pub phone_number: String,
+    pub label: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ContactNoteRevisionId(i64);
+
+impl ContactNoteRevisionId {
+    pub fn new(id: i64) -> Self {
+        Self(id)
+    }
+    #[cfg(feature = "server")]
+    pub fn as_inner(self) -> i64 {
+        self.0
+    }
+}
+
+impl FromStr for ContactNoteRevisionId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.parse()?))
+    }
+}
+
+impl std::fmt::Display for ContactNoteRevisionId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// One version of a contact's notes, kept whenever [`Contact::comments`]
+/// changes.  `comments` is `None` if the notes were cleared.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct ContactNoteRevision {
+    pub id: ContactNoteRevisionId,
+    pub contact_id: ContactId,
+    pub user_id: Option<UserId>,
+    pub username: Option<String>,
+    pub comments: Option<String>,
+    pub inserted_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::tags::TagId;
+
+    fn contact(action: &str, tag_actions: &[Option<&str>]) -> Contact {
+        let now = Utc::now();
+        Contact {
+            id: ContactId::new(1),
+            phone_number: "0299998888".to_string(),
+            name: None,
+            action: action.to_string(),
+            comments: None,
+            tags: tag_actions
+                .iter()
+                .enumerate()
+                .map(|(i, action)| Tag {
+                    id: TagId::new(i as i64),
+                    name: format!("tag{i}"),
+                    action: action.map(String::from),
+                    inserted_at: now,
+                    updated_at: now,
+                })
+                .collect(),
+            phone_call_count: 0,
+            first_call_at: None,
+            last_call_at: None,
+            inserted_at: now,
+            updated_at: now,
+            archived_at: None,
+            temporary_action: None,
+            group_id: None,
+        }
+    }
+
+    #[test]
+    fn test_effective_action_with_tag_and_temporary_action() {
+        let now = Utc::now();
+        let expires_at = now + chrono::Duration::days(3);
+
+        let mut tagged = contact("allow", &[Some("voicemail")]);
+        assert_eq!(tagged.effective_action_at(now), "voicemail");
+        assert_eq!(tagged.decision_source_at(now), DecisionSource::Tag);
+
+        tagged.temporary_action = Some(TemporaryAction {
+            action: "allow".to_string(),
+            expires_at,
+        });
+        assert_eq!(tagged.effective_action_at(now), "allow");
+        assert_eq!(
+            tagged.decision_source_at(now),
+            DecisionSource::TemporaryAction
+        );
+
+        // Once expired the tag applies again.
+        assert_eq!(tagged.effective_action_at(expires_at), "voicemail");
+        assert_eq!(tagged.decision_source_at(expires_at), DecisionSource::Tag);
+
+        let untagged = contact("voicemail", &[None]);
+        assert_eq!(untagged.effective_action_at(now), "voicemail");
+        assert_eq!(untagged.decision_source_at(now), DecisionSource::Contact);
+    }
+
+    #[test]
+    fn test_contact_cursor_from_route() {
+        let id = Some(ContactId::new(7));
+        let ts = DateTime::<Utc>::from_timestamp(1_800_000_000, 0);
+
+        let cursor = |sort, name: Option<&str>, null, count, ts, phone: Option<&str>| {
+            ContactCursor::from_route(
+                sort,
+                id,
+                name.map(String::from),
+                null,
+                count,
+                ts,
+                phone.map(String::from),
+            )
+        };
+
+        assert_eq!(
+            ContactCursor::from_route(ContactSort::Updated, None, None, false, None, None, None),
+            Ok(None)
+        );
+        assert_eq!(
+            cursor(ContactSort::Name, None, true, None, None, None),
+            Ok(Some(ContactCursor::Name(None, ContactId::new(7))))
+        );
+        assert_eq!(
+            cursor(ContactSort::CallCount, None, false, Some(3), None, None),
+            Ok(Some(ContactCursor::CallCount(3, ContactId::new(7))))
+        );
+        assert_eq!(
+            cursor(ContactSort::LastCall, None, false, None, None, None),
+            Ok(Some(ContactCursor::LastCall(None, ContactId::new(7))))
+        );
+        assert_eq!(
+            cursor(ContactSort::Updated, None, false, None, ts, None),
+            Ok(Some(ContactCursor::Updated(ts.unwrap(), ContactId::new(7))))
+        );
+
+        assert!(cursor(ContactSort::Name, None, false, None, None, None).is_err());
+        assert!(cursor(ContactSort::CallCount, Some("Jo"), false, None, ts, None).is_err());
+        assert!(cursor(ContactSort::Updated, None, false, Some(3), None, None).is_err());
+        assert!(cursor(ContactSort::PhoneNumber, None, false, None, None, None).is_err());
+    }
 }
@@ -0,0 +1,204 @@
+//! Call statistics shown on the home page and the call heatmaps.
+
+use std::collections::BTreeMap;
+
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use super::contacts::Contact;
+
+/// Number of calls with one action.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct ActionCount {
+    pub action: String,
+    pub count: i64,
+}
+
+/// Number of calls with one action on one local day.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct DailyActionCount {
+    pub date: NaiveDate,
+    pub action: String,
+    pub count: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct CallDashboard {
+    pub today: Vec<ActionCount>,
+    pub this_week: Vec<ActionCount>,
+    pub this_month: Vec<ActionCount>,
+    /// Every day from `first_day` to today, days without calls left out.
+    pub daily: Vec<DailyActionCount>,
+    pub first_day: NaiveDate,
+    pub last_day: NaiveDate,
+    /// Contacts with the most calls in the last 30 days, with that count.
+    pub top_callers: Vec<(Contact, i64)>,
+    /// Numbers first seen in the last 7 days, newest first.
+    pub new_numbers: Vec<Contact>,
+}
+
+impl CallDashboard {
+    /// Calls per action for each day from `first_day` to `last_day`,
+    /// including days without calls.
+    pub fn days(&self) -> Vec<(NaiveDate, Vec<ActionCount>)> {
+        let mut by_day: BTreeMap<NaiveDate, Vec<ActionCount>> = BTreeMap::new();
+
+        let mut date = self.first_day;
+        while date <= self.last_day {
+            by_day.insert(date, vec![]);
+            let Some(next) = date.checked_add_days(Days::new(1)) else {
+                break;
+            };
+            date = next;
+        }
+
+        for day in &self.daily {
+            if let Some(counts) = by_day.get_mut(&day.date) {
+                counts.push(ActionCount {
+                    action: day.action.clone(),
+                    count: day.count,
+                });
+            }
+        }
+
+        by_day.into_iter().collect()
+    }
+}
+
+/// Number of calls with one action in one local hour of the week.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct HourlyActionCount {
+    /// Days from Monday, 0 to 6.
+    pub weekday: u32,
+    /// 0 to 23.
+    pub hour: u32,
+    pub action: String,
+    pub count: i64,
+}
+
+/// Calls by hour of the week, for one contact or for all calls.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct CallHeatmap {
+    /// Hours without calls left out.
+    pub hourly: Vec<HourlyActionCount>,
+}
+
+impl CallHeatmap {
+    /// Actions with at least one call, in order.
+    pub fn actions(&self) -> Vec<String> {
+        let mut actions: Vec<String> = self.hourly.iter().map(|h| h.action.clone()).collect();
+        actions.sort();
+        actions.dedup();
+        actions
+    }
+
+    /// Calls with `action` by weekday, then hour.
+    pub fn grid(&self, action: &str) -> [[i64; 24]; 7] {
+        let mut grid = [[0; 24]; 7];
+        for h in self.hourly.iter().filter(|h| h.action == action) {
+            if let Some(cell) = grid
+                .get_mut(h.weekday as usize)
+                .and_then(|day| day.get_mut(h.hour as usize))
+            {
+                *cell += h.count;
+            }
+        }
+        grid
+    }
+}
+
+pub fn total_calls(counts: &[ActionCount]) -> i64 {
+    counts.iter().map(|c| c.count).sum()
+}
+
+/// Share of the calls that went to voicemail, `None` if there were none.
+pub fn voicemail_percentage(counts: &[ActionCount]) -> Option<f64> {
+    let total = total_calls(counts);
+    if total == 0 {
+        return None;
+    }
+
+    let voicemail: i64 = counts
+        .iter()
+        .filter(|c| c.action == "voicemail")
+        .map(|c| c.count)
+        .sum();
+
+    Some(voicemail as f64 * 100.0 / total as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(action: &str, count: i64) -> ActionCount {
+        ActionCount {
+            action: action.to_string(),
+            count,
+        }
+    }
+
+    #[test]
+    fn test_voicemail_percentage() {
+        assert_eq!(voicemail_percentage(&[]), None);
+        assert_eq!(
+            voicemail_percentage(&[count("allow", 3), count("voicemail", 1)]),
+            Some(25.0)
+        );
+        assert_eq!(voicemail_percentage(&[count("allow", 2)]), Some(0.0));
+    }
+
+    #[test]
+    fn test_days_fills_gaps() {
+        let first_day = NaiveDate::from_ymd_opt(2026, 1, 30).unwrap();
+        let last_day = NaiveDate::from_ymd_opt(2026, 2, 2).unwrap();
+        let dashboard = CallDashboard {
+            today: vec![],
+            this_week: vec![],
+            this_month: vec![],
+            daily: vec![DailyActionCount {
+                date: NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+                action: "allow".to_string(),
+                count: 4,
+            }],
+            first_day,
+            last_day,
+            top_callers: vec![],
+            new_numbers: vec![],
+        };
+
+        let days = dashboard.days();
+        assert_eq!(days.len(), 4);
+        assert_eq!(days[0], (first_day, vec![]));
+        assert_eq!(days[2].1, vec![count("allow", 4)]);
+        assert_eq!(days[3], (last_day, vec![]));
+    }
+
+    #[test]
+    fn test_heatmap_grid() {
+        let hourly = |weekday, hour, action: &str, count| HourlyActionCount {
+            weekday,
+            hour,
+            action: action.to_string(),
+            count,
+        };
+        let heatmap = CallHeatmap {
+            hourly: vec![
+                hourly(0, 9, "voicemail", 2),
+                hourly(6, 23, "allow", 1),
+                hourly(0, 9, "allow", 3),
+                hourly(7, 0, "allow", 5),
+            ],
+        };
+
+        assert_eq!(heatmap.actions(), vec!["allow", "voicemail"]);
+
+        let allow = heatmap.grid("allow");
+        assert_eq!(allow[0][9], 3);
+        assert_eq!(allow[6][23], 1);
+        assert_eq!(allow.iter().flatten().sum::<i64>(), 4);
+
+        assert_eq!(heatmap.grid("voicemail")[0][9], 2);
+        assert_eq!(heatmap.grid("other"), [[0; 24]; 7]);
+    }
+}
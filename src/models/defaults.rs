@@ -3,7 +3,9 @@ use std::str::FromStr;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use super::audit_log::{AuditEntity, AuditEntityRef};
 use super::common::MaybeSet;
+use super::groups::GroupId;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct DefaultId(i64);
@@ -32,6 +34,15 @@ impl std::fmt::Display for DefaultId {
     }
 }
 
+impl From<DefaultId> for AuditEntityRef {
+    fn from(id: DefaultId) -> Self {
+        AuditEntityRef {
+            entity: AuditEntity::Default,
+            id: id.0,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub struct Default {
     pub id: DefaultId,
@@ -41,6 +52,8 @@ pub struct Default {
     pub action: String,
     pub inserted_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub archived_at: Option<DateTime<Utc>>,
+    pub group_id: Option<GroupId>,
 }
 
 impl Default {
@@ -66,6 +79,7 @@ pub struct NewDefault {
     pub regexp: Option<String>,
     pub name: Option<String>,
     pub action: String,
+    pub group_id: Option<GroupId>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -75,6 +89,7 @@ pub struct ChangeDefault {
     pub regexp: MaybeSet<Option<String>>,
     pub name: MaybeSet<Option<String>>,
     pub action: MaybeSet<String>,
+    pub group_id: MaybeSet<Option<GroupId>>,
 }
 
 #[cfg(feature = "server")]
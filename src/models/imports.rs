@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+/// A record from an import file that could not be applied as given.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct ImportConflict {
+    pub item: String,
+    pub reason: String,
+}
+
+/// Summary of an import run.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct ImportReport {
+    /// Nothing was written; the counts say what would have happened.
+    pub dry_run: bool,
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub conflicts: Vec<ImportConflict>,
+}
+
+impl ImportReport {
+    pub fn conflict(&mut self, item: impl Into<String>, reason: impl Into<String>) {
+        self.conflicts.push(ImportConflict {
+            item: item.into(),
+            reason: reason.into(),
+        });
+    }
+}
+
+/// One validated row of a contact CSV import.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct ContactRow {
+    pub line: usize,
+    pub phone_number: String,
+    pub name: Option<String>,
+    pub action: String,
+    pub comments: Option<String>,
+}
+
+/// Columns of the contact CSV export, in order.  Import reads the first four
+/// and ignores the rest.
+pub const CONTACT_CSV_COLUMNS: [&str; 6] = [
+    "phone_number",
+    "name",
+    "action",
+    "comments",
+    "phone_call_count",
+    "updated_at",
+];
@@ -0,0 +1,96 @@
+//! Differences between the contacts and their LDAP entries.
+
+use serde::{Deserialize, Serialize};
+
+use super::contacts::ContactId;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum LdapDriftKind {
+    /// A contact that should be in LDAP has no entry.
+    Missing,
+    /// An entry without a contact, or for a contact that should not be in
+    /// LDAP.
+    Extra,
+    /// An entry whose attributes differ from its contact.
+    Mismatched,
+}
+
+impl LdapDriftKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LdapDriftKind::Missing => "missing",
+            LdapDriftKind::Extra => "extra",
+            LdapDriftKind::Mismatched => "mismatched",
+        }
+    }
+}
+
+impl std::fmt::Display for LdapDriftKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct LdapDrift {
+    pub kind: LdapDriftKind,
+    pub contact_id: Option<ContactId>,
+    pub phone_number: Option<String>,
+    pub dn: Option<String>,
+    /// Attributes that differ, for [`LdapDriftKind::Mismatched`].
+    pub attributes: Vec<String>,
+}
+
+impl std::fmt::Display for LdapDrift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if let Some(contact_id) = self.contact_id {
+            write!(f, " contact {contact_id}")?;
+        }
+        if let Some(phone_number) = &self.phone_number {
+            write!(f, " {phone_number}")?;
+        }
+        if let Some(dn) = &self.dn {
+            write!(f, " {dn}")?;
+        }
+        if !self.attributes.is_empty() {
+            write!(f, " ({})", self.attributes.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct LdapReconcileReport {
+    /// Number of contacts checked, including those not meant for LDAP.
+    pub contacts: usize,
+    /// Number of entries found under the base DN.
+    pub entries: usize,
+    pub drift: Vec<LdapDrift>,
+    /// Whether the drift was fixed, or only reported.
+    pub fixed: bool,
+}
+
+impl LdapReconcileReport {
+    pub fn count(&self, kind: LdapDriftKind) -> usize {
+        self.drift.iter().filter(|d| d.kind == kind).count()
+    }
+}
+
+impl std::fmt::Display for LdapReconcileReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} contacts, {} entries: {} missing, {} extra, {} mismatched",
+            self.contacts,
+            self.entries,
+            self.count(LdapDriftKind::Missing),
+            self.count(LdapDriftKind::Extra),
+            self.count(LdapDriftKind::Mismatched),
+        )?;
+        if self.fixed && !self.drift.is_empty() {
+            write!(f, ", all fixed")?;
+        }
+        Ok(())
+    }
+}
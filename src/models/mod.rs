@@ -1,5 +1,12 @@
+pub mod audit_log;
 pub mod common;
 pub mod contacts;
+pub mod dashboard;
 pub mod defaults;
+pub mod groups;
+pub mod imports;
+pub mod ldap;
 pub mod phone_calls;
+pub mod search;
+pub mod tags;
 pub mod users;
@@ -1,9 +1,12 @@
 use std::str::FromStr;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveTime, TimeDelta, Utc};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
-use crate::models::contacts::ContactId;
+use crate::models::audit_log::{AuditEntity, AuditEntityRef};
+use crate::models::contacts::{Contact, ContactId};
+use crate::models::search::SearchQuery;
 
 #[cfg(feature = "server")]
 use super::common::MaybeSet;
@@ -36,13 +39,89 @@ impl std::fmt::Display for PhoneCallId {
     }
 }
 
+impl From<PhoneCallId> for AuditEntityRef {
+    fn from(id: PhoneCallId) -> Self {
+        AuditEntityRef {
+            entity: AuditEntity::PhoneCall,
+            id: id.0,
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("Invalid decision source: {0}")]
+pub struct DecisionSourceError(String);
+
+/// What decided the action of a call.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum DecisionSource {
+    /// The contact's own action.
+    Contact,
+    /// A temporary action of the contact.
+    TemporaryAction,
+    /// A tag of the contact with an action.
+    Tag,
+    /// A default matching the number of a new contact.
+    Default,
+    /// A new contact without a matching default.
+    Fallback,
+}
+
+impl DecisionSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DecisionSource::Contact => "contact",
+            DecisionSource::TemporaryAction => "temporary_action",
+            DecisionSource::Tag => "tag",
+            DecisionSource::Default => "default",
+            DecisionSource::Fallback => "fallback",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            DecisionSource::Contact => "Action of the contact",
+            DecisionSource::TemporaryAction => "Temporary action of the contact",
+            DecisionSource::Tag => "Action of a tag of the contact",
+            DecisionSource::Default => "Default for a new number",
+            DecisionSource::Fallback => "New number without a default",
+        }
+    }
+}
+
+impl FromStr for DecisionSource {
+    type Err = DecisionSourceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "contact" => Ok(DecisionSource::Contact),
+            "temporary_action" => Ok(DecisionSource::TemporaryAction),
+            "tag" => Ok(DecisionSource::Tag),
+            "default" => Ok(DecisionSource::Default),
+            "fallback" => Ok(DecisionSource::Fallback),
+            _ => Err(DecisionSourceError(s.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for DecisionSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
 pub struct PhoneCall {
     pub id: PhoneCallId,
     pub action: String,
     pub contact_id: ContactId,
     pub destination_number: Option<String>,
-    pub source_number: String,
+    /// `None` once the call has been anonymised.
+    pub source_number: Option<String>,
+    /// Name of the contact when the call came in.
+    pub contact_name: Option<String>,
+    /// `None` for calls recorded before decisions were kept.
+    pub decision_source: Option<DecisionSource>,
     pub inserted_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -54,6 +133,8 @@ pub struct NewPhoneCall {
     pub contact_id: ContactId,
     pub destination_number: Option<String>,
     pub source_number: String,
+    pub contact_name: Option<String>,
+    pub decision_source: DecisionSource,
 }
 
 #[cfg(feature = "server")]
@@ -63,4 +144,208 @@ pub struct ChangePhoneCall {
     pub contact_id: MaybeSet<ContactId>,
     pub destination_number: MaybeSet<Option<String>>,
     pub source_number: MaybeSet<String>,
+    pub contact_name: MaybeSet<Option<String>>,
+}
+
+/// A single call with its context, for the call detail page.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct PhoneCallRecord {
+    pub phone_call: PhoneCall,
+    /// The contact the call belongs to now.
+    pub contact: Contact,
+    /// Calls from the same number just before this one, newest first.
+    pub earlier: Vec<PhoneCall>,
+    /// Calls from the same number just after this one, oldest first.
+    pub later: Vec<PhoneCall>,
+}
+
+/// Filters on the calls themselves, used by the call log and the calls of
+/// a contact.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Eq, PartialEq)]
+pub struct PhoneCallFilter {
+    pub from_date: Option<NaiveDate>,
+    pub to_date: Option<NaiveDate>,
+    pub action: Option<String>,
+    pub dest: Option<String>,
+    /// The browser's offset from UTC, so that the dates here and in
+    /// `after:` and `before:` start at local midnight, as on the dashboard.
+    pub utc_offset_seconds: i32,
+}
+
+impl PhoneCallFilter {
+    /// Local midnight at the start of `date`.  Dates too close to the ends
+    /// of the calendar to shift are taken as UTC.
+    pub fn start_of(&self, date: NaiveDate) -> DateTime<Utc> {
+        let offset = TimeDelta::seconds(i64::from(self.utc_offset_seconds));
+        let midnight = date.and_time(NaiveTime::MIN);
+        midnight
+            .checked_sub_signed(offset)
+            .unwrap_or(midnight)
+            .and_utc()
+    }
+}
+
+/// Columns of the call log CSV export, in order.  The NDJSON export uses the
+/// same names as keys.
+pub const PHONE_CALL_CSV_COLUMNS: [&str; 7] = [
+    "id",
+    "inserted_at",
+    "contact_id",
+    "contact_name",
+    "action",
+    "source_number",
+    "destination_number",
+];
+
+/// Where the call log export is served.
+pub const PHONE_CALL_EXPORT_PATH: &str = "/api/phone_calls/export";
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PhoneCallExportFormat {
+    #[default]
+    Csv,
+    /// One JSON object per line.
+    Ndjson,
+}
+
+impl PhoneCallExportFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PhoneCallExportFormat::Csv => "csv",
+            PhoneCallExportFormat::Ndjson => "ndjson",
+        }
+    }
+
+    pub fn mime(self) -> &'static str {
+        match self {
+            PhoneCallExportFormat::Csv => "text/csv",
+            PhoneCallExportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn filename(self) -> &'static str {
+        match self {
+            PhoneCallExportFormat::Csv => "phone_calls.csv",
+            PhoneCallExportFormat::Ndjson => "phone_calls.ndjson",
+        }
+    }
+}
+
+/// Percent-encode everything but the unreserved characters of RFC 3986.
+fn encode_query_value(value: &str) -> String {
+    let mut out = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+/// Link to download the calls matching `query` and `filter`.
+pub fn export_url(
+    format: PhoneCallExportFormat,
+    query: &SearchQuery,
+    filter: &PhoneCallFilter,
+) -> String {
+    let mut params = vec![("format", format.as_str().to_string())];
+    if !query.is_empty() {
+        params.push(("q", query.to_string()));
+    }
+    if let Some(from_date) = filter.from_date {
+        params.push(("from_date", from_date.to_string()));
+    }
+    if let Some(to_date) = filter.to_date {
+        params.push(("to_date", to_date.to_string()));
+    }
+    if let Some(action) = &filter.action {
+        params.push(("action", action.clone()));
+    }
+    if let Some(dest) = &filter.dest {
+        params.push(("dest", dest.clone()));
+    }
+    if filter.utc_offset_seconds != 0 {
+        params.push(("utc_offset", filter.utc_offset_seconds.to_string()));
+    }
+
+    let params: Vec<String> = params
+        .into_iter()
+        .map(|(key, value)| format!("{key}={}", encode_query_value(&value)))
+        .collect();
+    format!("{PHONE_CALL_EXPORT_PATH}?{}", params.join("&"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decision_source_round_trip() {
+        for source in [
+            DecisionSource::Contact,
+            DecisionSource::TemporaryAction,
+            DecisionSource::Tag,
+            DecisionSource::Default,
+            DecisionSource::Fallback,
+        ] {
+            assert_eq!(source.as_str().parse::<DecisionSource>().unwrap(), source);
+        }
+        assert!("unknown".parse::<DecisionSource>().is_err());
+    }
+
+    #[test]
+    fn test_export_url() {
+        assert_eq!(
+            export_url(
+                PhoneCallExportFormat::Csv,
+                &SearchQuery::default(),
+                &PhoneCallFilter::default()
+            ),
+            "/api/phone_calls/export?format=csv"
+        );
+
+        let filter = PhoneCallFilter {
+            from_date: NaiveDate::from_ymd_opt(2026, 1, 1),
+            to_date: None,
+            action: Some("voicemail".to_string()),
+            dest: Some("+61 2".to_string()),
+            utc_offset_seconds: 36000,
+        };
+        assert_eq!(
+            export_url(
+                PhoneCallExportFormat::Ndjson,
+                &SearchQuery::from("name:\"Jo Smith\"".to_string()),
+                &filter
+            ),
+            "/api/phone_calls/export?format=ndjson&q=name%3A%22Jo%20Smith%22\
+             &from_date=2026-01-01&action=voicemail&dest=%2B61%202&utc_offset=36000"
+        );
+    }
+
+    #[test]
+    fn test_filter_start_of() {
+        let date = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+
+        assert_eq!(
+            PhoneCallFilter::default().start_of(date),
+            at("2026-10-19T00:00:00Z")
+        );
+
+        let filter = PhoneCallFilter {
+            utc_offset_seconds: 11 * 3600,
+            ..PhoneCallFilter::default()
+        };
+        assert_eq!(filter.start_of(date), at("2026-10-18T13:00:00Z"));
+
+        let filter = PhoneCallFilter {
+            utc_offset_seconds: -5 * 3600,
+            ..PhoneCallFilter::default()
+        };
+        assert_eq!(filter.start_of(date), at("2026-10-19T05:00:00Z"));
+    }
 }
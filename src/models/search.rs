@@ -0,0 +1,295 @@
+//! Search box query language shared by the contact and phone call lists.
+//!
+//! A query is a list of space separated terms.  `key:value` terms with one of
+//! the keys below filter the results, anything else (including `Re: invoice`)
+//! is free text matched against names, numbers and tags.  Values containing
+//! spaces can be quoted, e.g. `name:"John Smith"`.
+//!
+//! ```text
+//! action:voicemail  name:none  calls:>5  tag:family
+//! after:2026-01-01  before:2026-02-01  dest:0299998888
+//! ```
+
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Comparison {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SearchTerm {
+    /// Free text, matched against names, numbers and tags.
+    Text(String),
+    /// `action:voicemail`
+    Action(String),
+    /// `name:none` for no name, otherwise a name containing the value.
+    Name(Option<String>),
+    /// `calls:>5`, compared with the contact's total number of calls.
+    Calls(Comparison, i64),
+    /// `tag:family`
+    Tag(String),
+    /// `after:2026-01-01`, calls on or after local midnight of the date.
+    After(NaiveDate),
+    /// `before:2026-01-01`, calls before local midnight of the date.
+    Before(NaiveDate),
+    /// `dest:0299998888`, calls to this number.
+    Dest(String),
+}
+
+#[derive(Error, Debug, Clone, Eq, PartialEq)]
+pub enum SearchQueryError {
+    #[error("Unknown filter '{0}'")]
+    UnknownFilter(String),
+
+    #[error("Invalid value for '{0}': {1}")]
+    InvalidValue(String, String),
+
+    #[error("Unterminated quote")]
+    UnterminatedQuote,
+}
+
+/// The text typed into the search box.
+///
+/// Kept as typed, so that an incomplete query such as `calls:>` still
+/// round trips through the URL; [`SearchQuery::terms`] reports any errors.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct SearchQuery(String);
+
+impl SearchQuery {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.trim().is_empty()
+    }
+
+    pub fn terms(&self) -> Result<Vec<SearchTerm>, SearchQueryError> {
+        tokenize(&self.0)?
+            .into_iter()
+            .map(|(key, value)| parse_term(key, value))
+            .collect()
+    }
+}
+
+impl From<String> for SearchQuery {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<SearchQuery> for String {
+    fn from(value: SearchQuery) -> Self {
+        value.0
+    }
+}
+
+impl FromStr for SearchQuery {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl std::fmt::Display for SearchQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The free text terms joined by spaces.
+pub fn free_text(terms: &[SearchTerm]) -> String {
+    terms
+        .iter()
+        .filter_map(|term| match term {
+            SearchTerm::Text(text) => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Keys of the `key:value` filters, see [`parse_term`].
+const FILTER_KEYS: [&str; 7] = ["action", "name", "calls", "tag", "after", "before", "dest"];
+
+/// Split into `(key, value)` pairs.  A key is one of [`FILTER_KEYS`] before
+/// the first unquoted `:`; any other word followed by `:` is free text.
+fn tokenize(s: &str) -> Result<Vec<(Option<String>, String)>, SearchQueryError> {
+    let mut tokens = vec![];
+    let mut key = None;
+    let mut value = String::new();
+    let mut started = false;
+    let mut quoted = false;
+
+    for c in s.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                started = true;
+            }
+            ':' if !quoted
+                && key.is_none()
+                && FILTER_KEYS
+                    .iter()
+                    .any(|filter| value.eq_ignore_ascii_case(filter)) =>
+            {
+                key = Some(std::mem::take(&mut value).to_lowercase());
+            }
+            c if c.is_whitespace() && !quoted => {
+                if started {
+                    tokens.push((key.take(), std::mem::take(&mut value)));
+                    started = false;
+                }
+            }
+            c => {
+                value.push(c);
+                started = true;
+            }
+        }
+    }
+
+    if quoted {
+        return Err(SearchQueryError::UnterminatedQuote);
+    }
+    if started {
+        tokens.push((key, value));
+    }
+
+    Ok(tokens)
+}
+
+fn parse_comparison(value: &str) -> (Comparison, &str) {
+    [
+        (">=", Comparison::Ge),
+        ("<=", Comparison::Le),
+        (">", Comparison::Gt),
+        ("<", Comparison::Lt),
+        ("=", Comparison::Eq),
+    ]
+    .into_iter()
+    .find_map(|(prefix, comparison)| value.strip_prefix(prefix).map(|rest| (comparison, rest)))
+    .unwrap_or((Comparison::Eq, value))
+}
+
+fn parse_term(key: Option<String>, value: String) -> Result<SearchTerm, SearchQueryError> {
+    let Some(key) = key else {
+        return Ok(SearchTerm::Text(value));
+    };
+
+    let invalid = |reason: &str| SearchQueryError::InvalidValue(key.clone(), reason.to_string());
+
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(invalid("value required"));
+    }
+
+    let date =
+        || NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| invalid("expected YYYY-MM-DD"));
+
+    match key.as_str() {
+        "action" => Ok(SearchTerm::Action(value.to_lowercase())),
+        "name" if value.eq_ignore_ascii_case("none") => Ok(SearchTerm::Name(None)),
+        "name" => Ok(SearchTerm::Name(Some(value.to_string()))),
+        "calls" => {
+            let (comparison, number) = parse_comparison(value);
+            let number = number
+                .trim()
+                .parse()
+                .map_err(|_| invalid("expected a number, e.g. >5"))?;
+            Ok(SearchTerm::Calls(comparison, number))
+        }
+        "tag" => Ok(SearchTerm::Tag(value.to_lowercase())),
+        "after" => Ok(SearchTerm::After(date()?)),
+        "before" => Ok(SearchTerm::Before(date()?)),
+        "dest" => Ok(SearchTerm::Dest(value.to_string())),
+        _ => Err(SearchQueryError::UnknownFilter(key)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(s: &str) -> Result<Vec<SearchTerm>, SearchQueryError> {
+        SearchQuery::from(s.to_string()).terms()
+    }
+
+    #[test]
+    fn test_filters() {
+        assert_eq!(
+            terms("john action:voicemail name:none calls:>5 tag:Family"),
+            Ok(vec![
+                SearchTerm::Text("john".to_string()),
+                SearchTerm::Action("voicemail".to_string()),
+                SearchTerm::Name(None),
+                SearchTerm::Calls(Comparison::Gt, 5),
+                SearchTerm::Tag("family".to_string()),
+            ])
+        );
+        assert_eq!(
+            terms("after:2026-01-01 dest:0299998888 calls:<=2"),
+            Ok(vec![
+                SearchTerm::After(NaiveDate::from_ymd_opt(2026, 1, 1).unwrap()),
+                SearchTerm::Dest("0299998888".to_string()),
+                SearchTerm::Calls(Comparison::Le, 2),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_quotes_and_free_text() {
+        let parsed = terms(r#"name:"John Smith" "0412 345" 10:30"#).unwrap();
+        assert_eq!(
+            parsed,
+            vec![
+                SearchTerm::Name(Some("John Smith".to_string())),
+                SearchTerm::Text("0412 345".to_string()),
+                SearchTerm::Text("10:30".to_string()),
+            ]
+        );
+        assert_eq!(free_text(&parsed), "0412 345 10:30");
+        assert_eq!(terms("  "), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_unknown_keys_are_free_text() {
+        let text = |s: &str| SearchTerm::Text(s.to_string());
+        assert_eq!(terms("Re: invoice"), Ok(vec![text("Re:"), text("invoice")]));
+        assert_eq!(
+            terms("Note: call back"),
+            Ok(vec![text("Note:"), text("call"), text("back")])
+        );
+        assert_eq!(terms("colour:red"), Ok(vec![text("colour:red")]));
+        assert_eq!(
+            terms("Tag:family"),
+            Ok(vec![SearchTerm::Tag("family".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            terms("calls:>"),
+            Err(SearchQueryError::InvalidValue(..))
+        ));
+        assert!(matches!(
+            terms("after:yesterday"),
+            Err(SearchQueryError::InvalidValue(..))
+        ));
+        assert_eq!(
+            terms("name:\"John"),
+            Err(SearchQueryError::UnterminatedQuote)
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::common::MaybeSet;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct TagId(i64);
+
+impl TagId {
+    pub fn new(id: i64) -> Self {
+        Self(id)
+    }
+    #[cfg(feature = "server")]
+    pub fn as_inner(self) -> i64 {
+        self.0
+    }
+}
+
+impl FromStr for TagId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.parse()?))
+    }
+}
+
+impl std::fmt::Display for TagId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct Tag {
+    pub id: TagId,
+    pub name: String,
+    pub action: Option<String>,
+    pub inserted_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NewTag {
+    pub name: String,
+    pub action: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChangeTag {
+    pub name: MaybeSet<String>,
+    pub action: MaybeSet<Option<String>>,
+}
+
+/// Split a comma separated list of tag names, dropping blanks and duplicates.
+pub fn parse_tag_names(str: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for name in str.split(',').map(|s| s.trim().to_lowercase()) {
+        if !name.is_empty() && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
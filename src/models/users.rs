@@ -1,5 +1,6 @@
 use std::str::FromStr;
 
+use super::audit_log::{AuditEntity, AuditEntityRef};
 use super::common::MaybeSet;
 
 use serde::{Deserialize, Serialize};
@@ -33,6 +34,15 @@ impl std::fmt::Display for UserId {
     }
 }
 
+impl From<UserId> for AuditEntityRef {
+    fn from(id: UserId) -> Self {
+        AuditEntityRef {
+            entity: AuditEntity::User,
+            id: id.0,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
 pub struct User {
     pub id: UserId,
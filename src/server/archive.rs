@@ -0,0 +1,72 @@
+//! Scheduled purge of archived contacts and defaults.
+//!
+//! Archived entities are kept for `ARCHIVE_RETENTION_DAYS` (default 30) so
+//! that accidental deletions can be restored, then deleted permanently.
+
+use std::env;
+
+use thiserror::Error;
+
+use crate::server::database::connection::{self as database, DatabasePool};
+use crate::server::database::service::{contacts, defaults};
+
+const DEFAULT_RETENTION_DAYS: i64 = 30;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Database error: {0}")]
+    Database(#[from] database::Error),
+    #[error("Contacts error: {0}")]
+    Contacts(#[from] contacts::Error),
+    #[error("Defaults error: {0}")]
+    Defaults(#[from] defaults::Error),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    pub retention: chrono::Duration,
+}
+
+impl Config {
+    pub fn get_from_env() -> Config {
+        let days = env::var("ARCHIVE_RETENTION_DAYS")
+            .map(|days| {
+                days.parse()
+                    .expect("ARCHIVE_RETENTION_DAYS must be a number of days")
+            })
+            .unwrap_or(DEFAULT_RETENTION_DAYS);
+        Config {
+            retention: chrono::Duration::days(days),
+        }
+    }
+}
+
+/// Delete everything archived for longer than the retention period.
+pub async fn purge_archived(pool: &DatabasePool, config: &Config) -> Result<(), Error> {
+    let mut conn = pool.get().await.map_err(database::Error::from)?;
+    let cutoff = chrono::Utc::now() - config.retention;
+
+    let contacts = contacts::purge_archived_contacts(&mut conn, cutoff).await?;
+    let defaults = defaults::purge_archived_defaults(&mut conn, cutoff).await?;
+
+    if contacts > 0 || defaults > 0 {
+        tracing::info!("Purged {contacts} archived contacts and {defaults} archived defaults");
+    }
+
+    Ok(())
+}
+
+/// Run [`purge_archived`] every `period`, forever.
+pub async fn continuously_purge_archived(
+    pool: DatabasePool,
+    config: Config,
+    period: tokio::time::Duration,
+) {
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+        if let Err(err) = purge_archived(&pool, &config).await {
+            tracing::error!("Error purging archived entities: {err}");
+        }
+    }
+}
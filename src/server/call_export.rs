@@ -0,0 +1,101 @@
+use axum::Extension;
+use axum::body::Body;
+use axum::extract::Query;
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use chrono::NaiveDate;
+use futures::TryStreamExt;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::models::phone_calls::{PhoneCallExportFormat, PhoneCallFilter};
+use crate::models::search::SearchQuery;
+use crate::server::auth::Session;
+use crate::server::database::connection as database;
+use crate::server::database::models::groups::get_scope_for_user;
+use crate::server::database::service::phone_calls;
+
+/// Query string of the export, named as in the call log route.
+#[derive(Debug, Deserialize)]
+pub struct ExportParams {
+    #[serde(default)]
+    format: PhoneCallExportFormat,
+    #[serde(default)]
+    q: SearchQuery,
+    from_date: Option<NaiveDate>,
+    to_date: Option<NaiveDate>,
+    action: Option<String>,
+    dest: Option<String>,
+    #[serde(default)]
+    utc_offset: i32,
+}
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Not logged in")]
+    NotAuthorized,
+    #[error("Database error: {0}")]
+    Database(#[from] database::Error),
+    #[error("Phone calls error: {0}")]
+    PhoneCalls(#[from] phone_calls::Error),
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        match self {
+            Error::NotAuthorized => (StatusCode::UNAUTHORIZED, self.to_string()).into_response(),
+            Error::PhoneCalls(phone_calls::Error::InvalidQuery(_)) => {
+                (StatusCode::BAD_REQUEST, self.to_string()).into_response()
+            }
+            Error::Database(e) => {
+                tracing::error!("Database error: {:?}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response()
+            }
+            Error::PhoneCalls(e) => {
+                tracing::error!("Phone calls error: {:?}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Internal Server Error").into_response()
+            }
+        }
+    }
+}
+
+/// Download the call log matching the filters, streamed as it is read.
+/// Only calls of contacts in the user's groups are included.
+pub async fn get_handler(
+    Extension(session): Extension<Session>,
+    Extension(db): Extension<database::DatabasePool>,
+    Query(params): Query<ExportParams>,
+) -> Result<Response, Error> {
+    let Some((user_id, is_admin)) = session.user().await.as_ref().map(|x| (x.id, x.is_admin))
+    else {
+        return Err(Error::NotAuthorized);
+    };
+
+    let mut conn = db.get().await.map_err(database::Error::from)?;
+    let scope = get_scope_for_user(&mut conn, user_id, is_admin)
+        .await
+        .map_err(database::Error::from)?;
+
+    let filter = PhoneCallFilter {
+        from_date: params.from_date,
+        to_date: params.to_date,
+        action: params.action,
+        dest: params.dest,
+        utc_offset_seconds: params.utc_offset,
+    };
+
+    let stream = phone_calls::export_phone_calls(conn, scope, params.q, filter, params.format)?
+        .inspect_err(|err| tracing::error!("Phone call export failed: {:?}", err));
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, params.format.mime().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", params.format.filename()),
+            ),
+        ],
+        Body::from_stream(stream),
+    )
+        .into_response())
+}
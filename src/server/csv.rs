@@ -0,0 +1,154 @@
+//! Minimal CSV reader and writer (RFC 4180).
+//!
+//! Enough for spreadsheet round-tripping: quoted fields, embedded commas,
+//! quotes and newlines, and both CRLF and LF line endings.
+
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Line {0}: unterminated quoted field")]
+    UnterminatedQuote(usize),
+    #[error("Line {0}: unexpected character after closing quote")]
+    TrailingCharacters(usize),
+}
+
+fn encode_field(field: &str, out: &mut String) {
+    if field.contains([',', '"', '\n', '\r']) {
+        out.push('"');
+        out.push_str(&field.replace('"', "\"\""));
+        out.push('"');
+    } else {
+        out.push_str(field);
+    }
+}
+
+pub fn encode<R, F>(records: R) -> String
+where
+    R: IntoIterator,
+    R::Item: IntoIterator<Item = F>,
+    F: AsRef<str>,
+{
+    let mut out = String::new();
+    for record in records {
+        for (i, field) in record.into_iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            encode_field(field.as_ref(), &mut out);
+        }
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Parse CSV text into records.  Blank lines are skipped.  Each record is
+/// returned with the line number it started on.
+pub fn decode(text: &str) -> Result<Vec<(usize, Vec<String>)>, Error> {
+    let mut records = vec![];
+    let mut record: Vec<String> = vec![];
+    let mut field = String::new();
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = text
+        .strip_prefix('\u{feff}')
+        .unwrap_or(text)
+        .chars()
+        .peekable();
+
+    let mut end_record = |record: &mut Vec<String>, field: &mut String, record_line: usize| {
+        record.push(std::mem::take(field));
+        let record = std::mem::take(record);
+        if !(record.len() == 1 && record[0].is_empty()) {
+            records.push((record_line, record));
+        }
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if field.is_empty() => {
+                let quote_line = line;
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            field.push(c);
+                        }
+                        None => return Err(Error::UnterminatedQuote(quote_line)),
+                    }
+                }
+                match chars.peek() {
+                    None | Some(',') | Some('\n') | Some('\r') => {}
+                    Some(_) => return Err(Error::TrailingCharacters(line)),
+                }
+            }
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                end_record(&mut record, &mut field, record_line);
+                line += 1;
+                record_line = line;
+            }
+            c => field.push(c),
+        }
+    }
+
+    if !field.is_empty() || !record.is_empty() {
+        end_record(&mut record, &mut field, record_line);
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let records = vec![
+            vec!["phone_number", "name", "comments"],
+            vec!["+61412345678", "Smith, John", "said \"hi\"\nthen hung up"],
+            vec!["0398765432", "", ""],
+        ];
+
+        let text = encode(records.clone());
+        assert_eq!(
+            text,
+            "phone_number,name,comments\r\n\
+             +61412345678,\"Smith, John\",\"said \"\"hi\"\"\nthen hung up\"\r\n\
+             0398765432,,\r\n"
+        );
+
+        let decoded = decode(&text).unwrap();
+        let lines: Vec<usize> = decoded.iter().map(|(line, _)| *line).collect();
+        assert_eq!(lines, vec![1, 2, 4]);
+        let decoded: Vec<Vec<String>> = decoded.into_iter().map(|(_, r)| r).collect();
+        assert_eq!(decoded, records);
+    }
+
+    #[test]
+    fn test_decode_lf_without_trailing_newline() {
+        let decoded = decode("\u{feff}a,b\n\nc,d").unwrap();
+        assert_eq!(
+            decoded,
+            vec![
+                (1, vec!["a".to_string(), "b".to_string()]),
+                (3, vec!["c".to_string(), "d".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(decode("a,\"b\nc"), Err(Error::UnterminatedQuote(1)));
+        assert_eq!(decode("a,\"b\"c\n"), Err(Error::TrailingCharacters(1)));
+    }
+}
@@ -1,4 +1,5 @@
 pub mod connection;
 pub mod models;
 pub mod schema;
+pub mod search;
 pub mod service;
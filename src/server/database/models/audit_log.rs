@@ -0,0 +1,121 @@
+use diesel::prelude::*;
+use diesel::{ExpressionMethods, QueryDsl, Queryable, Selectable};
+use diesel_async::RunQueryDsl;
+
+use crate::models::audit_log as model;
+use crate::models::users::UserId;
+use crate::server::database::{connection::DatabaseConnection, schema};
+
+#[allow(dead_code)]
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = schema::audit_log)]
+pub struct AuditLog {
+    pub id: i64,
+    pub user_id: Option<i64>,
+    pub entity_type: String,
+    pub entity_id: i64,
+    pub operation: String,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub inserted_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl AuditLog {
+    pub fn into_model(
+        self,
+        username: Option<String>,
+    ) -> Result<model::AuditLogEntry, diesel::result::Error> {
+        let to_error = |err: model::AuditParseError| {
+            diesel::result::Error::DeserializationError(Box::new(err))
+        };
+
+        Ok(model::AuditLogEntry {
+            id: model::AuditLogId::new(self.id),
+            user_id: self.user_id.map(UserId::new),
+            username,
+            entity: model::AuditEntityRef {
+                entity: self.entity_type.parse().map_err(to_error)?,
+                id: self.entity_id,
+            },
+            operation: self.operation.parse().map_err(to_error)?,
+            before: self.before,
+            after: self.after,
+            inserted_at: self.inserted_at,
+        })
+    }
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = schema::audit_log)]
+pub struct NewAuditLog {
+    pub user_id: Option<i64>,
+    pub entity_type: String,
+    pub entity_id: i64,
+    pub operation: String,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub inserted_at: chrono::DateTime<chrono::Utc>,
+}
+
+pub async fn create_audit_log(
+    conn: &mut DatabaseConnection,
+    entry: NewAuditLog,
+) -> Result<(), diesel::result::Error> {
+    use crate::server::database::schema::audit_log::table;
+
+    diesel::insert_into(table)
+        .values(&entry)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Newest first, keyset paginated on id.
+pub async fn search_audit_log(
+    conn: &mut DatabaseConnection,
+    filter: &model::AuditLogFilter,
+    before_id: Option<i64>,
+    page_size: i64,
+) -> Result<Vec<(AuditLog, Option<String>)>, diesel::result::Error> {
+    use crate::server::database::schema::audit_log::dsl as q;
+    use crate::server::database::schema::audit_log::table;
+    use crate::server::database::schema::users::dsl as u;
+    use crate::server::database::schema::users::table as users_table;
+
+    let mut query = table
+        .left_join(users_table.on(u::id.nullable().eq(q::user_id)))
+        .select((AuditLog::as_select(), u::username.nullable()))
+        .order(q::id.desc())
+        .limit(page_size)
+        .into_boxed();
+
+    if let Some(user_id) = filter.user_id {
+        query = query.filter(q::user_id.eq(user_id.as_inner()));
+    }
+
+    if let Some(entity) = filter.entity {
+        query = query
+            .filter(q::entity_type.eq(entity.entity.as_str()))
+            .filter(q::entity_id.eq(entity.id));
+    }
+
+    if let Some(from_date) = filter.from_date {
+        let from = from_date.and_time(chrono::NaiveTime::MIN).and_utc();
+        query = query.filter(q::inserted_at.ge(from));
+    }
+
+    if let Some(to_date) = filter.to_date
+        && let Some(next_day) = to_date.succ_opt()
+    {
+        let to = next_day.and_time(chrono::NaiveTime::MIN).and_utc();
+        query = query.filter(q::inserted_at.lt(to));
+    }
+
+    if let Some(before_id) = before_id {
+        query = query.filter(q::id.lt(before_id));
+    }
+
+    query.get_results(conn).await
+}
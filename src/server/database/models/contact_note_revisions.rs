@@ -0,0 +1,90 @@
+use diesel::prelude::*;
+use diesel::{ExpressionMethods, QueryDsl, Queryable, Selectable};
+use diesel_async::RunQueryDsl;
+
+use crate::models::contacts as model;
+use crate::models::users::UserId;
+use crate::server::database::{connection::DatabaseConnection, schema};
+
+#[allow(dead_code)]
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = schema::contact_note_revisions)]
+pub struct ContactNoteRevision {
+    pub id: i64,
+    pub contact_id: i64,
+    pub user_id: Option<i64>,
+    pub comments: Option<String>,
+    pub inserted_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl ContactNoteRevision {
+    pub fn into_model(self, username: Option<String>) -> model::ContactNoteRevision {
+        model::ContactNoteRevision {
+            id: model::ContactNoteRevisionId::new(self.id),
+            contact_id: model::ContactId::new(self.contact_id),
+            user_id: self.user_id.map(UserId::new),
+            username,
+            comments: self.comments,
+            inserted_at: self.inserted_at,
+        }
+    }
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = schema::contact_note_revisions)]
+pub struct NewContactNoteRevision {
+    pub contact_id: i64,
+    pub user_id: Option<i64>,
+    pub comments: Option<String>,
+    pub inserted_at: chrono::DateTime<chrono::Utc>,
+}
+
+pub async fn create_note_revision(
+    conn: &mut DatabaseConnection,
+    revision: NewContactNoteRevision,
+) -> Result<(), diesel::result::Error> {
+    use crate::server::database::schema::contact_note_revisions::table;
+
+    diesel::insert_into(table)
+        .values(&revision)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Revisions of a contact's notes with the username of the author, newest
+/// first.
+pub async fn get_note_revisions_for_contact(
+    conn: &mut DatabaseConnection,
+    contact_id: i64,
+) -> Result<Vec<(ContactNoteRevision, Option<String>)>, diesel::result::Error> {
+    use crate::server::database::schema::contact_note_revisions::dsl as q;
+    use crate::server::database::schema::contact_note_revisions::table;
+    use crate::server::database::schema::users::dsl as u;
+    use crate::server::database::schema::users::table as users_table;
+
+    table
+        .left_join(users_table.on(u::id.nullable().eq(q::user_id)))
+        .select((ContactNoteRevision::as_select(), u::username.nullable()))
+        .filter(q::contact_id.eq(contact_id))
+        .order(q::id.desc())
+        .get_results(conn)
+        .await
+}
+
+pub async fn get_note_revision_by_id(
+    conn: &mut DatabaseConnection,
+    id: i64,
+) -> Result<Option<ContactNoteRevision>, diesel::result::Error> {
+    use crate::server::database::schema::contact_note_revisions::dsl as q;
+    use crate::server::database::schema::contact_note_revisions::table;
+
+    table
+        .select(ContactNoteRevision::as_select())
+        .filter(q::id.eq(id))
+        .get_result(conn)
+        .await
+        .optional()
+}
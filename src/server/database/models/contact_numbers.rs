@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+use diesel::{ExpressionMethods, QueryDsl, Queryable, Selectable};
+use diesel_async::RunQueryDsl;
+
+use chrono::Utc;
+
+use crate::models::contacts as model;
+use crate::server::database::{connection::DatabaseConnection, schema};
+
+#[allow(dead_code)]
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = schema::contact_numbers)]
+pub struct ContactNumber {
+    pub id: i64,
+    pub contact_id: i64,
+    pub phone_number: String,
+    pub label: Option<String>,
+    pub inserted_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<ContactNumber> for model::ContactNumber {
+    fn from(number: ContactNumber) -> Self {
+        Self {
+            id: model::ContactNumberId::new(number.id),
+            contact_id: model::ContactId::new(number.contact_id),
+            phone_number: number.phone_number,
+            label: number.label,
+            inserted_at: number.inserted_at,
+            updated_at: number.updated_at,
+        }
+    }
+}
+
+pub async fn get_numbers_for_contact(
+    conn: &mut DatabaseConnection,
+    contact_id: i64,
+) -> Result<Vec<ContactNumber>, diesel::result::Error> {
+    use crate::server::database::schema::contact_numbers::dsl as q;
+    use crate::server::database::schema::contact_numbers::table;
+
+    table
+        .select(ContactNumber::as_select())
+        .filter(q::contact_id.eq(contact_id))
+        .order(q::id.asc())
+        .get_results(conn)
+        .await
+}
+
+pub async fn get_numbers_for_contacts(
+    conn: &mut DatabaseConnection,
+    contact_ids: &[i64],
+) -> Result<HashMap<i64, Vec<ContactNumber>>, diesel::result::Error> {
+    use crate::server::database::schema::contact_numbers::dsl as q;
+    use crate::server::database::schema::contact_numbers::table;
+
+    let rows: Vec<ContactNumber> = table
+        .select(ContactNumber::as_select())
+        .filter(q::contact_id.eq_any(contact_ids))
+        .order(q::id.asc())
+        .get_results(conn)
+        .await?;
+
+    let mut out: HashMap<i64, Vec<ContactNumber>> = HashMap::new();
+    for number in rows {
+        out.entry(number.contact_id).or_default().push(number);
+    }
+    Ok(out)
+}
+
+pub async fn get_contact_number_by_id(
+    conn: &mut DatabaseConnection,
+    id: i64,
+) -> Result<Option<ContactNumber>, diesel::result::Error> {
+    use crate::server::database::schema::contact_numbers::dsl as q;
+    use crate::server::database::schema::contact_numbers::table;
+
+    table
+        .select(ContactNumber::as_select())
+        .filter(q::id.eq(id))
+        .get_result(conn)
+        .await
+        .optional()
+}
+
+/// Find an additional number of a contact that is not archived.
+pub async fn get_contact_number_by_phone_number(
+    conn: &mut DatabaseConnection,
+    phone_number: &str,
+) -> Result<Option<ContactNumber>, diesel::result::Error> {
+    use crate::server::database::schema::contact_numbers::dsl as q;
+    use crate::server::database::schema::contact_numbers::table;
+    use crate::server::database::schema::contacts::dsl as c;
+    use crate::server::database::schema::contacts::table as c_table;
+
+    let active_contacts = c_table.filter(c::archived_at.is_null()).select(c::id);
+
+    table
+        .select(ContactNumber::as_select())
+        .filter(q::phone_number.eq(phone_number))
+        .filter(q::contact_id.eq_any(active_contacts))
+        .get_result(conn)
+        .await
+        .optional()
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = schema::contact_numbers)]
+pub struct NewContactNumber {
+    pub contact_id: i64,
+    pub phone_number: String,
+    pub label: Option<String>,
+    pub inserted_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl NewContactNumber {
+    pub fn from_front_end(number: &model::NewContactNumber) -> Self {
+        let now = Utc::now();
+        Self {
+            contact_id: number.contact_id.as_inner(),
+            phone_number: number.phone_number.clone(),
+            label: number.label.clone(),
+            inserted_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+pub async fn create_contact_number(
+    conn: &mut DatabaseConnection,
+    update: NewContactNumber,
+) -> Result<ContactNumber, diesel::result::Error> {
+    use crate::server::database::schema::contact_numbers::table;
+
+    diesel::insert_into(table)
+        .values(&update)
+        .returning(ContactNumber::as_returning())
+        .get_result(conn)
+        .await
+}
+
+/// Move every additional number of `from_contact_ids` onto `to_contact_id`.
+pub async fn reassign_contact_numbers(
+    conn: &mut DatabaseConnection,
+    from_contact_ids: &[i64],
+    to_contact_id: i64,
+) -> Result<usize, diesel::result::Error> {
+    use crate::server::database::schema::contact_numbers::dsl as q;
+    use crate::server::database::schema::contact_numbers::table;
+
+    diesel::update(table.filter(q::contact_id.eq_any(from_contact_ids)))
+        .set((
+            q::contact_id.eq(to_contact_id),
+            q::updated_at.eq(Utc::now()),
+        ))
+        .execute(conn)
+        .await
+}
+
+pub async fn delete_contact_number(
+    conn: &mut DatabaseConnection,
+    id: i64,
+) -> Result<(), diesel::result::Error> {
+    use crate::server::database::schema::contact_numbers::dsl as q;
+    use crate::server::database::schema::contact_numbers::table;
+
+    diesel::delete(table.filter(q::id.eq(id)))
+        .execute(conn)
+        .await?;
+    Ok(())
+}
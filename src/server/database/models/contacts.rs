@@ -1,11 +1,22 @@
+use diesel::declare_sql_function;
 use diesel::prelude::*;
+use diesel::sql_types::{Nullable, Timestamptz};
 use diesel::{ExpressionMethods, QueryDsl, Queryable, Selectable};
 use diesel_async::RunQueryDsl;
 
 use chrono::DateTime;
 use chrono::Utc;
+use chrono::{NaiveDate, NaiveTime};
 
+use crate::models::audit_log::AuditEntity;
 use crate::models::contacts as model;
+use crate::models::contacts::{ContactCursor, ContactSort};
+use crate::models::groups::GroupId;
+use crate::models::search::{Comparison, SearchTerm, free_text};
+use crate::models::tags::Tag;
+use crate::server::database::models::groups::GroupScope;
+use crate::server::database::models::tags::get_tags_for_contacts;
+use crate::server::database::search::{contains_pattern, digits, word_similar, word_similarity};
 use crate::server::database::{connection::DatabaseConnection, schema};
 
 #[allow(dead_code)]
@@ -20,140 +31,376 @@ pub struct Contact {
     pub comments: Option<String>,
     pub inserted_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub archived_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub temporary_action: Option<String>,
+    pub temporary_action_expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub call_count: i64,
+    pub first_call_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_call_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub group_id: Option<i64>,
 }
 
 impl Contact {
-    pub fn into_model(self, phone_call_count: i64) -> model::Contact {
+    pub fn into_model(self, phone_call_count: i64, tags: Vec<Tag>) -> model::Contact {
         model::Contact {
             id: model::ContactId::new(self.id),
             phone_number: self.phone_number,
             name: self.name,
             action: self.action,
             comments: self.comments,
+            tags,
             phone_call_count,
+            first_call_at: self.first_call_at,
+            last_call_at: self.last_call_at,
             inserted_at: self.inserted_at,
             updated_at: self.updated_at,
+            archived_at: self.archived_at,
+            temporary_action: match (self.temporary_action, self.temporary_action_expires_at) {
+                (Some(action), Some(expires_at)) => {
+                    Some(model::TemporaryAction { action, expires_at })
+                }
+                _ => None,
+            },
+            group_id: self.group_id.map(GroupId::new),
         }
     }
 }
 
-/// Fetch the number of phone calls associated with a contact.
-pub async fn get_phone_call_count(
+/// Convert contacts with their phone call counts into front end models,
+/// loading the tags of all of them in one query.
+pub async fn into_models(
+    conn: &mut DatabaseConnection,
+    rows: Vec<(Contact, i64)>,
+) -> Result<Vec<model::Contact>, diesel::result::Error> {
+    let ids: Vec<i64> = rows.iter().map(|(c, _)| c.id).collect();
+    let mut tags = get_tags_for_contacts(conn, &ids).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(c, n)| {
+            let contact_tags = tags.remove(&c.id).unwrap_or_default();
+            c.into_model(n, contact_tags)
+        })
+        .collect())
+}
+
+#[declare_sql_function]
+extern "SQL" {
+    /// The earlier of two timestamps, ignoring NULL.
+    fn least(a: Nullable<Timestamptz>, b: Nullable<Timestamptz>) -> Nullable<Timestamptz>;
+
+    /// The later of two timestamps, ignoring NULL.
+    fn greatest(a: Nullable<Timestamptz>, b: Nullable<Timestamptz>) -> Nullable<Timestamptz>;
+}
+
+/// Add a call made at `at` to the contact's call statistics.
+pub async fn record_phone_call(
     conn: &mut DatabaseConnection,
     contact_id: i64,
-) -> Result<i64, diesel::result::Error> {
+    at: DateTime<Utc>,
+) -> Result<usize, diesel::result::Error> {
+    use crate::server::database::schema::contacts::dsl as q;
+    use crate::server::database::schema::contacts::table;
+
+    diesel::update(table.filter(q::id.eq(contact_id)))
+        .set((
+            q::call_count.eq(q::call_count + 1),
+            q::first_call_at.eq(least(q::first_call_at, Some(at))),
+            q::last_call_at.eq(greatest(q::last_call_at, Some(at))),
+        ))
+        .execute(conn)
+        .await
+}
+
+/// Recount the call statistics of the contacts from their phone calls,
+/// after calls have been moved or deleted.
+pub async fn refresh_call_statistics(
+    conn: &mut DatabaseConnection,
+    contact_ids: &[i64],
+) -> Result<usize, diesel::result::Error> {
+    use crate::server::database::schema::contacts::dsl as q;
+    use crate::server::database::schema::contacts::table;
     use crate::server::database::schema::phone_calls::dsl as pc;
     use crate::server::database::schema::phone_calls::table as pc_table;
-    use diesel::dsl::count_star;
-
-    pc_table
-        .filter(pc::contact_id.eq(contact_id))
-        .select(count_star())
-        .first(conn)
+    use diesel::dsl::{count_star, max, min};
+
+    let calls = || pc_table.filter(pc::contact_id.eq(q::id));
+
+    diesel::update(table.filter(q::id.eq_any(contact_ids)))
+        .set((
+            q::call_count.eq(calls()
+                .select(count_star())
+                .single_value()
+                .assume_not_null()),
+            q::first_call_at.eq(calls().select(min(pc::inserted_at)).single_value()),
+            q::last_call_at.eq(calls().select(max(pc::inserted_at)).single_value()),
+        ))
+        .execute(conn)
         .await
 }
 
+type ContactSearchFilter = Box<
+    dyn BoxableExpression<
+            schema::contacts::table,
+            diesel::pg::Pg,
+            SqlType = diesel::sql_types::Nullable<diesel::sql_types::Bool>,
+        >,
+>;
+
+/// Contacts with a name containing or similar to `search`, a primary or
+/// additional number containing its digits, or a tag containing it.  An
+/// empty search matches everything.
+fn contact_search_filter(search: &str) -> ContactSearchFilter {
+    use crate::server::database::schema::contact_numbers::dsl as cn;
+    use crate::server::database::schema::contact_numbers::table as cn_table;
+    use crate::server::database::schema::contact_tags::dsl as ct;
+    use crate::server::database::schema::contact_tags::table as ct_table;
+    use crate::server::database::schema::contacts::dsl as q;
+    use crate::server::database::schema::tags::dsl as t;
+    use crate::server::database::schema::tags::table as t_table;
+    use diesel::sql_types::{Bool, Nullable};
+
+    let search = search.trim();
+    if search.is_empty() {
+        return Box::new(true.into_sql::<Nullable<Bool>>());
+    }
+
+    let pattern = contains_pattern(search);
+    let digits = digits(search);
+    let has_digits = (!digits.is_empty()).into_sql::<Bool>();
+    let digits_pattern = contains_pattern(&digits);
+
+    let number_subquery = cn_table
+        .filter(cn::phone_number_digits.like(digits_pattern.clone()))
+        .select(cn::contact_id);
+
+    let tag_subquery = ct_table
+        .inner_join(t_table.on(t::id.eq(ct::tag_id)))
+        .filter(t::name.ilike(pattern.clone()))
+        .select(ct::contact_id);
+
+    Box::new(
+        q::name
+            .ilike(pattern)
+            .or(word_similar(search, q::name))
+            .or(has_digits.and(
+                q::phone_number_digits
+                    .like(digits_pattern)
+                    .or(q::id.eq_any(number_subquery)),
+            ))
+            .or(q::id.eq_any(tag_subquery)),
+    )
+}
+
+/// Contacts carrying exactly the tag `name`.
+fn contact_tag_filter(name: &str) -> ContactSearchFilter {
+    use crate::server::database::schema::contact_tags::dsl as ct;
+    use crate::server::database::schema::contact_tags::table as ct_table;
+    use crate::server::database::schema::contacts::dsl as q;
+    use crate::server::database::schema::tags::dsl as t;
+    use crate::server::database::schema::tags::table as t_table;
+
+    let tag_subquery = ct_table
+        .inner_join(t_table.on(t::id.eq(ct::tag_id)))
+        .filter(t::name.eq(name.to_string()))
+        .select(ct::contact_id);
+
+    Box::new(q::id.eq_any(tag_subquery).nullable())
+}
+
+/// Contacts visible within `scope`.
+pub(crate) fn contact_group_filter(scope: &GroupScope) -> ContactSearchFilter {
+    use crate::server::database::schema::contacts::dsl as q;
+    use diesel::sql_types::{Bool, Nullable};
+
+    match scope {
+        GroupScope::All => Box::new(true.into_sql::<Nullable<Bool>>()),
+        GroupScope::Member(group_ids) => Box::new(
+            q::group_id
+                .is_null()
+                .or(q::group_id.eq_any(group_ids.clone())),
+        ),
+    }
+}
+
+/// All terms of a search query as one filter.  Free text is matched as by
+/// [`contact_search_filter`]; `action:` compares the contact's own action,
+/// the temporary one while it is active, and ignores tag actions.
+pub(crate) fn contact_query_filter(
+    terms: &[SearchTerm],
+    now: DateTime<Utc>,
+) -> ContactSearchFilter {
+    use crate::server::database::schema::contacts::dsl as q;
+    use crate::server::database::schema::phone_calls::dsl as pc;
+    use crate::server::database::schema::phone_calls::table as pc_table;
+
+    let start_of = |date: &NaiveDate| date.and_time(NaiveTime::MIN).and_utc();
+
+    let count = || q::call_count.nullable();
+
+    let mut filter = contact_search_filter(&free_text(terms));
+
+    for term in terms {
+        let term_filter: ContactSearchFilter = match term {
+            SearchTerm::Text(_) => continue,
+            SearchTerm::Action(action) => {
+                let active = q::temporary_action_expires_at.gt(now);
+                let inactive = q::temporary_action_expires_at
+                    .is_null()
+                    .or(q::temporary_action_expires_at.le(now));
+                Box::new(
+                    active
+                        .and(q::temporary_action.eq(action.clone()))
+                        .or(inactive.and(q::action.eq(action.clone()))),
+                )
+            }
+            SearchTerm::Name(None) => Box::new(q::name.is_null().nullable()),
+            SearchTerm::Name(Some(name)) => Box::new(q::name.ilike(contains_pattern(name))),
+            SearchTerm::Calls(Comparison::Lt, n) => Box::new(count().lt(*n)),
+            SearchTerm::Calls(Comparison::Le, n) => Box::new(count().le(*n)),
+            SearchTerm::Calls(Comparison::Eq, n) => Box::new(count().eq(*n)),
+            SearchTerm::Calls(Comparison::Ge, n) => Box::new(count().ge(*n)),
+            SearchTerm::Calls(Comparison::Gt, n) => Box::new(count().gt(*n)),
+            SearchTerm::Tag(name) => contact_tag_filter(name),
+            // A call on or after the date exactly when the last one was.
+            SearchTerm::After(date) => Box::new(q::last_call_at.ge(start_of(date))),
+            // A call before the date exactly when the first one was.
+            SearchTerm::Before(date) => Box::new(q::first_call_at.lt(start_of(date))),
+            SearchTerm::Dest(number) => {
+                let calls = pc_table
+                    .filter(pc::destination_number.eq(number.clone()))
+                    .select(pc::contact_id);
+                Box::new(q::id.eq_any(calls).nullable())
+            }
+        };
+
+        filter = Box::new(filter.and(term_filter));
+    }
+
+    filter
+}
+
+/// The ten best matches for `search` within `scope`: exact phone numbers
+/// first, then by name similarity.
 pub async fn search_contacts(
     conn: &mut DatabaseConnection,
+    scope: &GroupScope,
     search: &str,
 ) -> Result<Vec<(Contact, i64)>, diesel::result::Error> {
     use crate::server::database::schema::contacts::dsl as q;
     use crate::server::database::schema::contacts::table;
-    use crate::server::database::schema::phone_calls::dsl as pc;
-    use crate::server::database::schema::phone_calls::table as pc_table;
-    use diesel::dsl::count_star;
-
-    let search = search.replace("%", "\\%");
+    use diesel::sql_types::Bool;
 
-    // Use a correlated subquery for the count so GROUP BY is not needed.
-    let count_subquery = pc_table
-        .filter(pc::contact_id.eq(q::id))
-        .select(count_star())
-        .single_value();
+    let digits = digits(search);
+    let has_digits = (!digits.is_empty()).into_sql::<Bool>();
 
     table
-        .select((Contact::as_select(), count_subquery))
-        .filter(
-            q::name
-                .ilike(format!("%{}%", search))
-                .or(q::phone_number.ilike(format!("%{}%", search))),
-        )
-        .order((q::name.asc(),))
+        .select((Contact::as_select(), q::call_count))
+        .filter(q::archived_at.is_null())
+        .filter(contact_search_filter(search))
+        .filter(contact_group_filter(scope))
+        .order((
+            has_digits.and(q::phone_number_digits.eq(digits)).desc(),
+            word_similarity(search.trim().to_string(), q::name)
+                .desc()
+                .nulls_last(),
+            q::name.asc(),
+        ))
         .limit(10)
         .into_boxed()
-        .get_results::<(Contact, Option<i64>)>(conn)
+        .get_results(conn)
         .await
-        .map(|rows| rows.into_iter().map(|(c, n)| (c, n.unwrap_or(0))).collect())
 }
 
 /// Paginated contact search.
 ///
-/// Results are sorted `(name ASC NULLS LAST, id ASC)`.  Pass a cursor to
-/// start after the last row seen on the previous page:
-///   - `before_name`      — `Some(Some(name))` if the row had a name,
-///     `Some(None)` if name was NULL,
-///     `None` for the first page
-///   - `before_id`   — `Some(id)` of the last visible row, `None` for first page
+/// Results are sorted by `sort`, ties broken by `id ASC`.  Pass the
+/// `cursor` of the last row seen on the previous page to start after it, or
+/// `None` for the first page.  The cursor carries that row's sort key, so the
+/// row itself is not read back.  The cursor must be for `sort`.
+///
+/// `terms` are applied as by [`contact_query_filter`]; `tag`, if given,
+/// restricts results to contacts carrying exactly that tag.  Only contacts
+/// visible within `scope` are returned.
 ///
 /// Caller should request `page_size + 1` rows to detect whether a next page exists.
 pub async fn search_contacts_paginated(
     conn: &mut DatabaseConnection,
-    search: &str,
-    before_name: Option<Option<String>>,
-    before_id: Option<i64>,
+    scope: &GroupScope,
+    terms: &[SearchTerm],
+    tag: Option<&str>,
+    sort: ContactSort,
+    cursor: Option<ContactCursor>,
     page_size: i64,
 ) -> Result<Vec<(Contact, i64)>, diesel::result::Error> {
     use crate::server::database::schema::contacts::dsl as q;
     use crate::server::database::schema::contacts::table;
-    use crate::server::database::schema::phone_calls::dsl as pc;
-    use crate::server::database::schema::phone_calls::table as pc_table;
-    use diesel::dsl::count_star;
-
-    let search = search.replace("%", "\\%");
-    let pattern = format!("%{}%", search);
-
-    let count_subquery = pc_table
-        .filter(pc::contact_id.eq(q::id))
-        .select(count_star())
-        .single_value();
 
-    let base = table
-        .select((Contact::as_select(), count_subquery))
-        .filter(
-            q::name
-                .ilike(pattern.clone())
-                .or(q::phone_number.ilike(pattern)),
-        )
-        .order((q::name.asc().nulls_last(), q::id.asc()))
+    let mut base = table
+        .select((Contact::as_select(), q::call_count))
+        .filter(q::archived_at.is_null())
+        .filter(contact_query_filter(terms, Utc::now()))
+        .filter(contact_group_filter(scope))
         .limit(page_size)
         .into_boxed();
 
-    let rows = match (before_name, before_id) {
-        (Some(cursor_name), Some(cursor_id)) => match cursor_name {
-            // Cursor row had a non-NULL name: next rows satisfy
-            //   name > cursor_name  OR  (name = cursor_name AND id > cursor_id)
-            // Rows with NULL name sort after all named rows, so include them too.
-            Some(name) => {
-                base.filter(
-                    q::name
-                        .gt(name.clone())
-                        .or(q::name.eq(name).and(q::id.gt(cursor_id)))
-                        .or(q::name.is_null()),
-                )
-                .get_results::<(Contact, Option<i64>)>(conn)
-                .await?
-            }
-            // Cursor row had NULL name: only null-named rows with id > cursor_id remain.
-            None => {
-                base.filter(q::name.is_null().and(q::id.gt(cursor_id)))
-                    .get_results::<(Contact, Option<i64>)>(conn)
-                    .await?
-            }
-        },
-        _ => base.get_results::<(Contact, Option<i64>)>(conn).await?,
+    if let Some(tag) = tag {
+        base = base.filter(contact_tag_filter(tag));
+    }
+
+    base = match sort {
+        ContactSort::Name => base.order((q::name.asc().nulls_last(), q::id.asc())),
+        ContactSort::CallCount => base.order((q::call_count.desc(), q::id.asc())),
+        ContactSort::LastCall => base.order((q::last_call_at.desc().nulls_last(), q::id.asc())),
+        ContactSort::Updated => base.order((q::updated_at.desc(), q::id.asc())),
+        ContactSort::PhoneNumber => base.order((q::phone_number.asc(), q::id.asc())),
     };
 
-    Ok(rows.into_iter().map(|(c, n)| (c, n.unwrap_or(0))).collect())
+    let base = match cursor {
+        None => base,
+        // Cursor row had a non-NULL name: next rows satisfy
+        //   name > cursor_name  OR  (name = cursor_name AND id > cursor_id)
+        // Rows with NULL name sort after all named rows, so include them too.
+        Some(ContactCursor::Name(Some(name), id)) => base.filter(
+            q::name
+                .gt(name.clone())
+                .or(q::name.eq(name).and(q::id.gt(id.as_inner())))
+                .or(q::name.is_null()),
+        ),
+        // Cursor row had NULL name: only null-named rows with id > cursor_id remain.
+        Some(ContactCursor::Name(None, id)) => {
+            base.filter(q::name.is_null().and(q::id.gt(id.as_inner())))
+        }
+        Some(ContactCursor::CallCount(call_count, id)) => base.filter(
+            q::call_count
+                .lt(call_count)
+                .or(q::call_count.eq(call_count).and(q::id.gt(id.as_inner()))),
+        ),
+        // Never called contacts sort after all called ones.
+        Some(ContactCursor::LastCall(Some(last_call_at), id)) => base.filter(
+            q::last_call_at
+                .lt(last_call_at)
+                .or(q::last_call_at
+                    .eq(last_call_at)
+                    .and(q::id.gt(id.as_inner())))
+                .or(q::last_call_at.is_null()),
+        ),
+        Some(ContactCursor::LastCall(None, id)) => {
+            base.filter(q::last_call_at.is_null().and(q::id.gt(id.as_inner())))
+        }
+        Some(ContactCursor::Updated(updated_at, id)) => base.filter(
+            q::updated_at
+                .lt(updated_at)
+                .or(q::updated_at.eq(updated_at).and(q::id.gt(id.as_inner()))),
+        ),
+        Some(ContactCursor::PhoneNumber(phone_number, id)) => base.filter(
+            q::phone_number.gt(phone_number.clone()).or(q::phone_number
+                .eq(phone_number)
+                .and(q::id.gt(id.as_inner()))),
+        ),
+    };
+
+    base.get_results(conn).await
 }
 
 pub async fn get_contact_by_id(
@@ -163,39 +410,198 @@ pub async fn get_contact_by_id(
     use crate::server::database::schema::contacts as q;
     use crate::server::database::schema::contacts::table;
 
-    let Some(contact) = table
-        .select(Contact::as_select())
+    table
+        .select((Contact::as_select(), q::call_count))
         .filter(q::id.eq(id))
         .get_result(conn)
         .await
-        .optional()?
-    else {
-        return Ok(None);
-    };
+        .optional()
+}
+
+pub async fn get_contacts_by_ids(
+    conn: &mut DatabaseConnection,
+    ids: &[i64],
+) -> Result<Vec<(Contact, i64)>, diesel::result::Error> {
+    use crate::server::database::schema::contacts::dsl as q;
+    use crate::server::database::schema::contacts::table;
 
-    let count = get_phone_call_count(conn, contact.id).await?;
-    Ok(Some((contact, count)))
+    table
+        .select((Contact::as_select(), q::call_count))
+        .filter(q::id.eq_any(ids))
+        .order(q::id.asc())
+        .get_results(conn)
+        .await
 }
 
+/// Find the contact owning `phone_number`, either as its primary number or
+/// as one of its additional numbers.  Archived contacts are ignored.
 pub async fn get_contact_by_phone_number(
     conn: &mut DatabaseConnection,
     phone_number: &str,
 ) -> Result<Option<(Contact, i64)>, diesel::result::Error> {
+    use crate::server::database::schema::contact_numbers::dsl as cn;
+    use crate::server::database::schema::contact_numbers::table as cn_table;
     use crate::server::database::schema::contacts as q;
     use crate::server::database::schema::contacts::table;
 
-    let Some(contact) = table
-        .select(Contact::as_select())
-        .filter(q::phone_number.eq(phone_number))
+    let number_subquery = cn_table
+        .filter(cn::phone_number.eq(phone_number))
+        .select(cn::contact_id);
+
+    table
+        .select((Contact::as_select(), q::call_count))
+        .filter(q::archived_at.is_null())
+        .filter(
+            q::phone_number
+                .eq(phone_number)
+                .or(q::id.eq_any(number_subquery)),
+        )
         .get_result(conn)
         .await
-        .optional()?
-    else {
-        return Ok(None);
-    };
+        .optional()
+}
+
+/// Every contact, archived or not, in id order.
+pub async fn get_all_contacts(
+    conn: &mut DatabaseConnection,
+) -> Result<Vec<(Contact, i64)>, diesel::result::Error> {
+    use crate::server::database::schema::contacts::dsl as q;
+    use crate::server::database::schema::contacts::table;
+
+    table
+        .select((Contact::as_select(), q::call_count))
+        .order(q::id.asc())
+        .get_results(conn)
+        .await
+}
+
+/// Archived contacts visible within `scope`, most recently archived first.
+pub async fn get_archived_contacts(
+    conn: &mut DatabaseConnection,
+    scope: &GroupScope,
+) -> Result<Vec<(Contact, i64)>, diesel::result::Error> {
+    use crate::server::database::schema::contacts::dsl as q;
+    use crate::server::database::schema::contacts::table;
+
+    table
+        .select((Contact::as_select(), q::call_count))
+        .filter(q::archived_at.is_not_null())
+        .filter(contact_group_filter(scope))
+        .order((q::archived_at.desc(), q::id.desc()))
+        .get_results(conn)
+        .await
+}
+
+/// The group of each of the contacts, for access checks.
+pub async fn get_contact_groups(
+    conn: &mut DatabaseConnection,
+    ids: &[i64],
+) -> Result<Vec<(i64, Option<i64>)>, diesel::result::Error> {
+    use crate::server::database::schema::contacts::dsl as q;
+    use crate::server::database::schema::contacts::table;
+
+    table
+        .select((q::id, q::group_id))
+        .filter(q::id.eq_any(ids))
+        .get_results(conn)
+        .await
+}
+
+/// Contacts visible within `scope` that were created since `since` and
+/// have called, newest first.
+pub async fn get_new_contacts(
+    conn: &mut DatabaseConnection,
+    scope: &GroupScope,
+    since: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<(Contact, i64)>, diesel::result::Error> {
+    use crate::server::database::schema::contacts::dsl as q;
+    use crate::server::database::schema::contacts::table;
 
-    let count = get_phone_call_count(conn, contact.id).await?;
-    Ok(Some((contact, count)))
+    table
+        .select((Contact::as_select(), q::call_count))
+        .filter(q::archived_at.is_null())
+        .filter(q::inserted_at.ge(since))
+        .filter(q::first_call_at.is_not_null())
+        .filter(contact_group_filter(scope))
+        .order((q::inserted_at.desc(), q::id.desc()))
+        .limit(limit)
+        .get_results(conn)
+        .await
+}
+
+/// Ids of contacts archived before `cutoff`, for the purge job.
+pub async fn get_contact_ids_archived_before(
+    conn: &mut DatabaseConnection,
+    cutoff: DateTime<Utc>,
+) -> Result<Vec<i64>, diesel::result::Error> {
+    use crate::server::database::schema::contacts::dsl as q;
+    use crate::server::database::schema::contacts::table;
+
+    table
+        .select(q::id)
+        .filter(q::archived_at.lt(cutoff))
+        .get_results(conn)
+        .await
+}
+
+/// Ids of contacts that look created by incoming calls and never used since:
+/// action still `allow`, no name, comments, tags, other numbers or temporary
+/// action, not archived, never changed by a user, and no calls at or after
+/// `calls_since`.  The action matters because changes made before the audit
+/// log existed left no audit rows, and a blocked number must not be lost.
+pub async fn get_unused_contact_ids(
+    conn: &mut DatabaseConnection,
+    calls_since: DateTime<Utc>,
+) -> Result<Vec<i64>, diesel::result::Error> {
+    use crate::server::database::schema::audit_log::dsl as al;
+    use crate::server::database::schema::audit_log::table as al_table;
+    use crate::server::database::schema::contact_numbers::dsl as cn;
+    use crate::server::database::schema::contact_numbers::table as cn_table;
+    use crate::server::database::schema::contact_tags::dsl as ct;
+    use crate::server::database::schema::contact_tags::table as ct_table;
+    use crate::server::database::schema::contacts::dsl as q;
+    use crate::server::database::schema::contacts::table;
+    use crate::server::database::schema::phone_calls::dsl as pc;
+    use crate::server::database::schema::phone_calls::table as pc_table;
+    use diesel::dsl::not;
+
+    let recent_calls = pc_table
+        .filter(pc::inserted_at.ge(calls_since))
+        .select(pc::contact_id);
+    let user_changes = al_table
+        .filter(al::entity_type.eq(AuditEntity::Contact.as_str()))
+        .filter(al::user_id.is_not_null())
+        .select(al::entity_id);
+
+    table
+        .select(q::id)
+        .filter(q::action.eq("allow"))
+        .filter(q::name.is_null())
+        .filter(q::comments.is_null())
+        .filter(q::temporary_action.is_null())
+        .filter(q::archived_at.is_null())
+        .filter(not(q::id.eq_any(ct_table.select(ct::contact_id))))
+        .filter(not(q::id.eq_any(cn_table.select(cn::contact_id))))
+        .filter(not(q::id.eq_any(recent_calls)))
+        .filter(not(q::id.eq_any(user_changes)))
+        .get_results(conn)
+        .await
+}
+
+/// Ids of contacts whose temporary action expired at or before `now`.
+pub async fn get_contact_ids_with_expired_temporary_action(
+    conn: &mut DatabaseConnection,
+    now: DateTime<Utc>,
+) -> Result<Vec<i64>, diesel::result::Error> {
+    use crate::server::database::schema::contacts::dsl as q;
+    use crate::server::database::schema::contacts::table;
+
+    table
+        .select(q::id)
+        .filter(q::temporary_action_expires_at.le(now))
+        .get_results(conn)
+        .await
 }
 
 #[derive(Insertable, Debug, Clone)]
@@ -208,6 +614,9 @@ pub struct NewContact {
     pub comments: Option<String>,
     pub inserted_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub temporary_action: Option<String>,
+    pub temporary_action_expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub group_id: Option<i64>,
 }
 
 impl NewContact {
@@ -220,6 +629,9 @@ impl NewContact {
             comments: contact.comments.clone(),
             inserted_at: now,
             updated_at: now,
+            temporary_action: contact.temporary_action.as_ref().map(|t| t.action.clone()),
+            temporary_action_expires_at: contact.temporary_action.as_ref().map(|t| t.expires_at),
+            group_id: contact.group_id.map(|id| id.as_inner()),
         }
     }
 }
@@ -247,6 +659,10 @@ pub struct ChangeContact {
     pub comments: Option<Option<String>>,
     pub inserted_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+    pub archived_at: Option<Option<DateTime<Utc>>>,
+    pub temporary_action: Option<Option<String>>,
+    pub temporary_action_expires_at: Option<Option<DateTime<Utc>>>,
+    pub group_id: Option<Option<i64>>,
 }
 
 impl ChangeContact {
@@ -258,6 +674,22 @@ impl ChangeContact {
             comments: contact.comments.clone().into_option(),
             inserted_at: None,
             updated_at: Some(Utc::now()),
+            archived_at: None,
+            temporary_action: contact
+                .temporary_action
+                .as_ref()
+                .map(|t| t.as_ref().map(|t| t.action.clone()))
+                .into_option(),
+            temporary_action_expires_at: contact
+                .temporary_action
+                .as_ref()
+                .map(|t| t.as_ref().map(|t| t.expires_at))
+                .into_option(),
+            group_id: contact
+                .group_id
+                .as_ref()
+                .map(|id| id.map(|id| id.as_inner()))
+                .into_option(),
         }
     }
 }
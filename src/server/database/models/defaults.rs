@@ -6,6 +6,9 @@ use chrono::DateTime;
 use chrono::Utc;
 
 use crate::models::defaults as model;
+use crate::models::groups::GroupId;
+use crate::server::database::models::groups::GroupScope;
+use crate::server::database::search::contains_pattern;
 use crate::server::database::{connection::DatabaseConnection, schema};
 
 #[allow(dead_code)]
@@ -20,6 +23,8 @@ pub struct Default {
     pub action: String,
     pub inserted_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub archived_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub group_id: Option<i64>,
 }
 
 impl From<Default> for model::Default {
@@ -32,22 +37,48 @@ impl From<Default> for model::Default {
             action: default.action,
             inserted_at: default.inserted_at,
             updated_at: default.updated_at,
+            archived_at: default.archived_at,
+            group_id: default.group_id.map(GroupId::new),
         }
     }
 }
 
+type DefaultFilter = Box<
+    dyn BoxableExpression<
+            schema::defaults::table,
+            diesel::pg::Pg,
+            SqlType = diesel::sql_types::Nullable<diesel::sql_types::Bool>,
+        >,
+>;
+
+/// Defaults visible within `scope`.
+fn default_group_filter(scope: &GroupScope) -> DefaultFilter {
+    use crate::server::database::schema::defaults::dsl as q;
+    use diesel::sql_types::{Bool, Nullable};
+
+    match scope {
+        GroupScope::All => Box::new(true.into_sql::<Nullable<Bool>>()),
+        GroupScope::Member(group_ids) => Box::new(
+            q::group_id
+                .is_null()
+                .or(q::group_id.eq_any(group_ids.clone())),
+        ),
+    }
+}
+
 pub async fn search_defaults(
     conn: &mut DatabaseConnection,
+    scope: &GroupScope,
     search: &str,
 ) -> Result<Vec<Default>, diesel::result::Error> {
     use crate::server::database::schema::defaults::dsl as q;
     use crate::server::database::schema::defaults::table;
 
-    let search = search.replace("%", "\\%");
-
     table
         .select(Default::as_select())
-        .filter(q::name.ilike(format!("%{}%", search)))
+        .filter(q::archived_at.is_null())
+        .filter(q::name.ilike(contains_pattern(search)))
+        .filter(default_group_filter(scope))
         .order((q::order.asc(), q::id.asc()))
         .limit(10)
         .into_boxed()
@@ -55,6 +86,7 @@ pub async fn search_defaults(
         .await
 }
 
+/// All defaults that are not archived, in the order they are matched.
 pub async fn get_all_defaults(
     conn: &mut DatabaseConnection,
 ) -> Result<Vec<Default>, diesel::result::Error> {
@@ -63,11 +95,44 @@ pub async fn get_all_defaults(
 
     table
         .select(Default::as_select())
+        .filter(q::archived_at.is_null())
         .order((q::order.asc(), q::id.asc()))
         .get_results(conn)
         .await
 }
 
+/// Archived defaults visible within `scope`, most recently archived first.
+pub async fn get_archived_defaults(
+    conn: &mut DatabaseConnection,
+    scope: &GroupScope,
+) -> Result<Vec<Default>, diesel::result::Error> {
+    use crate::server::database::schema::defaults::dsl as q;
+    use crate::server::database::schema::defaults::table;
+
+    table
+        .select(Default::as_select())
+        .filter(q::archived_at.is_not_null())
+        .filter(default_group_filter(scope))
+        .order((q::archived_at.desc(), q::id.desc()))
+        .get_results(conn)
+        .await
+}
+
+/// Ids of defaults archived before `cutoff`, for the purge job.
+pub async fn get_default_ids_archived_before(
+    conn: &mut DatabaseConnection,
+    cutoff: DateTime<Utc>,
+) -> Result<Vec<i64>, diesel::result::Error> {
+    use crate::server::database::schema::defaults::dsl as q;
+    use crate::server::database::schema::defaults::table;
+
+    table
+        .select(q::id)
+        .filter(q::archived_at.lt(cutoff))
+        .get_results(conn)
+        .await
+}
+
 pub async fn get_default_by_id(
     conn: &mut DatabaseConnection,
     id: i64,
@@ -93,6 +158,7 @@ pub struct NewDefault {
     pub action: String,
     pub inserted_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub group_id: Option<i64>,
 }
 
 impl NewDefault {
@@ -105,6 +171,7 @@ impl NewDefault {
             action: default.action.clone(),
             inserted_at: now,
             updated_at: now,
+            group_id: default.group_id.map(|id| id.as_inner()),
         }
     }
 }
@@ -132,6 +199,8 @@ pub struct ChangeDefault {
     pub action: Option<String>,
     pub inserted_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
+    pub archived_at: Option<Option<DateTime<Utc>>>,
+    pub group_id: Option<Option<i64>>,
 }
 
 impl ChangeDefault {
@@ -143,6 +212,12 @@ impl ChangeDefault {
             action: default.action.clone().into_option(),
             inserted_at: None,
             updated_at: Some(Utc::now()),
+            archived_at: None,
+            group_id: default
+                .group_id
+                .as_ref()
+                .map(|id| id.map(|id| id.as_inner()))
+                .into_option(),
         }
     }
 }
@@ -0,0 +1,201 @@
+use diesel::prelude::*;
+use diesel::{ExpressionMethods, QueryDsl, Queryable, Selectable};
+use diesel_async::RunQueryDsl;
+
+use chrono::Utc;
+
+use crate::models::groups as model;
+use crate::server::database::{connection::DatabaseConnection, schema};
+
+#[allow(dead_code)]
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = schema::groups)]
+pub struct Group {
+    pub id: i64,
+    pub name: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<Group> for model::Group {
+    fn from(group: Group) -> Self {
+        Self {
+            id: model::GroupId::new(group.id),
+            name: group.name,
+        }
+    }
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = schema::groups)]
+pub struct NewGroup {
+    pub name: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// The contacts and defaults a user may see.  Records in no group are
+/// visible to everyone.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum GroupScope {
+    /// Every record, for admins and system jobs.
+    All,
+    /// Records in one of these groups, or in none.
+    Member(Vec<i64>),
+}
+
+impl GroupScope {
+    pub fn allows(&self, group_id: Option<i64>) -> bool {
+        match (self, group_id) {
+            (GroupScope::All, _) | (_, None) => true,
+            (GroupScope::Member(group_ids), Some(group_id)) => group_ids.contains(&group_id),
+        }
+    }
+
+    /// The group records created without a choice of group go in: the
+    /// user's first group by name, or none for admins and users without a
+    /// group.
+    pub fn default_group(&self) -> Option<i64> {
+        match self {
+            GroupScope::All => None,
+            GroupScope::Member(group_ids) => group_ids.first().copied(),
+        }
+    }
+}
+
+pub async fn get_all_groups(
+    conn: &mut DatabaseConnection,
+) -> Result<Vec<Group>, diesel::result::Error> {
+    use crate::server::database::schema::groups::dsl as q;
+    use crate::server::database::schema::groups::table;
+
+    table
+        .select(Group::as_select())
+        .order(q::name.asc())
+        .get_results(conn)
+        .await
+}
+
+/// Groups the user is a member of, by name.
+pub async fn get_groups_for_user(
+    conn: &mut DatabaseConnection,
+    user_id: i64,
+) -> Result<Vec<Group>, diesel::result::Error> {
+    use crate::server::database::schema::groups::dsl as q;
+    use crate::server::database::schema::groups::table;
+    use crate::server::database::schema::user_groups::dsl as ug;
+    use crate::server::database::schema::user_groups::table as ug_table;
+
+    table
+        .inner_join(ug_table.on(ug::group_id.eq(q::id)))
+        .filter(ug::user_id.eq(user_id))
+        .select(Group::as_select())
+        .order(q::name.asc())
+        .get_results(conn)
+        .await
+}
+
+/// The scope of a user: everything for admins, otherwise the user's groups.
+pub async fn get_scope_for_user(
+    conn: &mut DatabaseConnection,
+    user_id: i64,
+    is_admin: bool,
+) -> Result<GroupScope, diesel::result::Error> {
+    if is_admin {
+        return Ok(GroupScope::All);
+    }
+
+    let group_ids = get_groups_for_user(conn, user_id)
+        .await?
+        .into_iter()
+        .map(|group| group.id)
+        .collect();
+
+    Ok(GroupScope::Member(group_ids))
+}
+
+/// Look up groups by name, creating any that do not exist yet.
+pub async fn get_or_create_groups(
+    conn: &mut DatabaseConnection,
+    names: &[String],
+) -> Result<Vec<Group>, diesel::result::Error> {
+    use crate::server::database::schema::groups::dsl as q;
+    use crate::server::database::schema::groups::table;
+
+    let now = Utc::now();
+    let new_groups: Vec<NewGroup> = names
+        .iter()
+        .map(|name| NewGroup {
+            name: name.clone(),
+            created_at: now,
+            updated_at: now,
+        })
+        .collect();
+
+    diesel::insert_into(table)
+        .values(&new_groups)
+        .on_conflict(q::name)
+        .do_nothing()
+        .execute(conn)
+        .await?;
+
+    table
+        .select(Group::as_select())
+        .filter(q::name.eq_any(names))
+        .order(q::name.asc())
+        .get_results(conn)
+        .await
+}
+
+/// Replace the group memberships of a user with `group_ids`.
+pub async fn set_user_groups(
+    conn: &mut DatabaseConnection,
+    user_id: i64,
+    group_ids: &[i64],
+) -> Result<(), diesel::result::Error> {
+    use crate::server::database::schema::user_groups::dsl as ug;
+    use crate::server::database::schema::user_groups::table;
+
+    diesel::delete(table.filter(ug::user_id.eq(user_id)))
+        .execute(conn)
+        .await?;
+
+    let rows: Vec<_> = group_ids
+        .iter()
+        .map(|group_id| (ug::user_id.eq(user_id), ug::group_id.eq(*group_id)))
+        .collect();
+
+    diesel::insert_into(table)
+        .values(&rows)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_scope_allows() {
+        assert!(GroupScope::All.allows(None));
+        assert!(GroupScope::All.allows(Some(1)));
+
+        let scope = GroupScope::Member(vec![1, 2]);
+        assert!(scope.allows(None));
+        assert!(scope.allows(Some(2)));
+        assert!(!scope.allows(Some(3)));
+
+        assert!(!GroupScope::Member(vec![]).allows(Some(1)));
+    }
+
+    #[test]
+    fn test_group_scope_default_group() {
+        assert_eq!(GroupScope::All.default_group(), None);
+        assert_eq!(GroupScope::Member(vec![2, 1]).default_group(), Some(2));
+        assert_eq!(GroupScope::Member(vec![]).default_group(), None);
+    }
+}
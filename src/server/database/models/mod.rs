@@ -1,5 +1,10 @@
+pub mod audit_log;
+pub mod contact_note_revisions;
+pub mod contact_numbers;
 pub mod contacts;
 pub mod defaults;
+pub mod groups;
 pub mod phone_calls;
 pub mod session;
+pub mod tags;
 pub mod users;
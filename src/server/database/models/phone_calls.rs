@@ -1,13 +1,20 @@
 use diesel::prelude::*;
+use diesel::sql_types::{Array, BigInt, Bool, Date, Integer, Nullable, Text, Timestamptz};
 use diesel::{ExpressionMethods, QueryDsl, Queryable, Selectable};
 use diesel_async::RunQueryDsl;
 
 use chrono::DateTime;
+use chrono::NaiveDate;
 use chrono::Utc;
 
 use crate::models::contacts::ContactId;
 use crate::models::phone_calls as model;
-use crate::server::database::models::contacts::Contact;
+use crate::models::search::SearchTerm;
+use crate::server::database::models::contacts::{
+    Contact, contact_group_filter, contact_query_filter,
+};
+use crate::server::database::models::groups::GroupScope;
+use crate::server::database::search::{contains_pattern, digits};
 use crate::server::database::{connection::DatabaseConnection, schema};
 
 #[allow(dead_code)]
@@ -19,9 +26,11 @@ pub struct PhoneCall {
     pub action: String,
     pub contact_id: i64,
     pub destination_number: Option<String>,
-    pub source_number: String,
+    pub source_number: Option<String>,
     pub inserted_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub contact_name: Option<String>,
+    pub decision_source: Option<String>,
 }
 
 impl From<PhoneCall> for model::PhoneCall {
@@ -32,83 +41,130 @@ impl From<PhoneCall> for model::PhoneCall {
             contact_id: ContactId::new(phone_call.contact_id),
             destination_number: phone_call.destination_number,
             source_number: phone_call.source_number,
+            contact_name: phone_call.contact_name,
+            decision_source: phone_call
+                .decision_source
+                .and_then(|source| source.parse().ok()),
             inserted_at: phone_call.inserted_at,
             updated_at: phone_call.updated_at,
         }
     }
 }
 
+/// Latest calls of contacts visible within `scope` matching `search`.
 pub async fn search_phone_calls(
     conn: &mut DatabaseConnection,
+    scope: &GroupScope,
     search: &str,
 ) -> Result<Vec<(PhoneCall, Contact, i64)>, diesel::result::Error> {
     use crate::server::database::schema::contacts::dsl as c_q;
     use crate::server::database::schema::contacts::table as c_table;
     use crate::server::database::schema::phone_calls::dsl as q;
-    use crate::server::database::schema::phone_calls::dsl as pc;
     use crate::server::database::schema::phone_calls::table;
-    use crate::server::database::schema::phone_calls::table as pc_table;
-    use diesel::dsl::count_star;
 
-    let search = search.replace("%", "\\%");
+    let pattern = contains_pattern(search);
+    let digits = digits(search);
+    let has_digits = (!digits.is_empty()).into_sql::<Bool>();
+    let visible = c_table.filter(contact_group_filter(scope)).select(c_q::id);
 
-    let rows: Vec<(PhoneCall, Contact)> = table
+    table
         .inner_join(c_table.on(c_q::id.eq(q::contact_id)))
-        .select((PhoneCall::as_select(), Contact::as_select()))
+        .select((
+            PhoneCall::as_select(),
+            Contact::as_select(),
+            c_q::call_count,
+        ))
         .filter(
             c_q::name
-                .ilike(format!("%{}%", search))
-                .or(c_q::phone_number.ilike(format!("%{}%", search))),
+                .ilike(pattern.clone())
+                .or(c_q::phone_number.ilike(pattern))
+                .or(has_digits.and(c_q::phone_number_digits.like(contains_pattern(&digits)))),
         )
+        .filter(q::contact_id.eq_any(visible))
         .order((q::inserted_at.desc(),))
         .limit(10)
         .get_results(conn)
-        .await?;
-
-    // For each result, fetch the phone call count for the associated contact.
-    let mut out = Vec::with_capacity(rows.len());
-    for (phone_call, contact) in rows {
-        let count: i64 = pc_table
-            .filter(pc::contact_id.eq(contact.id))
-            .select(count_star())
-            .first(conn)
-            .await?;
-        out.push((phone_call, contact, count));
-    }
-    Ok(out)
+        .await
 }
 
+/// Paginated phone call search.
+///
+/// `action:`, `after:`, `before:` and `dest:` match the call itself, the
+/// other terms match the call's contact as in the contact search.  `filter`
+/// applies on top of the terms, and its UTC offset sets where the days of
+/// both start.  Only calls of contacts visible within `scope` are returned.
 pub async fn search_phone_calls_paginated(
     conn: &mut DatabaseConnection,
-    search: &str,
+    scope: &GroupScope,
+    terms: &[SearchTerm],
+    filter: &model::PhoneCallFilter,
     before: Option<(chrono::DateTime<chrono::Utc>, i64)>,
     page_size: i64,
 ) -> Result<Vec<(PhoneCall, Contact, i64)>, diesel::result::Error> {
     use crate::server::database::schema::contacts::dsl as c_q;
     use crate::server::database::schema::contacts::table as c_table;
     use crate::server::database::schema::phone_calls::dsl as q;
-    use crate::server::database::schema::phone_calls::dsl as pc;
     use crate::server::database::schema::phone_calls::table;
-    use crate::server::database::schema::phone_calls::table as pc_table;
-    use diesel::dsl::count_star;
 
-    let search = search.replace("%", "\\%");
-    let pattern = format!("%{}%", search);
+    let visible = c_table.filter(contact_group_filter(scope)).select(c_q::id);
 
-    let base = table
+    let mut base = table
         .inner_join(c_table.on(c_q::id.eq(q::contact_id)))
-        .select((PhoneCall::as_select(), Contact::as_select()))
-        .filter(
-            c_q::name
-                .ilike(pattern.clone())
-                .or(c_q::phone_number.ilike(pattern)),
-        )
+        .select((
+            PhoneCall::as_select(),
+            Contact::as_select(),
+            c_q::call_count,
+        ))
+        .filter(q::contact_id.eq_any(visible))
         .order((q::inserted_at.desc(), q::id.desc()))
         .limit(page_size)
         .into_boxed();
 
-    let rows: Vec<(PhoneCall, Contact)> = match before {
-        None => base.get_results(conn).await?,
+    let mut contact_terms = vec![];
+    for term in terms {
+        match term {
+            SearchTerm::Action(action) => base = base.filter(q::action.eq(action.clone())),
+            SearchTerm::After(date) => {
+                base = base.filter(q::inserted_at.ge(filter.start_of(*date)))
+            }
+            SearchTerm::Before(date) => {
+                base = base.filter(q::inserted_at.lt(filter.start_of(*date)))
+            }
+            SearchTerm::Dest(number) => {
+                base = base.filter(q::destination_number.eq(number.clone()))
+            }
+            SearchTerm::Text(_)
+            | SearchTerm::Name(_)
+            | SearchTerm::Calls(..)
+            | SearchTerm::Tag(_) => contact_terms.push(term.clone()),
+        }
+    }
+
+    if let Some(from_date) = &filter.from_date {
+        base = base.filter(q::inserted_at.ge(filter.start_of(*from_date)));
+    }
+
+    if let Some(next_day) = filter.to_date.and_then(|date| date.succ_opt()) {
+        base = base.filter(q::inserted_at.lt(filter.start_of(next_day)));
+    }
+
+    if let Some(action) = &filter.action {
+        base = base.filter(q::action.eq(action.clone()));
+    }
+
+    if let Some(dest) = &filter.dest {
+        base = base.filter(q::destination_number.eq(dest.clone()));
+    }
+
+    if !contact_terms.is_empty() {
+        let contacts = c_table
+            .filter(contact_query_filter(&contact_terms, Utc::now()))
+            .select(c_q::id);
+        base = base.filter(q::contact_id.eq_any(contacts));
+    }
+
+    match before {
+        None => base.get_results(conn).await,
         Some((ts, id)) => {
             base.filter(
                 q::inserted_at
@@ -116,38 +172,51 @@ pub async fn search_phone_calls_paginated(
                     .or(q::inserted_at.eq(ts).and(q::id.lt(id))),
             )
             .get_results(conn)
-            .await?
+            .await
         }
-    };
-
-    let mut out = Vec::with_capacity(rows.len());
-    for (phone_call, contact) in rows {
-        let count: i64 = pc_table
-            .filter(pc::contact_id.eq(contact.id))
-            .select(count_star())
-            .first(conn)
-            .await?;
-        out.push((phone_call, contact, count));
     }
-    Ok(out)
 }
 
+/// Calls of `contact_id`, none if the contact is not visible within `scope`.
 pub async fn get_phone_calls_for_contact(
     conn: &mut DatabaseConnection,
+    scope: &GroupScope,
     contact_id: i64,
+    filter: &model::PhoneCallFilter,
     before: Option<(chrono::DateTime<chrono::Utc>, i64)>,
     page_size: i64,
 ) -> Result<Vec<PhoneCall>, diesel::result::Error> {
+    use crate::server::database::schema::contacts::dsl as c_q;
+    use crate::server::database::schema::contacts::table as c_table;
     use crate::server::database::schema::phone_calls::dsl as q;
     use crate::server::database::schema::phone_calls::table;
 
-    let base = table
+    let visible = c_table.filter(contact_group_filter(scope)).select(c_q::id);
+
+    let mut base = table
         .select(PhoneCall::as_select())
         .filter(q::contact_id.eq(contact_id))
+        .filter(q::contact_id.eq_any(visible))
         .order((q::inserted_at.desc(), q::id.desc()))
         .limit(page_size)
         .into_boxed();
 
+    if let Some(from_date) = &filter.from_date {
+        base = base.filter(q::inserted_at.ge(filter.start_of(*from_date)));
+    }
+
+    if let Some(next_day) = filter.to_date.and_then(|date| date.succ_opt()) {
+        base = base.filter(q::inserted_at.lt(filter.start_of(next_day)));
+    }
+
+    if let Some(action) = &filter.action {
+        base = base.filter(q::action.eq(action.clone()));
+    }
+
+    if let Some(dest) = &filter.dest {
+        base = base.filter(q::destination_number.eq(dest.clone()));
+    }
+
     match before {
         None => base.get_results(conn).await,
         Some((ts, id)) => {
@@ -177,6 +246,192 @@ pub async fn get_phone_call_by_id(
         .optional()
 }
 
+/// Up to `limit` calls from `source_number` before and after the call `id`
+/// at `inserted_at`, of contacts visible within `scope`.  The earlier calls
+/// are newest first, the later ones oldest first.
+pub async fn get_neighbouring_phone_calls(
+    conn: &mut DatabaseConnection,
+    scope: &GroupScope,
+    source_number: &str,
+    inserted_at: DateTime<Utc>,
+    id: i64,
+    limit: i64,
+) -> Result<(Vec<PhoneCall>, Vec<PhoneCall>), diesel::result::Error> {
+    use crate::server::database::schema::contacts::dsl as c_q;
+    use crate::server::database::schema::contacts::table as c_table;
+    use crate::server::database::schema::phone_calls::dsl as q;
+    use crate::server::database::schema::phone_calls::table;
+
+    let visible = || c_table.filter(contact_group_filter(scope)).select(c_q::id);
+
+    let earlier = table
+        .select(PhoneCall::as_select())
+        .filter(q::source_number.eq(source_number))
+        .filter(q::contact_id.eq_any(visible()))
+        .filter(
+            q::inserted_at
+                .lt(inserted_at)
+                .or(q::inserted_at.eq(inserted_at).and(q::id.lt(id))),
+        )
+        .order((q::inserted_at.desc(), q::id.desc()))
+        .limit(limit)
+        .get_results(conn)
+        .await?;
+
+    let later = table
+        .select(PhoneCall::as_select())
+        .filter(q::source_number.eq(source_number))
+        .filter(q::contact_id.eq_any(visible()))
+        .filter(
+            q::inserted_at
+                .gt(inserted_at)
+                .or(q::inserted_at.eq(inserted_at).and(q::id.gt(id))),
+        )
+        .order((q::inserted_at.asc(), q::id.asc()))
+        .limit(limit)
+        .get_results(conn)
+        .await?;
+
+    Ok((earlier, later))
+}
+
+/// Number of calls since `since` for each action.
+pub async fn count_phone_calls_by_action(
+    conn: &mut DatabaseConnection,
+    scope: &GroupScope,
+    since: DateTime<Utc>,
+) -> Result<Vec<(String, i64)>, diesel::result::Error> {
+    use crate::server::database::schema::contacts::dsl as c_q;
+    use crate::server::database::schema::contacts::table as c_table;
+    use crate::server::database::schema::phone_calls::dsl as q;
+    use crate::server::database::schema::phone_calls::table;
+    use diesel::dsl::count_star;
+
+    let visible = c_table.filter(contact_group_filter(scope)).select(c_q::id);
+
+    table
+        .filter(q::inserted_at.ge(since))
+        .filter(q::contact_id.eq_any(visible))
+        .group_by(q::action)
+        .select((q::action, count_star()))
+        .order(q::action.asc())
+        .get_results(conn)
+        .await
+}
+
+/// The group ids of `scope` bound into raw SQL, where `None` stands for
+/// every group as in `GroupScope::All`.
+fn scope_group_ids(scope: &GroupScope) -> Option<Vec<i64>> {
+    match scope {
+        GroupScope::All => None,
+        GroupScope::Member(group_ids) => Some(group_ids.clone()),
+    }
+}
+
+#[derive(QueryableByName, Debug, Clone)]
+pub struct DailyActionCount {
+    #[diesel(sql_type = Date)]
+    pub day: NaiveDate,
+    #[diesel(sql_type = Text)]
+    pub action: String,
+    #[diesel(sql_type = BigInt)]
+    pub count: i64,
+}
+
+/// Number of calls since `since` for each action on each day, where days
+/// start at midnight `utc_offset_seconds` ahead of UTC.  Only calls of
+/// contacts visible within `scope`.
+pub async fn count_phone_calls_by_day(
+    conn: &mut DatabaseConnection,
+    scope: &GroupScope,
+    since: DateTime<Utc>,
+    utc_offset_seconds: i32,
+) -> Result<Vec<DailyActionCount>, diesel::result::Error> {
+    diesel::sql_query(
+        "SELECT ((p.inserted_at AT TIME ZONE 'UTC') + $1 * INTERVAL '1 second')::date AS day, \
+                p.action, count(*) AS count \
+         FROM phone_calls p \
+         JOIN contacts c ON c.id = p.contact_id \
+         WHERE p.inserted_at >= $2 \
+           AND ($3::bigint[] IS NULL OR c.group_id IS NULL OR c.group_id = ANY($3)) \
+         GROUP BY day, p.action \
+         ORDER BY day, p.action",
+    )
+    .bind::<Integer, _>(utc_offset_seconds)
+    .bind::<Timestamptz, _>(since)
+    .bind::<Nullable<Array<BigInt>>, _>(scope_group_ids(scope))
+    .load(conn)
+    .await
+}
+
+#[derive(QueryableByName, Debug, Clone)]
+pub struct HourlyActionCount {
+    #[diesel(sql_type = Integer)]
+    pub weekday: i32,
+    #[diesel(sql_type = Integer)]
+    pub hour: i32,
+    #[diesel(sql_type = Text)]
+    pub action: String,
+    #[diesel(sql_type = BigInt)]
+    pub count: i64,
+}
+
+/// Number of calls for each action in each hour of the week, where Monday is
+/// weekday 0 and hours are `utc_offset_seconds` ahead of UTC.  Only calls of
+/// contacts visible within `scope`, and of `contact_id` if given.
+pub async fn count_phone_calls_by_hour(
+    conn: &mut DatabaseConnection,
+    scope: &GroupScope,
+    contact_id: Option<i64>,
+    utc_offset_seconds: i32,
+) -> Result<Vec<HourlyActionCount>, diesel::result::Error> {
+    diesel::sql_query(
+        "SELECT (EXTRACT(ISODOW FROM local_time)::int - 1) AS weekday, \
+                EXTRACT(HOUR FROM local_time)::int AS hour, \
+                action, count(*) AS count \
+         FROM (SELECT (p.inserted_at AT TIME ZONE 'UTC') + $1 * INTERVAL '1 second' AS local_time, \
+                      p.action \
+               FROM phone_calls p \
+               JOIN contacts c ON c.id = p.contact_id \
+               WHERE ($2::bigint IS NULL OR p.contact_id = $2) \
+                 AND ($3::bigint[] IS NULL OR c.group_id IS NULL OR c.group_id = ANY($3))) AS calls \
+         GROUP BY weekday, hour, action \
+         ORDER BY weekday, hour, action",
+    )
+    .bind::<Integer, _>(utc_offset_seconds)
+    .bind::<Nullable<BigInt>, _>(contact_id)
+    .bind::<Nullable<Array<BigInt>>, _>(scope_group_ids(scope))
+    .load(conn)
+    .await
+}
+
+/// Ids of the contacts visible within `scope` with the most calls since
+/// `since`, with that number of calls.
+pub async fn get_top_callers(
+    conn: &mut DatabaseConnection,
+    scope: &GroupScope,
+    since: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<(i64, i64)>, diesel::result::Error> {
+    use crate::server::database::schema::contacts::dsl as c_q;
+    use crate::server::database::schema::contacts::table as c_table;
+    use crate::server::database::schema::phone_calls::dsl as q;
+    use crate::server::database::schema::phone_calls::table;
+    use diesel::dsl::count_star;
+
+    let visible = c_table.filter(contact_group_filter(scope)).select(c_q::id);
+
+    table
+        .filter(q::inserted_at.ge(since))
+        .filter(q::contact_id.eq_any(visible))
+        .group_by(q::contact_id)
+        .select((q::contact_id, count_star()))
+        .order((count_star().desc(), q::contact_id.asc()))
+        .limit(limit)
+        .get_results(conn)
+        .await
+}
+
 #[derive(Insertable, Debug, Clone)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 #[diesel(table_name = schema::phone_calls)]
@@ -184,7 +439,9 @@ pub struct NewPhoneCall {
     pub action: String,
     pub contact_id: i64,
     pub destination_number: Option<String>,
-    pub source_number: String,
+    pub source_number: Option<String>,
+    pub contact_name: Option<String>,
+    pub decision_source: Option<String>,
     pub inserted_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
 }
@@ -196,7 +453,9 @@ impl NewPhoneCall {
             action: phone_call.action.clone(),
             contact_id: phone_call.contact_id.as_inner(),
             destination_number: phone_call.destination_number.clone(),
-            source_number: phone_call.source_number.clone(),
+            source_number: Some(phone_call.source_number.clone()),
+            contact_name: phone_call.contact_name.clone(),
+            decision_source: Some(phone_call.decision_source.as_str().to_string()),
             inserted_at: now,
             updated_at: now,
         }
@@ -216,6 +475,119 @@ pub async fn create_phone_call(
         .await
 }
 
+/// Move every phone call belonging to one of `from_contact_ids` onto
+/// `to_contact_id`.  Returns the number of calls moved.
+pub async fn reassign_phone_calls(
+    conn: &mut DatabaseConnection,
+    from_contact_ids: &[i64],
+    to_contact_id: i64,
+) -> Result<usize, diesel::result::Error> {
+    use crate::server::database::schema::phone_calls::dsl as q;
+    use crate::server::database::schema::phone_calls::table;
+
+    diesel::update(table.filter(q::contact_id.eq_any(from_contact_ids)))
+        .set((
+            q::contact_id.eq(to_contact_id),
+            q::updated_at.eq(Utc::now()),
+        ))
+        .execute(conn)
+        .await
+}
+
+/// Delete the call history of a contact before the contact itself is
+/// permanently deleted.
+pub async fn delete_phone_calls_for_contact(
+    conn: &mut DatabaseConnection,
+    contact_id: i64,
+) -> Result<usize, diesel::result::Error> {
+    use crate::server::database::schema::phone_calls::dsl as q;
+    use crate::server::database::schema::phone_calls::table;
+
+    diesel::delete(table.filter(q::contact_id.eq(contact_id)))
+        .execute(conn)
+        .await
+}
+
+/// Number of calls before `cutoff`.
+pub async fn count_phone_calls_before(
+    conn: &mut DatabaseConnection,
+    cutoff: DateTime<Utc>,
+) -> Result<i64, diesel::result::Error> {
+    use crate::server::database::schema::phone_calls::dsl as q;
+    use crate::server::database::schema::phone_calls::table;
+    use diesel::dsl::count_star;
+
+    table
+        .filter(q::inserted_at.lt(cutoff))
+        .select(count_star())
+        .get_result(conn)
+        .await
+}
+
+/// Delete every call before `cutoff`.  Returns the contact of each deleted
+/// call, so that their call statistics can be refreshed.
+pub async fn delete_phone_calls_before(
+    conn: &mut DatabaseConnection,
+    cutoff: DateTime<Utc>,
+) -> Result<Vec<i64>, diesel::result::Error> {
+    use crate::server::database::schema::phone_calls::dsl as q;
+    use crate::server::database::schema::phone_calls::table;
+
+    diesel::delete(table.filter(q::inserted_at.lt(cutoff)))
+        .returning(q::contact_id)
+        .get_results(conn)
+        .await
+}
+
+/// Number of calls before `cutoff` that still have numbers or a name.
+pub async fn count_identifiable_phone_calls_before(
+    conn: &mut DatabaseConnection,
+    cutoff: DateTime<Utc>,
+) -> Result<i64, diesel::result::Error> {
+    use crate::server::database::schema::phone_calls::dsl as q;
+    use crate::server::database::schema::phone_calls::table;
+    use diesel::dsl::count_star;
+
+    table
+        .filter(q::inserted_at.lt(cutoff))
+        .filter(
+            q::source_number
+                .is_not_null()
+                .or(q::destination_number.is_not_null())
+                .or(q::contact_name.is_not_null()),
+        )
+        .select(count_star())
+        .get_result(conn)
+        .await
+}
+
+/// Clear the numbers and contact name of every call before `cutoff`.  The
+/// action, time and contact of the call are kept.
+pub async fn anonymise_phone_calls_before(
+    conn: &mut DatabaseConnection,
+    cutoff: DateTime<Utc>,
+) -> Result<usize, diesel::result::Error> {
+    use crate::server::database::schema::phone_calls::dsl as q;
+    use crate::server::database::schema::phone_calls::table;
+
+    diesel::update(
+        table.filter(q::inserted_at.lt(cutoff)).filter(
+            q::source_number
+                .is_not_null()
+                .or(q::destination_number.is_not_null())
+                .or(q::contact_name.is_not_null()),
+        ),
+    )
+    .set((
+        q::source_number.eq(None::<String>),
+        q::destination_number.eq(None::<String>),
+        q::contact_name.eq(None::<String>),
+        q::updated_at.eq(Utc::now()),
+    ))
+    .execute(conn)
+    .await
+}
+
 #[derive(AsChangeset, Debug, Clone)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 #[diesel(table_name = schema::phone_calls)]
@@ -223,7 +595,8 @@ pub struct ChangePhoneCall {
     pub action: Option<String>,
     pub contact_id: Option<i64>,
     pub destination_number: Option<Option<String>>,
-    pub source_number: Option<String>,
+    pub source_number: Option<Option<String>>,
+    pub contact_name: Option<Option<String>>,
     pub inserted_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
 }
@@ -234,37 +607,38 @@ impl ChangePhoneCall {
             action: phone_call.action.clone().into_option(),
             contact_id: phone_call.contact_id.map(|x| x.as_inner()).into_option(),
             destination_number: phone_call.destination_number.clone().into_option(),
-            source_number: phone_call.source_number.clone().into_option(),
+            source_number: phone_call.source_number.clone().map(Some).into_option(),
+            contact_name: phone_call.contact_name.clone().into_option(),
             inserted_at: None,
             updated_at: Some(Utc::now()),
         }
     }
 }
 
-// pub async fn update_phone_call(
-//     conn: &mut DatabaseConnection,
-//     id: i64,
-//     update: &ChangePhoneCall<'_>,
-// ) -> Result<PhoneCall, diesel::result::Error> {
-//     use crate::server::database::schema::phone_calls::dsl as q;
-//     use crate::server::database::schema::phone_calls::table;
-
-//     diesel::update(table.filter(q::id.eq(id)))
-//         .set(update)
-//         .returning(PhoneCall::as_returning())
-//         .get_result(conn)
-//         .await
-// }
-
-// pub async fn delete_phone_call(
-//     conn: &mut DatabaseConnection,
-//     id: i64,
-// ) -> Result<(), diesel::result::Error> {
-//     use crate::server::database::schema::phone_calls::dsl as q;
-//     use crate::server::database::schema::phone_calls::table;
-
-//     diesel::delete(table.filter(q::id.eq(id)))
-//         .execute(conn)
-//         .await?;
-//     Ok(())
-// }
+pub async fn update_phone_call(
+    conn: &mut DatabaseConnection,
+    id: i64,
+    update: &ChangePhoneCall,
+) -> Result<PhoneCall, diesel::result::Error> {
+    use crate::server::database::schema::phone_calls::dsl as q;
+    use crate::server::database::schema::phone_calls::table;
+
+    diesel::update(table.filter(q::id.eq(id)))
+        .set(update)
+        .returning(PhoneCall::as_returning())
+        .get_result(conn)
+        .await
+}
+
+pub async fn delete_phone_call(
+    conn: &mut DatabaseConnection,
+    id: i64,
+) -> Result<(), diesel::result::Error> {
+    use crate::server::database::schema::phone_calls::dsl as q;
+    use crate::server::database::schema::phone_calls::table;
+
+    diesel::delete(table.filter(q::id.eq(id)))
+        .execute(conn)
+        .await?;
+    Ok(())
+}
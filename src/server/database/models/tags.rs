@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+
+use diesel::prelude::*;
+use diesel::{ExpressionMethods, QueryDsl, Queryable, Selectable};
+use diesel_async::RunQueryDsl;
+
+use chrono::DateTime;
+use chrono::Utc;
+
+use crate::models::tags as model;
+use crate::server::database::{connection::DatabaseConnection, schema};
+
+#[allow(dead_code)]
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = schema::tags)]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+    pub action: Option<String>,
+    pub inserted_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<Tag> for model::Tag {
+    fn from(tag: Tag) -> Self {
+        Self {
+            id: model::TagId::new(tag.id),
+            name: tag.name,
+            action: tag.action,
+            inserted_at: tag.inserted_at,
+            updated_at: tag.updated_at,
+        }
+    }
+}
+
+pub async fn get_all_tags(
+    conn: &mut DatabaseConnection,
+) -> Result<Vec<Tag>, diesel::result::Error> {
+    use crate::server::database::schema::tags::dsl as q;
+    use crate::server::database::schema::tags::table;
+
+    table
+        .select(Tag::as_select())
+        .order(q::name.asc())
+        .get_results(conn)
+        .await
+}
+
+pub async fn get_tag_by_id(
+    conn: &mut DatabaseConnection,
+    id: i64,
+) -> Result<Option<Tag>, diesel::result::Error> {
+    use crate::server::database::schema::tags::dsl as q;
+    use crate::server::database::schema::tags::table;
+
+    table
+        .select(Tag::as_select())
+        .filter(q::id.eq(id))
+        .get_result(conn)
+        .await
+        .optional()
+}
+
+/// Fetch the tags of every contact in `contact_ids`, keyed by contact id.
+pub async fn get_tags_for_contacts(
+    conn: &mut DatabaseConnection,
+    contact_ids: &[i64],
+) -> Result<HashMap<i64, Vec<model::Tag>>, diesel::result::Error> {
+    use crate::server::database::schema::contact_tags::dsl as ct;
+    use crate::server::database::schema::contact_tags::table as ct_table;
+    use crate::server::database::schema::tags::dsl as q;
+    use crate::server::database::schema::tags::table;
+
+    let rows: Vec<(i64, Tag)> = ct_table
+        .inner_join(table.on(q::id.eq(ct::tag_id)))
+        .select((ct::contact_id, Tag::as_select()))
+        .filter(ct::contact_id.eq_any(contact_ids))
+        .order(q::name.asc())
+        .get_results(conn)
+        .await?;
+
+    let mut out: HashMap<i64, Vec<model::Tag>> = HashMap::new();
+    for (contact_id, tag) in rows {
+        out.entry(contact_id).or_default().push(tag.into());
+    }
+    Ok(out)
+}
+
+pub async fn get_contact_ids_for_tag(
+    conn: &mut DatabaseConnection,
+    tag_id: i64,
+) -> Result<Vec<i64>, diesel::result::Error> {
+    use crate::server::database::schema::contact_tags::dsl as ct;
+    use crate::server::database::schema::contact_tags::table;
+
+    table
+        .select(ct::contact_id)
+        .filter(ct::tag_id.eq(tag_id))
+        .get_results(conn)
+        .await
+}
+
+/// Look up tags by name, creating any that do not exist yet.
+pub async fn get_or_create_tags(
+    conn: &mut DatabaseConnection,
+    names: &[String],
+) -> Result<Vec<Tag>, diesel::result::Error> {
+    use crate::server::database::schema::tags::dsl as q;
+    use crate::server::database::schema::tags::table;
+
+    let now = Utc::now();
+    let new_tags: Vec<NewTag> = names
+        .iter()
+        .map(|name| NewTag {
+            name: name.clone(),
+            action: None,
+            inserted_at: now,
+            updated_at: now,
+        })
+        .collect();
+
+    diesel::insert_into(table)
+        .values(&new_tags)
+        .on_conflict(q::name)
+        .do_nothing()
+        .execute(conn)
+        .await?;
+
+    table
+        .select(Tag::as_select())
+        .filter(q::name.eq_any(names))
+        .order(q::name.asc())
+        .get_results(conn)
+        .await
+}
+
+/// Replace the tags of a contact with `tag_ids`.
+pub async fn set_contact_tags(
+    conn: &mut DatabaseConnection,
+    contact_id: i64,
+    tag_ids: &[i64],
+) -> Result<(), diesel::result::Error> {
+    use crate::server::database::schema::contact_tags::dsl as ct;
+    use crate::server::database::schema::contact_tags::table;
+
+    diesel::delete(table.filter(ct::contact_id.eq(contact_id)))
+        .execute(conn)
+        .await?;
+
+    let rows: Vec<_> = tag_ids
+        .iter()
+        .map(|tag_id| (ct::contact_id.eq(contact_id), ct::tag_id.eq(*tag_id)))
+        .collect();
+
+    diesel::insert_into(table)
+        .values(&rows)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Move the tags of `from_contact_ids` onto `to_contact_id`, skipping tags
+/// it already has.
+pub async fn reassign_contact_tags(
+    conn: &mut DatabaseConnection,
+    from_contact_ids: &[i64],
+    to_contact_id: i64,
+) -> Result<(), diesel::result::Error> {
+    use crate::server::database::schema::contact_tags::dsl as ct;
+    use crate::server::database::schema::contact_tags::table;
+
+    let tag_ids: Vec<i64> = table
+        .select(ct::tag_id)
+        .filter(ct::contact_id.eq_any(from_contact_ids))
+        .distinct()
+        .get_results(conn)
+        .await?;
+
+    let rows: Vec<_> = tag_ids
+        .iter()
+        .map(|tag_id| (ct::contact_id.eq(to_contact_id), ct::tag_id.eq(*tag_id)))
+        .collect();
+
+    diesel::insert_into(table)
+        .values(&rows)
+        .on_conflict_do_nothing()
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = schema::tags)]
+pub struct NewTag {
+    pub name: String,
+    pub action: Option<String>,
+    pub inserted_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl NewTag {
+    pub fn from_front_end(tag: &model::NewTag) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            name: tag.name.clone(),
+            action: tag.action.clone(),
+            inserted_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+pub async fn create_tag(
+    conn: &mut DatabaseConnection,
+    update: NewTag,
+) -> Result<Tag, diesel::result::Error> {
+    use crate::server::database::schema::tags::table;
+
+    diesel::insert_into(table)
+        .values(&update)
+        .returning(Tag::as_returning())
+        .get_result(conn)
+        .await
+}
+
+#[derive(AsChangeset, Debug, Clone)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(table_name = schema::tags)]
+pub struct ChangeTag {
+    pub name: Option<String>,
+    pub action: Option<Option<String>>,
+    pub inserted_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl ChangeTag {
+    pub fn from_front_end(tag: &model::ChangeTag) -> Self {
+        Self {
+            name: tag.name.clone().into_option(),
+            action: tag.action.clone().into_option(),
+            inserted_at: None,
+            updated_at: Some(Utc::now()),
+        }
+    }
+}
+
+pub async fn update_tag(
+    conn: &mut DatabaseConnection,
+    id: i64,
+    update: ChangeTag,
+) -> Result<Tag, diesel::result::Error> {
+    use crate::server::database::schema::tags::dsl as q;
+    use crate::server::database::schema::tags::table;
+
+    diesel::update(table.filter(q::id.eq(id)))
+        .set(&update)
+        .returning(Tag::as_returning())
+        .get_result(conn)
+        .await
+}
+
+pub async fn delete_tag(
+    conn: &mut DatabaseConnection,
+    id: i64,
+) -> Result<(), diesel::result::Error> {
+    use crate::server::database::schema::tags::dsl as q;
+    use crate::server::database::schema::tags::table;
+
+    diesel::delete(table.filter(q::id.eq(id)))
+        .execute(conn)
+        .await?;
+    Ok(())
+}
@@ -1,5 +1,51 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    audit_log (id) {
+        id -> Int8,
+        user_id -> Nullable<Int8>,
+        #[max_length = 255]
+        entity_type -> Varchar,
+        entity_id -> Int8,
+        #[max_length = 255]
+        operation -> Varchar,
+        before -> Nullable<Jsonb>,
+        after -> Nullable<Jsonb>,
+        inserted_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    contact_note_revisions (id) {
+        id -> Int8,
+        contact_id -> Int8,
+        user_id -> Nullable<Int8>,
+        comments -> Nullable<Text>,
+        inserted_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    contact_numbers (id) {
+        id -> Int8,
+        contact_id -> Int8,
+        #[max_length = 255]
+        phone_number -> Varchar,
+        #[max_length = 255]
+        label -> Nullable<Varchar>,
+        inserted_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        phone_number_digits -> Text,
+    }
+}
+
+diesel::table! {
+    contact_tags (contact_id, tag_id) {
+        contact_id -> Int8,
+        tag_id -> Int8,
+    }
+}
+
 diesel::table! {
     contacts (id) {
         id -> Int8,
@@ -11,8 +57,16 @@ diesel::table! {
         action -> Varchar,
         inserted_at -> Timestamptz,
         updated_at -> Timestamptz,
+        comments -> Nullable<Text>,
+        archived_at -> Nullable<Timestamptz>,
         #[max_length = 255]
-        comments -> Nullable<Varchar>,
+        temporary_action -> Nullable<Varchar>,
+        temporary_action_expires_at -> Nullable<Timestamptz>,
+        phone_number_digits -> Text,
+        call_count -> Int8,
+        first_call_at -> Nullable<Timestamptz>,
+        last_call_at -> Nullable<Timestamptz>,
+        group_id -> Nullable<Int8>,
     }
 }
 
@@ -28,6 +82,8 @@ diesel::table! {
         action -> Varchar,
         inserted_at -> Timestamptz,
         updated_at -> Timestamptz,
+        archived_at -> Nullable<Timestamptz>,
+        group_id -> Nullable<Int8>,
     }
 }
 
@@ -51,7 +107,11 @@ diesel::table! {
         #[max_length = 255]
         destination_number -> Nullable<Varchar>,
         #[max_length = 255]
-        source_number -> Varchar,
+        source_number -> Nullable<Varchar>,
+        #[max_length = 255]
+        contact_name -> Nullable<Varchar>,
+        #[max_length = 255]
+        decision_source -> Nullable<Varchar>,
     }
 }
 
@@ -63,6 +123,18 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    tags (id) {
+        id -> Int8,
+        #[max_length = 255]
+        name -> Varchar,
+        #[max_length = 255]
+        action -> Nullable<Varchar>,
+        inserted_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     user_groups (user_id, group_id) {
         user_id -> Int8,
@@ -84,16 +156,29 @@ diesel::table! {
     }
 }
 
+diesel::joinable!(audit_log -> users (user_id));
+diesel::joinable!(contact_note_revisions -> contacts (contact_id));
+diesel::joinable!(contact_note_revisions -> users (user_id));
+diesel::joinable!(contact_numbers -> contacts (contact_id));
+diesel::joinable!(contact_tags -> contacts (contact_id));
+diesel::joinable!(contact_tags -> tags (tag_id));
+diesel::joinable!(contacts -> groups (group_id));
+diesel::joinable!(defaults -> groups (group_id));
 diesel::joinable!(phone_calls -> contacts (contact_id));
 diesel::joinable!(user_groups -> groups (group_id));
 diesel::joinable!(user_groups -> users (user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    audit_log,
+    contact_note_revisions,
+    contact_numbers,
+    contact_tags,
     contacts,
     defaults,
     groups,
     phone_calls,
     session,
+    tags,
     user_groups,
     users,
 );
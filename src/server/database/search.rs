@@ -0,0 +1,69 @@
+//! Helpers for free text search.
+//!
+//! Phone numbers are matched on their digits only, using the generated
+//! `phone_number_digits` columns, and names with `pg_trgm` so that small
+//! typos still match.  Both are backed by trigram indexes.
+
+use diesel::declare_sql_function;
+use diesel::dsl::AsExprOf;
+use diesel::expression::AsExpression;
+use diesel::sql_types::{Float4, Nullable, Text};
+
+#[declare_sql_function]
+extern "SQL" {
+    /// Greatest similarity between `search` and any part of `value`, from
+    /// 0 to 1.
+    fn word_similarity(search: Text, value: Nullable<Text>) -> Nullable<Float4>;
+}
+
+diesel::infix_operator!(WordSimilar, " <% ", backend: diesel::pg::Pg);
+
+/// `search <% column`: true if [`word_similarity`] is above the `pg_trgm`
+/// threshold (0.6 by default).
+pub fn word_similar<C>(search: &str, column: C) -> WordSimilar<AsExprOf<String, Text>, C> {
+    WordSimilar::new(
+        AsExpression::<Text>::as_expression(search.to_string()),
+        column,
+    )
+}
+
+/// Escape `\`, `%` and `_` so `search` matches literally inside a LIKE
+/// pattern.
+pub fn escape_like(search: &str) -> String {
+    let mut out = String::with_capacity(search.len());
+    for c in search.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// A LIKE pattern matching `search` anywhere.
+pub fn contains_pattern(search: &str) -> String {
+    format!("%{}%", escape_like(search))
+}
+
+/// The digits of `search`, for matching against `phone_number_digits`.
+pub fn digits(search: &str) -> String {
+    search.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contains_pattern() {
+        assert_eq!(contains_pattern("john"), "%john%");
+        assert_eq!(contains_pattern("50%_off\\"), "%50\\%\\_off\\\\%");
+    }
+
+    #[test]
+    fn test_digits() {
+        assert_eq!(digits("0412 345 678"), "0412345678");
+        assert_eq!(digits("+61 (2) 9999-8888"), "61299998888");
+        assert_eq!(digits("john"), "");
+    }
+}
@@ -0,0 +1,60 @@
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::models::audit_log as models;
+use crate::models::users::UserId;
+use crate::server::database::connection as database;
+use crate::server::database::models::audit_log;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Database error: {0}")]
+    Database(#[from] database::Error),
+    #[error("Diesel error: {0}")]
+    Diesel(#[from] diesel::result::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Record a change.  Call inside the transaction making the change, so the
+/// log entry and the change are committed together.
+pub async fn log<T: Serialize>(
+    conn: &mut database::DatabaseConnection,
+    user_id: Option<UserId>,
+    entity: models::AuditEntity,
+    entity_id: i64,
+    operation: models::AuditOperation,
+    before: Option<&T>,
+    after: Option<&T>,
+) -> Result<(), Error> {
+    let entry = audit_log::NewAuditLog {
+        user_id: user_id.map(|id| id.as_inner()),
+        entity_type: entity.as_str().to_string(),
+        entity_id,
+        operation: operation.as_str().to_string(),
+        before: before.map(serde_json::to_value).transpose()?,
+        after: after.map(serde_json::to_value).transpose()?,
+        inserted_at: chrono::Utc::now(),
+    };
+
+    audit_log::create_audit_log(conn, entry).await?;
+
+    Ok(())
+}
+
+pub async fn search_audit_log(
+    conn: &mut database::DatabaseConnection,
+    filter: models::AuditLogFilter,
+    before_id: Option<models::AuditLogId>,
+    page_size: i64,
+) -> Result<Vec<models::AuditLogEntry>, Error> {
+    let rows =
+        audit_log::search_audit_log(conn, &filter, before_id.map(|id| id.as_inner()), page_size)
+            .await
+            .map_err(database::Error::from)?;
+
+    rows.into_iter()
+        .map(|(row, username)| row.into_model(username))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(Error::from)
+}
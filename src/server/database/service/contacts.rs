@@ -1,9 +1,19 @@
 use diesel_async::AsyncConnection;
 use thiserror::Error;
 
+use crate::models::audit_log::{AuditEntity, AuditOperation};
+use crate::models::common::MaybeSet;
 use crate::models::contacts as models;
+use crate::models::groups::GroupId;
+use crate::models::ldap::{LdapDrift, LdapDriftKind, LdapReconcileReport};
+use crate::models::search::{SearchQuery, SearchQueryError};
+use crate::models::users::UserId;
 use crate::server::database::connection as database;
-use crate::server::database::models::contacts;
+use crate::server::database::models::groups::GroupScope;
+use crate::server::database::models::{
+    contact_note_revisions, contact_numbers, contacts, phone_calls, tags,
+};
+use crate::server::database::service::audit_log;
 use crate::server::ldap::connect as ldap;
 use crate::server::ldap::query::Error as LdapError;
 
@@ -15,30 +25,300 @@ pub enum Error {
     Diesel(#[from] diesel::result::Error),
     #[error("LDAP error: {0}")]
     Ldap(#[from] LdapError),
+    #[error("Invalid merge: {0}")]
+    InvalidMerge(String),
+    #[error("Phone number {0} already belongs to a contact")]
+    DuplicatePhoneNumber(String),
+    #[error("Only archived contacts can be deleted")]
+    NotArchived,
+    #[error("Note revision not found")]
+    NoteRevisionNotFound,
+    #[error("Phone number not found on this contact")]
+    NumberNotFound,
+    #[error("Page cursor does not match the sort order")]
+    InvalidCursor,
+    #[error("Contact not found")]
+    NotVisible,
+    #[error("Not a member of the contact's group")]
+    GroupNotAllowed,
+    #[error("Audit log error: {0}")]
+    AuditLog(#[from] audit_log::Error),
+    #[error("Invalid search: {0}")]
+    InvalidQuery(#[from] SearchQueryError),
+}
+
+/// Convert a single contact row into a front end model, with its tags.
+async fn into_model(
+    conn: &mut database::DatabaseConnection,
+    row: Option<(contacts::Contact, i64)>,
+) -> Result<Option<models::Contact>, Error> {
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    Ok(contacts::into_models(conn, vec![row]).await?.pop())
+}
+
+/// Load the current state of a contact inside a transaction, for the audit log.
+async fn load_contact(
+    conn: &mut database::DatabaseConnection,
+    contact_id: i64,
+) -> Result<models::Contact, Error> {
+    let row = contacts::get_contact_by_id(conn, contact_id).await?;
+    into_model(conn, row)
+        .await?
+        .ok_or(diesel::result::Error::NotFound)
+        .map_err(Error::from)
+}
+
+/// Record a change to a contact in the audit log, and keep a revision of its
+/// notes if they changed.
+pub async fn audit_contact(
+    conn: &mut database::DatabaseConnection,
+    user_id: Option<UserId>,
+    contact_id: i64,
+    operation: AuditOperation,
+    before: Option<&models::Contact>,
+    after: Option<&models::Contact>,
+) -> Result<(), Error> {
+    audit_log::log(
+        conn,
+        user_id,
+        AuditEntity::Contact,
+        contact_id,
+        operation,
+        before,
+        after,
+    )
+    .await?;
+
+    if let Some(after) = after
+        && before.and_then(|b| b.comments.as_ref()) != after.comments.as_ref()
+    {
+        let revision = contact_note_revisions::NewContactNoteRevision {
+            contact_id,
+            user_id: user_id.map(|id| id.as_inner()),
+            comments: after.comments.clone(),
+            inserted_at: chrono::Utc::now(),
+        };
+        contact_note_revisions::create_note_revision(conn, revision).await?;
+    }
+
+    Ok(())
+}
+
+/// Fail unless every one of the contacts exists and is visible within
+/// `scope`.  Hidden contacts are reported as not found.
+pub async fn check_contacts_visible(
+    conn: &mut database::DatabaseConnection,
+    scope: &GroupScope,
+    contact_ids: &[models::ContactId],
+) -> Result<(), Error> {
+    let ids: Vec<i64> = contact_ids.iter().map(|id| id.as_inner()).collect();
+    let groups = contacts::get_contact_groups(conn, &ids).await?;
+
+    let all_visible = ids.iter().all(|id| {
+        groups
+            .iter()
+            .any(|(contact_id, group_id)| contact_id == id && scope.allows(*group_id))
+    });
+
+    if all_visible {
+        Ok(())
+    } else {
+        Err(Error::NotVisible)
+    }
+}
+
+/// Fail unless a contact may be put in `group_id` by a user with `scope`.
+pub fn check_group_allowed(scope: &GroupScope, group_id: Option<GroupId>) -> Result<(), Error> {
+    if scope.allows(group_id.map(|id| id.as_inner())) {
+        Ok(())
+    } else {
+        Err(Error::GroupNotAllowed)
+    }
+}
+
+/// Replace the tags of a contact by name, creating missing tags.
+pub async fn set_contact_tags(
+    conn: &mut database::DatabaseConnection,
+    contact_id: i64,
+    names: &[String],
+) -> Result<(), Error> {
+    let tag_ids: Vec<i64> = tags::get_or_create_tags(conn, names)
+        .await?
+        .into_iter()
+        .map(|t| t.id)
+        .collect();
+    tags::set_contact_tags(conn, contact_id, &tag_ids).await?;
+    Ok(())
+}
+
+/// Write the contact and all of its numbers to LDAP.
+pub async fn sync_ldap_contact(
+    conn: &mut database::DatabaseConnection,
+    old_phone_number: &str,
+    contact: &models::Contact,
+    base_dn: &str,
+    ldap: &mut ldap::LdapConnection,
+) -> Result<(), Error> {
+    use crate::server::ldap::query::update_ldap_contact_from_contact;
+
+    let numbers: Vec<models::ContactNumber> =
+        contact_numbers::get_numbers_for_contact(conn, contact.id.as_inner())
+            .await?
+            .into_iter()
+            .map(models::ContactNumber::from)
+            .collect();
+
+    update_ldap_contact_from_contact(old_phone_number, contact, &numbers, base_dn, ldap).await?;
+
+    Ok(())
+}
+
+/// Compare every contact with the person entries under `base_dn`, and with
+/// `fix` make LDAP match the contacts.  Entries are matched by uid, or for
+/// entries named before uids were used, by primary phone number.
+pub async fn reconcile_ldap(
+    conn: &mut database::DatabaseConnection,
+    base_dn: &str,
+    ldap: &mut ldap::LdapConnection,
+    fix: bool,
+) -> Result<LdapReconcileReport, Error> {
+    use crate::server::ldap::query;
+
+    let rows = contacts::get_all_contacts(conn).await?;
+    let all_contacts = contacts::into_models(conn, rows).await?;
+    let ids: Vec<i64> = all_contacts.iter().map(|c| c.id.as_inner()).collect();
+    let mut all_numbers = contact_numbers::get_numbers_for_contacts(conn, &ids).await?;
+
+    let entries = query::get_all_contacts(base_dn, ldap).await?;
+    let keys: Vec<(String, &str)> = all_contacts
+        .iter()
+        .map(|contact| (contact.id.to_string(), contact.phone_number.as_str()))
+        .collect();
+    let matches = query::match_ldap_contacts(&keys, &entries);
+    let mut matched = vec![false; entries.len()];
+    let mut drift = vec![];
+
+    for (contact, index) in all_contacts.iter().zip(matches) {
+        let numbers: Vec<models::ContactNumber> = all_numbers
+            .remove(&contact.id.as_inner())
+            .unwrap_or_default()
+            .into_iter()
+            .map(models::ContactNumber::from)
+            .collect();
+
+        if let Some(index) = index {
+            matched[index] = true;
+        }
+        let entry = index.map(|index| &entries[index]);
+
+        let drift_for =
+            |kind, entry: Option<&query::LdapContact>, attributes: Vec<&'static str>| LdapDrift {
+                kind,
+                contact_id: Some(contact.id),
+                phone_number: Some(contact.phone_number.clone()),
+                dn: entry.map(|e| e.dn().as_str().to_string()),
+                attributes: attributes.into_iter().map(String::from).collect(),
+            };
+
+        match (query::expected_ldap_contact(contact, &numbers), entry) {
+            (Some(expected), None) => {
+                drift.push(drift_for(LdapDriftKind::Missing, None, vec![]));
+                if fix {
+                    query::put_ldap_contact(expected, None, base_dn, ldap).await?;
+                }
+            }
+            (Some(expected), Some(entry)) => {
+                let attributes = query::ldap_contact_differences(&expected, base_dn, entry);
+                if !attributes.is_empty() {
+                    drift.push(drift_for(
+                        LdapDriftKind::Mismatched,
+                        Some(entry),
+                        attributes,
+                    ));
+                    if fix {
+                        query::put_ldap_contact(expected, Some(entry.dn()), base_dn, ldap).await?;
+                    }
+                }
+            }
+            (None, Some(entry)) => {
+                drift.push(drift_for(LdapDriftKind::Extra, Some(entry), vec![]));
+                if fix {
+                    query::delete_ldap_contact(entry.dn(), ldap).await?;
+                }
+            }
+            (None, None) => {}
+        }
+    }
+
+    for (entry, _) in entries
+        .iter()
+        .zip(&matched)
+        .filter(|(_, matched)| !**matched)
+    {
+        drift.push(LdapDrift {
+            kind: LdapDriftKind::Extra,
+            contact_id: None,
+            phone_number: entry.telephone_numbers().first().cloned(),
+            dn: Some(entry.dn().as_str().to_string()),
+            attributes: vec![],
+        });
+        if fix {
+            query::delete_ldap_contact(entry.dn(), ldap).await?;
+        }
+    }
+
+    Ok(LdapReconcileReport {
+        contacts: all_contacts.len(),
+        entries: entries.len(),
+        drift,
+        fixed: fix,
+    })
 }
 
 pub async fn search_contacts(
     conn: &mut database::DatabaseConnection,
+    scope: &GroupScope,
     query: String,
 ) -> Result<Vec<models::Contact>, Error> {
-    contacts::search_contacts(conn, &query)
+    let rows = contacts::search_contacts(conn, scope, &query)
+        .await
+        .map_err(database::Error::from)?;
+
+    contacts::into_models(conn, rows)
         .await
-        .map(|rows| rows.into_iter().map(|(c, n)| c.into_model(n)).collect())
         .map_err(database::Error::from)
         .map_err(Error::from)
 }
 
 pub async fn search_contacts_paginated(
     conn: &mut database::DatabaseConnection,
-    query: String,
-    before_name: Option<Option<String>>,
-    before_id: Option<models::ContactId>,
+    scope: &GroupScope,
+    query: &SearchQuery,
+    tag: Option<String>,
+    sort: models::ContactSort,
+    cursor: Option<models::ContactCursor>,
     page_size: i64,
 ) -> Result<Vec<models::Contact>, Error> {
-    let before_id_raw = before_id.map(|id| id.as_inner());
-    contacts::search_contacts_paginated(conn, &query, before_name, before_id_raw, page_size)
+    let terms = query.terms()?;
+    if cursor.as_ref().is_some_and(|cursor| cursor.sort() != sort) {
+        return Err(Error::InvalidCursor);
+    }
+    let rows = contacts::search_contacts_paginated(
+        conn,
+        scope,
+        &terms,
+        tag.as_deref(),
+        sort,
+        cursor,
+        page_size,
+    )
+    .await
+    .map_err(database::Error::from)?;
+
+    contacts::into_models(conn, rows)
         .await
-        .map(|rows| rows.into_iter().map(|(c, n)| c.into_model(n)).collect())
         .map_err(database::Error::from)
         .map_err(Error::from)
 }
@@ -47,52 +327,74 @@ pub async fn get_contact_by_id(
     conn: &mut database::DatabaseConnection,
     id: models::ContactId,
 ) -> Result<Option<models::Contact>, Error> {
-    contacts::get_contact_by_id(conn, id.as_inner())
+    let row = contacts::get_contact_by_id(conn, id.as_inner())
         .await
-        .map(|x| x.map(|(c, n)| c.into_model(n)))
-        .map_err(database::Error::from)
-        .map_err(Error::from)
+        .map_err(database::Error::from)?;
+
+    into_model(conn, row).await
 }
 
 pub async fn get_contact_by_phone_number(
     conn: &mut database::DatabaseConnection,
     phone_number: &str,
 ) -> Result<Option<models::Contact>, Error> {
-    contacts::get_contact_by_phone_number(conn, phone_number)
+    let row = contacts::get_contact_by_phone_number(conn, phone_number)
         .await
-        .map(|x| x.map(|(c, n)| c.into_model(n)))
-        .map_err(database::Error::from)
-        .map_err(Error::from)
+        .map_err(database::Error::from)?;
+
+    into_model(conn, row).await
 }
 
 pub async fn create_contact(
     conn: &mut database::DatabaseConnection,
     base_dn: &str,
     ldap: &mut ldap::LdapConnection,
+    user_id: Option<UserId>,
     contact: models::NewContact,
 ) -> Result<models::Contact, Error> {
     let new_contact = contacts::NewContact::from_front_end(&contact);
+    let tag_names = contact.tags;
     let base_dn = base_dn.to_string();
     let ldap = ldap.clone();
 
     conn.transaction::<_, Error, _>(async move |conn| {
         let mut ldap = ldap;
+
+        if contact_numbers::get_contact_number_by_phone_number(conn, &new_contact.phone_number)
+            .await?
+            .is_some()
+        {
+            return Err(Error::DuplicatePhoneNumber(new_contact.phone_number));
+        }
+
         let contact = contacts::create_contact(conn, new_contact)
             .await
             .map_err(Error::from)?;
 
-        use crate::server::ldap::query::update_ldap_contact_from_contact;
+        set_contact_tags(conn, contact.id, &tag_names).await?;
 
-        let model_contact = contact.into_model(0);
+        let model_contact = into_model(conn, Some((contact, 0)))
+            .await?
+            .ok_or(diesel::result::Error::NotFound)?;
 
-        update_ldap_contact_from_contact(
+        audit_contact(
+            conn,
+            user_id,
+            model_contact.id.as_inner(),
+            AuditOperation::Create,
+            None,
+            Some(&model_contact),
+        )
+        .await?;
+
+        sync_ldap_contact(
+            conn,
             &model_contact.phone_number,
             &model_contact,
             &base_dn,
             &mut ldap,
         )
-        .await
-        .map_err(Error::from)?;
+        .await?;
 
         Ok(model_contact)
     })
@@ -103,10 +405,12 @@ pub async fn update_contact(
     conn: &mut database::DatabaseConnection,
     base_dn: &str,
     ldap: &mut ldap::LdapConnection,
+    user_id: Option<UserId>,
     old_contact: models::Contact,
     change_contact: models::ChangeContact,
 ) -> Result<models::Contact, Error> {
     let updates = contacts::ChangeContact::from_front_end(&change_contact);
+    let tag_names = change_contact.tags.into_option();
     let old_contact_id = old_contact.id.as_inner();
     let old_phone_number = old_contact.phone_number.clone();
     let base_dn = base_dn.to_string();
@@ -114,31 +418,99 @@ pub async fn update_contact(
 
     conn.transaction::<_, Error, _>(async move |conn| {
         let mut ldap = ldap;
+
+        if let Some(phone_number) = &updates.phone_number
+            && let Some(number) =
+                contact_numbers::get_contact_number_by_phone_number(conn, phone_number).await?
+            && number.contact_id != old_contact_id
+        {
+            return Err(Error::DuplicatePhoneNumber(phone_number.clone()));
+        }
+
+        let before = load_contact(conn, old_contact_id).await?;
+
         let contact = contacts::update_contact(conn, old_contact_id, updates)
             .await
             .map_err(Error::from)?;
 
-        let count = contacts::get_phone_call_count(conn, contact.id)
-            .await
-            .map_err(Error::from)?;
+        if let Some(tag_names) = &tag_names {
+            set_contact_tags(conn, contact.id, tag_names).await?;
+        }
 
-        use crate::server::ldap::query::update_ldap_contact_from_contact;
+        let count = contact.call_count;
+        let model_contact = into_model(conn, Some((contact, count)))
+            .await?
+            .ok_or(diesel::result::Error::NotFound)?;
 
-        let model_contact = contact.into_model(count);
+        audit_contact(
+            conn,
+            user_id,
+            old_contact_id,
+            AuditOperation::Update,
+            Some(&before),
+            Some(&model_contact),
+        )
+        .await?;
 
-        update_ldap_contact_from_contact(&old_phone_number, &model_contact, &base_dn, &mut ldap)
-            .await
-            .map_err(Error::from)?;
+        sync_ldap_contact(conn, &old_phone_number, &model_contact, &base_dn, &mut ldap).await?;
 
         Ok(model_contact)
     })
     .await
 }
 
+pub async fn get_note_revisions(
+    conn: &mut database::DatabaseConnection,
+    contact_id: models::ContactId,
+) -> Result<Vec<models::ContactNoteRevision>, Error> {
+    contact_note_revisions::get_note_revisions_for_contact(conn, contact_id.as_inner())
+        .await
+        .map(|rows| {
+            rows.into_iter()
+                .map(|(row, username)| row.into_model(username))
+                .collect()
+        })
+        .map_err(database::Error::from)
+        .map_err(Error::from)
+}
+
+/// Put back the notes of an earlier revision.  This is an ordinary update,
+/// so it is audited and kept as a new revision.  The contact must be
+/// visible within `scope`.
+pub async fn restore_note_revision(
+    conn: &mut database::DatabaseConnection,
+    base_dn: &str,
+    ldap: &mut ldap::LdapConnection,
+    user_id: Option<UserId>,
+    scope: &GroupScope,
+    revision_id: models::ContactNoteRevisionId,
+) -> Result<models::Contact, Error> {
+    let revision = contact_note_revisions::get_note_revision_by_id(conn, revision_id.as_inner())
+        .await?
+        .ok_or(Error::NoteRevisionNotFound)?;
+
+    check_contacts_visible(conn, scope, &[models::ContactId::new(revision.contact_id)]).await?;
+
+    let contact = load_contact(conn, revision.contact_id).await?;
+
+    let change = models::ChangeContact {
+        phone_number: MaybeSet::NoChange,
+        name: MaybeSet::NoChange,
+        action: MaybeSet::NoChange,
+        comments: MaybeSet::Set(revision.comments),
+        tags: MaybeSet::NoChange,
+        temporary_action: MaybeSet::NoChange,
+        group_id: MaybeSet::NoChange,
+    };
+
+    update_contact(conn, base_dn, ldap, user_id, contact, change).await
+}
+
 pub async fn delete_contact(
     conn: &mut database::DatabaseConnection,
     base_dn: &str,
     ldap: &mut ldap::LdapConnection,
+    user_id: Option<UserId>,
     old_contact: models::Contact,
 ) -> Result<(), Error> {
     let old_contact_id = old_contact.id.as_inner();
@@ -148,10 +520,30 @@ pub async fn delete_contact(
 
     conn.transaction::<_, Error, _>(async move |conn| {
         let mut ldap = ldap;
+
+        let before = load_contact(conn, old_contact_id).await?;
+        if before.archived_at.is_none() {
+            return Err(Error::NotArchived);
+        }
+
+        phone_calls::delete_phone_calls_for_contact(conn, old_contact_id)
+            .await
+            .map_err(Error::from)?;
+
         crate::server::database::models::contacts::delete_contact(conn, old_contact_id)
             .await
             .map_err(Error::from)?;
 
+        audit_contact(
+            conn,
+            user_id,
+            old_contact_id,
+            AuditOperation::Delete,
+            Some(&before),
+            None,
+        )
+        .await?;
+
         use crate::server::ldap::query::delete_ldap_contact_from_phone_number;
 
         delete_ldap_contact_from_phone_number(&old_phone_number, &base_dn, &mut ldap)
@@ -162,3 +554,812 @@ pub async fn delete_contact(
     })
     .await
 }
+
+/// Set or clear the archived timestamp of a contact.  Archived contacts are
+/// removed from LDAP by [`sync_ldap_contact`], restored ones are added back.
+async fn set_contact_archived(
+    conn: &mut database::DatabaseConnection,
+    base_dn: &str,
+    ldap: &mut ldap::LdapConnection,
+    user_id: Option<UserId>,
+    contact_id: i64,
+    archived_at: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<models::Contact, Error> {
+    let before = load_contact(conn, contact_id).await?;
+
+    let updates = contacts::ChangeContact {
+        phone_number: None,
+        name: None,
+        action: None,
+        comments: None,
+        inserted_at: None,
+        updated_at: Some(chrono::Utc::now()),
+        archived_at: Some(archived_at),
+        temporary_action: None,
+        temporary_action_expires_at: None,
+        group_id: None,
+    };
+    contacts::update_contact(conn, contact_id, updates)
+        .await
+        .map_err(Error::from)?;
+
+    let model_contact = load_contact(conn, contact_id).await?;
+
+    let operation = if archived_at.is_some() {
+        AuditOperation::Archive
+    } else {
+        AuditOperation::Restore
+    };
+    audit_contact(
+        conn,
+        user_id,
+        contact_id,
+        operation,
+        Some(&before),
+        Some(&model_contact),
+    )
+    .await?;
+
+    sync_ldap_contact(conn, &before.phone_number, &model_contact, base_dn, ldap).await?;
+
+    Ok(model_contact)
+}
+
+/// Hide a contact from search and call matching until it is restored or
+/// purged.
+pub async fn archive_contact(
+    conn: &mut database::DatabaseConnection,
+    base_dn: &str,
+    ldap: &mut ldap::LdapConnection,
+    user_id: Option<UserId>,
+    old_contact: models::Contact,
+) -> Result<models::Contact, Error> {
+    let old_contact_id = old_contact.id.as_inner();
+    let base_dn = base_dn.to_string();
+    let ldap = ldap.clone();
+
+    conn.transaction::<_, Error, _>(async move |conn| {
+        let mut ldap = ldap;
+        set_contact_archived(
+            conn,
+            &base_dn,
+            &mut ldap,
+            user_id,
+            old_contact_id,
+            Some(chrono::Utc::now()),
+        )
+        .await
+    })
+    .await
+}
+
+/// Bring back an archived contact.  Fails if one of its numbers has since
+/// been given to another contact.
+pub async fn restore_contact(
+    conn: &mut database::DatabaseConnection,
+    base_dn: &str,
+    ldap: &mut ldap::LdapConnection,
+    user_id: Option<UserId>,
+    old_contact: models::Contact,
+) -> Result<models::Contact, Error> {
+    let old_contact_id = old_contact.id.as_inner();
+    let base_dn = base_dn.to_string();
+    let ldap = ldap.clone();
+
+    conn.transaction::<_, Error, _>(async move |conn| {
+        let mut ldap = ldap;
+
+        let contact = load_contact(conn, old_contact_id).await?;
+        let numbers = contact_numbers::get_numbers_for_contact(conn, old_contact_id).await?;
+        let phone_numbers = std::iter::once(contact.phone_number.clone())
+            .chain(numbers.into_iter().map(|n| n.phone_number));
+
+        for phone_number in phone_numbers {
+            if contacts::get_contact_by_phone_number(conn, &phone_number)
+                .await?
+                .is_some()
+            {
+                return Err(Error::DuplicatePhoneNumber(phone_number));
+            }
+        }
+
+        set_contact_archived(conn, &base_dn, &mut ldap, user_id, old_contact_id, None).await
+    })
+    .await
+}
+
+pub async fn get_archived_contacts(
+    conn: &mut database::DatabaseConnection,
+    scope: &GroupScope,
+) -> Result<Vec<models::Contact>, Error> {
+    let rows = contacts::get_archived_contacts(conn, scope)
+        .await
+        .map_err(database::Error::from)?;
+
+    contacts::into_models(conn, rows)
+        .await
+        .map_err(database::Error::from)
+        .map_err(Error::from)
+}
+
+/// Permanently delete contacts, with their call history, that were archived
+/// before `cutoff`.  Archived contacts are not in LDAP so it is not touched.
+/// Returns the number of contacts deleted.
+pub async fn purge_archived_contacts(
+    conn: &mut database::DatabaseConnection,
+    cutoff: chrono::DateTime<chrono::Utc>,
+) -> Result<usize, Error> {
+    conn.transaction::<_, Error, _>(async move |conn| {
+        let ids = contacts::get_contact_ids_archived_before(conn, cutoff).await?;
+
+        for &contact_id in &ids {
+            let before = load_contact(conn, contact_id).await?;
+
+            phone_calls::delete_phone_calls_for_contact(conn, contact_id).await?;
+            contacts::delete_contact(conn, contact_id).await?;
+
+            audit_contact(
+                conn,
+                None,
+                contact_id,
+                AuditOperation::Delete,
+                Some(&before),
+                None,
+            )
+            .await?;
+        }
+
+        Ok(ids.len())
+    })
+    .await
+}
+
+/// Delete contacts that were created by incoming calls and have had no
+/// calls since `calls_since`, along with their LDAP entries and any older
+/// calls.  With `dry_run` nothing is deleted.  Returns the number of contacts.
+pub async fn purge_unused_contacts(
+    conn: &mut database::DatabaseConnection,
+    base_dn: &str,
+    ldap: &mut ldap::LdapConnection,
+    calls_since: chrono::DateTime<chrono::Utc>,
+    dry_run: bool,
+) -> Result<usize, Error> {
+    let base_dn = base_dn.to_string();
+    let ldap = ldap.clone();
+
+    conn.transaction::<_, Error, _>(async move |conn| {
+        let mut ldap = ldap;
+        let ids = contacts::get_unused_contact_ids(conn, calls_since).await?;
+        if dry_run {
+            return Ok(ids.len());
+        }
+
+        for &contact_id in &ids {
+            let before = load_contact(conn, contact_id).await?;
+
+            phone_calls::delete_phone_calls_for_contact(conn, contact_id).await?;
+            contacts::delete_contact(conn, contact_id).await?;
+
+            audit_contact(
+                conn,
+                None,
+                contact_id,
+                AuditOperation::Delete,
+                Some(&before),
+                None,
+            )
+            .await?;
+
+            use crate::server::ldap::query::delete_ldap_contact_from_phone_number;
+
+            delete_ldap_contact_from_phone_number(&before.phone_number, &base_dn, &mut ldap)
+                .await
+                .map_err(Error::from)?;
+        }
+
+        Ok(ids.len())
+    })
+    .await
+}
+
+/// Clear temporary actions that expired at or before `now`, so that LDAP
+/// follows the permanent action again.  Returns the number of contacts
+/// changed.
+pub async fn expire_temporary_actions(
+    conn: &mut database::DatabaseConnection,
+    base_dn: &str,
+    ldap: &mut ldap::LdapConnection,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<usize, Error> {
+    let base_dn = base_dn.to_string();
+    let ldap = ldap.clone();
+
+    conn.transaction::<_, Error, _>(async move |conn| {
+        let mut ldap = ldap;
+        let ids = contacts::get_contact_ids_with_expired_temporary_action(conn, now).await?;
+
+        for &contact_id in &ids {
+            let before = load_contact(conn, contact_id).await?;
+
+            let updates = contacts::ChangeContact {
+                phone_number: None,
+                name: None,
+                action: None,
+                comments: None,
+                inserted_at: None,
+                updated_at: Some(chrono::Utc::now()),
+                archived_at: None,
+                temporary_action: Some(None),
+                temporary_action_expires_at: Some(None),
+                group_id: None,
+            };
+            contacts::update_contact(conn, contact_id, updates)
+                .await
+                .map_err(Error::from)?;
+
+            let model_contact = load_contact(conn, contact_id).await?;
+
+            audit_contact(
+                conn,
+                None,
+                contact_id,
+                AuditOperation::Update,
+                Some(&before),
+                Some(&model_contact),
+            )
+            .await?;
+
+            sync_ldap_contact(
+                conn,
+                &before.phone_number,
+                &model_contact,
+                &base_dn,
+                &mut ldap,
+            )
+            .await?;
+        }
+
+        Ok(ids.len())
+    })
+    .await
+}
+
+pub async fn preview_merge_contacts(
+    conn: &mut database::DatabaseConnection,
+    contact_ids: Vec<models::ContactId>,
+) -> Result<models::MergeContactsPreview, Error> {
+    let ids: Vec<i64> = contact_ids.iter().map(|id| id.as_inner()).collect();
+
+    let rows = contacts::get_contacts_by_ids(conn, &ids)
+        .await
+        .map_err(database::Error::from)?;
+
+    let contacts: Vec<models::Contact> = contacts::into_models(conn, rows)
+        .await
+        .map_err(database::Error::from)?;
+
+    let phone_call_count = contacts.iter().map(|c| c.phone_call_count).sum();
+
+    Ok(models::MergeContactsPreview {
+        contacts,
+        phone_call_count,
+    })
+}
+
+/// The contacts a merge touches, checked before anything is written.
+#[derive(Debug, Eq, PartialEq)]
+struct MergePlan {
+    survivor_id: i64,
+    /// Distinct ids of the contacts whose calls, numbers and tags move to the
+    /// survivor before they are deleted.
+    merged_ids: Vec<i64>,
+}
+
+impl MergePlan {
+    fn new(survivor_id: i64, merged_ids: &[i64]) -> Result<Self, Error> {
+        let mut merged_ids = merged_ids.to_vec();
+        merged_ids.sort_unstable();
+        merged_ids.dedup();
+
+        if merged_ids.contains(&survivor_id) {
+            return Err(Error::InvalidMerge(
+                "The surviving contact cannot also be merged away".to_string(),
+            ));
+        }
+
+        if merged_ids.is_empty() {
+            return Err(Error::InvalidMerge(
+                "At least two contacts are required".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            survivor_id,
+            merged_ids,
+        })
+    }
+
+    fn all_ids(&self) -> Vec<i64> {
+        let mut ids = self.merged_ids.clone();
+        ids.push(self.survivor_id);
+        ids
+    }
+
+    /// The primary numbers of the merged contacts, kept as additional numbers
+    /// of the survivor so future calls still match.  `found` is the id and
+    /// primary number of every contact loaded for the merge.
+    fn moved_numbers(&self, found: &[(i64, &str)]) -> Result<Vec<String>, Error> {
+        let number_of = |id: &i64| {
+            found
+                .iter()
+                .find(|(found_id, _)| found_id == id)
+                .map(|(_, number)| number.to_string())
+        };
+
+        if number_of(&self.survivor_id).is_none() {
+            return Err(Error::InvalidMerge(
+                "One or more contacts no longer exist".to_string(),
+            ));
+        }
+
+        self.merged_ids
+            .iter()
+            .map(|id| {
+                number_of(id).ok_or_else(|| {
+                    Error::InvalidMerge("One or more contacts no longer exist".to_string())
+                })
+            })
+            .collect()
+    }
+}
+
+/// Merge several contacts into one surviving contact.
+///
+/// All phone calls of the merged contacts are re-pointed to the survivor, the
+/// merged contacts are deleted and the survivor takes the chosen name, action
+/// and comments.  The survivor's LDAP entry is updated in the same
+/// transaction, and the entries of the merged contacts are deleted once it is
+/// committed.  Repeated ids in `merged_ids` are ignored; the survivor must not
+/// be one of them.
+pub async fn merge_contacts(
+    conn: &mut database::DatabaseConnection,
+    base_dn: &str,
+    ldap: &mut ldap::LdapConnection,
+    user_id: Option<UserId>,
+    merge: models::MergeContacts,
+) -> Result<models::Contact, Error> {
+    use crate::server::ldap::query::{delete_ldap_contact, get_contact_entry};
+
+    let merged_ids: Vec<i64> = merge.merged_ids.iter().map(|id| id.as_inner()).collect();
+    let plan = MergePlan::new(merge.survivor_id.as_inner(), &merged_ids)?;
+
+    let updates = contacts::ChangeContact {
+        phone_number: None,
+        name: Some(merge.name),
+        action: Some(merge.action),
+        comments: Some(merge.comments),
+        inserted_at: None,
+        updated_at: Some(chrono::Utc::now()),
+        archived_at: None,
+        temporary_action: None,
+        temporary_action_expires_at: None,
+        group_id: None,
+    };
+    let base_dn = base_dn.to_string();
+    let mut merged_ldap = ldap.clone();
+    let ldap = ldap.clone();
+
+    let (model_contact, merged_dns) = conn
+        .transaction::<_, Error, _>(async move |conn| {
+            let mut ldap = ldap;
+            let survivor_id = plan.survivor_id;
+
+            let existing = contacts::get_contacts_by_ids(conn, &plan.all_ids())
+                .await
+                .map_err(Error::from)?;
+            let found: Vec<(i64, &str)> = existing
+                .iter()
+                .map(|(c, _)| (c.id, c.phone_number.as_str()))
+                .collect();
+            let moved_numbers = plan.moved_numbers(&found)?;
+
+            let Some((survivor, _)) = existing.iter().find(|(c, _)| c.id == survivor_id) else {
+                return Err(Error::InvalidMerge("Cannot find survivor".to_string()));
+            };
+            let old_phone_number = survivor.phone_number.clone();
+            let before = load_contact(conn, survivor_id).await?;
+
+            crate::server::database::models::phone_calls::reassign_phone_calls(
+                conn,
+                &plan.merged_ids,
+                survivor_id,
+            )
+            .await
+            .map_err(Error::from)?;
+
+            contacts::refresh_call_statistics(conn, &plan.all_ids())
+                .await
+                .map_err(Error::from)?;
+
+            contact_numbers::reassign_contact_numbers(conn, &plan.merged_ids, survivor_id)
+                .await
+                .map_err(Error::from)?;
+
+            tags::reassign_contact_tags(conn, &plan.merged_ids, survivor_id)
+                .await
+                .map_err(Error::from)?;
+
+            let mut merged_dns = vec![];
+            for (contact, _) in existing.iter().filter(|(c, _)| c.id != survivor_id) {
+                let merged = load_contact(conn, contact.id).await?;
+
+                // Looked up before the survivor's entry takes over the number.
+                if let Some(entry) =
+                    get_contact_entry(&merged.phone_number, &merged, &base_dn, &mut ldap).await?
+                {
+                    merged_dns.push(entry.dn().clone());
+                }
+
+                contacts::delete_contact(conn, contact.id)
+                    .await
+                    .map_err(Error::from)?;
+
+                audit_contact(
+                    conn,
+                    user_id,
+                    contact.id,
+                    AuditOperation::Delete,
+                    Some(&merged),
+                    None,
+                )
+                .await?;
+            }
+
+            for phone_number in moved_numbers {
+                let now = chrono::Utc::now();
+                let number = contact_numbers::NewContactNumber {
+                    contact_id: survivor_id,
+                    phone_number,
+                    label: None,
+                    inserted_at: now,
+                    updated_at: now,
+                };
+                contact_numbers::create_contact_number(conn, number)
+                    .await
+                    .map_err(Error::from)?;
+            }
+
+            let contact = contacts::update_contact(conn, survivor_id, updates)
+                .await
+                .map_err(Error::from)?;
+
+            let count = contact.call_count;
+            let model_contact = into_model(conn, Some((contact, count)))
+                .await?
+                .ok_or(diesel::result::Error::NotFound)?;
+
+            audit_contact(
+                conn,
+                user_id,
+                survivor_id,
+                AuditOperation::Merge,
+                Some(&before),
+                Some(&model_contact),
+            )
+            .await?;
+
+            sync_ldap_contact(conn, &old_phone_number, &model_contact, &base_dn, &mut ldap).await?;
+
+            Ok((model_contact, merged_dns))
+        })
+        .await?;
+
+    // The merge is committed, so an entry that cannot be deleted is left for
+    // the LDAP reconcile rather than failing it.
+    for dn in merged_dns {
+        if let Err(err) = delete_ldap_contact(&dn, &mut merged_ldap).await {
+            tracing::error!(
+                "Error deleting LDAP entry {} of merged contact: {}",
+                dn.as_str(),
+                err
+            );
+        }
+    }
+
+    Ok(model_contact)
+}
+
+/// Build the change a bulk operation makes to a single contact.
+fn bulk_change(
+    contact: &models::Contact,
+    operation: &models::BulkOperation,
+) -> contacts::ChangeContact {
+    let mut change = contacts::ChangeContact {
+        phone_number: None,
+        name: None,
+        action: None,
+        comments: None,
+        inserted_at: None,
+        updated_at: Some(chrono::Utc::now()),
+        archived_at: None,
+        temporary_action: None,
+        temporary_action_expires_at: None,
+        group_id: None,
+    };
+
+    match operation {
+        models::BulkOperation::SetAction(action) => change.action = Some(action.clone()),
+        models::BulkOperation::SetName(name) => change.name = Some(name.clone()),
+        models::BulkOperation::AddComment(comment) => {
+            let comments = match &contact.comments {
+                Some(existing) => format!("{existing}\n\n{comment}"),
+                None => comment.clone(),
+            };
+            change.comments = Some(Some(comments));
+        }
+        models::BulkOperation::Archive => change.archived_at = Some(Some(chrono::Utc::now())),
+    }
+
+    change
+}
+
+/// Apply one operation to many contacts in a single transaction, keeping
+/// LDAP in step for each contact.  Returns the number of contacts changed.
+pub async fn bulk_edit_contacts(
+    conn: &mut database::DatabaseConnection,
+    base_dn: &str,
+    ldap: &mut ldap::LdapConnection,
+    user_id: Option<UserId>,
+    bulk: models::BulkEditContacts,
+) -> Result<usize, Error> {
+    let ids: Vec<i64> = bulk.contact_ids.iter().map(|id| id.as_inner()).collect();
+    let operation = bulk.operation;
+    let base_dn = base_dn.to_string();
+    let ldap = ldap.clone();
+
+    conn.transaction::<_, Error, _>(async move |conn| {
+        let mut ldap = ldap;
+
+        let rows = contacts::get_contacts_by_ids(conn, &ids)
+            .await
+            .map_err(Error::from)?;
+        let existing = contacts::into_models(conn, rows)
+            .await
+            .map_err(Error::from)?;
+
+        let audit_operation = match operation {
+            models::BulkOperation::Archive => AuditOperation::Archive,
+            _ => AuditOperation::Update,
+        };
+
+        for contact in &existing {
+            let contact_id = contact.id.as_inner();
+
+            let updates = bulk_change(contact, &operation);
+            let updated = contacts::update_contact(conn, contact_id, updates)
+                .await
+                .map_err(Error::from)?;
+
+            let model_contact = into_model(conn, Some((updated, contact.phone_call_count)))
+                .await?
+                .ok_or(diesel::result::Error::NotFound)?;
+
+            audit_contact(
+                conn,
+                user_id,
+                contact_id,
+                audit_operation,
+                Some(contact),
+                Some(&model_contact),
+            )
+            .await?;
+
+            sync_ldap_contact(
+                conn,
+                &contact.phone_number,
+                &model_contact,
+                &base_dn,
+                &mut ldap,
+            )
+            .await?;
+        }
+
+        Ok(existing.len())
+    })
+    .await
+}
+
+pub async fn get_contacts_by_ids(
+    conn: &mut database::DatabaseConnection,
+    contact_ids: Vec<models::ContactId>,
+) -> Result<Vec<models::Contact>, Error> {
+    let ids: Vec<i64> = contact_ids.iter().map(|id| id.as_inner()).collect();
+
+    let rows = contacts::get_contacts_by_ids(conn, &ids)
+        .await
+        .map_err(database::Error::from)?;
+
+    contacts::into_models(conn, rows)
+        .await
+        .map_err(database::Error::from)
+        .map_err(Error::from)
+}
+
+pub async fn get_contact_numbers(
+    conn: &mut database::DatabaseConnection,
+    contact_id: models::ContactId,
+) -> Result<Vec<models::ContactNumber>, Error> {
+    contact_numbers::get_numbers_for_contact(conn, contact_id.as_inner())
+        .await
+        .map(|rows| rows.into_iter().map(models::ContactNumber::from).collect())
+        .map_err(database::Error::from)
+        .map_err(Error::from)
+}
+
+pub async fn get_contact_number_by_id(
+    conn: &mut database::DatabaseConnection,
+    id: models::ContactNumberId,
+) -> Result<Option<models::ContactNumber>, Error> {
+    contact_numbers::get_contact_number_by_id(conn, id.as_inner())
+        .await
+        .map(|x| x.map(models::ContactNumber::from))
+        .map_err(database::Error::from)
+        .map_err(Error::from)
+}
+
+/// Add an additional number to a contact.  The number must not already be
+/// used by any contact, either as a primary or additional number.  The
+/// contact must be visible within `scope`.
+pub async fn create_contact_number(
+    conn: &mut database::DatabaseConnection,
+    base_dn: &str,
+    ldap: &mut ldap::LdapConnection,
+    user_id: Option<UserId>,
+    scope: &GroupScope,
+    number: models::NewContactNumber,
+) -> Result<models::ContactNumber, Error> {
+    let new_number = contact_numbers::NewContactNumber::from_front_end(&number);
+    let base_dn = base_dn.to_string();
+    let ldap = ldap.clone();
+    let scope = scope.clone();
+
+    conn.transaction::<_, Error, _>(async move |conn| {
+        let mut ldap = ldap;
+
+        check_contacts_visible(conn, &scope, &[number.contact_id]).await?;
+        let contact = load_contact(conn, new_number.contact_id).await?;
+
+        if contacts::get_contact_by_phone_number(conn, &new_number.phone_number)
+            .await?
+            .is_some()
+        {
+            return Err(Error::DuplicatePhoneNumber(new_number.phone_number));
+        }
+
+        let number: models::ContactNumber =
+            contact_numbers::create_contact_number(conn, new_number)
+                .await
+                .map_err(Error::from)?
+                .into();
+
+        audit_log::log(
+            conn,
+            user_id,
+            AuditEntity::Contact,
+            number.contact_id.as_inner(),
+            AuditOperation::AddNumber,
+            None,
+            Some(&number),
+        )
+        .await?;
+
+        sync_ldap_contact(conn, &contact.phone_number, &contact, &base_dn, &mut ldap).await?;
+
+        Ok(number)
+    })
+    .await
+}
+
+/// Remove an additional number from a contact.  The number is looked up
+/// here rather than trusted from the caller, and must belong to
+/// `contact_id`, which must be visible within `scope`.
+pub async fn delete_contact_number(
+    conn: &mut database::DatabaseConnection,
+    base_dn: &str,
+    ldap: &mut ldap::LdapConnection,
+    user_id: Option<UserId>,
+    scope: &GroupScope,
+    contact_id: models::ContactId,
+    number_id: models::ContactNumberId,
+) -> Result<(), Error> {
+    let base_dn = base_dn.to_string();
+    let ldap = ldap.clone();
+    let scope = scope.clone();
+
+    conn.transaction::<_, Error, _>(async move |conn| {
+        let mut ldap = ldap;
+
+        let number: models::ContactNumber =
+            contact_numbers::get_contact_number_by_id(conn, number_id.as_inner())
+                .await?
+                .ok_or(Error::NumberNotFound)?
+                .into();
+        if number.contact_id != contact_id {
+            return Err(Error::NumberNotFound);
+        }
+
+        check_contacts_visible(conn, &scope, &[number.contact_id]).await?;
+        let contact = load_contact(conn, number.contact_id.as_inner()).await?;
+
+        contact_numbers::delete_contact_number(conn, number.id.as_inner())
+            .await
+            .map_err(Error::from)?;
+
+        audit_log::log(
+            conn,
+            user_id,
+            AuditEntity::Contact,
+            number.contact_id.as_inner(),
+            AuditOperation::RemoveNumber,
+            Some(&number),
+            None,
+        )
+        .await?;
+
+        sync_ldap_contact(conn, &contact.phone_number, &contact, &base_dn, &mut ldap).await?;
+
+        Ok(())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_plan_ignores_repeated_ids() {
+        let plan = MergePlan::new(1, &[3, 2, 3]).unwrap();
+        assert_eq!(plan.merged_ids, vec![2, 3]);
+        assert_eq!(plan.all_ids(), vec![2, 3, 1]);
+    }
+
+    #[test]
+    fn test_merge_plan_rejects_survivor_in_merged_ids() {
+        assert!(matches!(
+            MergePlan::new(1, &[2, 1]),
+            Err(Error::InvalidMerge(_))
+        ));
+        assert!(matches!(
+            MergePlan::new(1, &[1, 1]),
+            Err(Error::InvalidMerge(_))
+        ));
+        assert!(matches!(
+            MergePlan::new(1, &[]),
+            Err(Error::InvalidMerge(_))
+        ));
+    }
+
+    #[test]
+    fn test_merge_plan_moved_numbers() {
+        let plan = MergePlan::new(1, &[3, 2]).unwrap();
+
+        let found = [(1, "0390000001"), (3, "0390000003"), (2, "0390000002")];
+        assert_eq!(
+            plan.moved_numbers(&found).unwrap(),
+            vec!["0390000002", "0390000003"]
+        );
+
+        let missing = [(1, "0390000001"), (2, "0390000002")];
+        assert!(matches!(
+            plan.moved_numbers(&missing),
+            Err(Error::InvalidMerge(_))
+        ));
+
+        let no_survivor = [(2, "0390000002"), (3, "0390000003")];
+        assert!(matches!(
+            plan.moved_numbers(&no_survivor),
+            Err(Error::InvalidMerge(_))
+        ));
+    }
+}
@@ -1,9 +1,14 @@
 use diesel_async::AsyncConnection;
 use thiserror::Error;
 
+use crate::models::audit_log::{AuditEntity, AuditOperation};
 use crate::models::defaults as models;
+use crate::models::groups::GroupId;
+use crate::models::users::UserId;
 use crate::server::database::connection as database;
 use crate::server::database::models::defaults;
+use crate::server::database::models::groups::GroupScope;
+use crate::server::database::service::audit_log;
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -11,13 +16,44 @@ pub enum Error {
     DatabaseError(#[from] database::Error),
     #[error("Diesel error: {0}")]
     DieselError(#[from] diesel::result::Error),
+    #[error("Only archived defaults can be deleted")]
+    NotArchived,
+    #[error("Default not found")]
+    NotVisible,
+    #[error("Not a member of the default's group")]
+    GroupNotAllowed,
+    #[error("Audit log error: {0}")]
+    AuditLog(#[from] audit_log::Error),
+}
+
+/// Fail unless the default exists and is visible within `scope`.  Hidden
+/// defaults are reported as not found.
+pub async fn check_default_visible(
+    conn: &mut database::DatabaseConnection,
+    scope: &GroupScope,
+    id: models::DefaultId,
+) -> Result<(), Error> {
+    match defaults::get_default_by_id(conn, id.as_inner()).await? {
+        Some(default) if scope.allows(default.group_id) => Ok(()),
+        _ => Err(Error::NotVisible),
+    }
+}
+
+/// Fail unless a default may be put in `group_id` by a user with `scope`.
+pub fn check_group_allowed(scope: &GroupScope, group_id: Option<GroupId>) -> Result<(), Error> {
+    if scope.allows(group_id.map(|id| id.as_inner())) {
+        Ok(())
+    } else {
+        Err(Error::GroupNotAllowed)
+    }
 }
 
 pub async fn search_defaults(
     conn: &mut database::DatabaseConnection,
+    scope: &GroupScope,
     query: String,
 ) -> Result<Vec<models::Default>, Error> {
-    defaults::search_defaults(conn, &query)
+    defaults::search_defaults(conn, scope, &query)
         .await
         .map(|x| {
             x.into_iter()
@@ -53,8 +89,31 @@ pub async fn get_default_by_id(
         .map_err(Error::from)
 }
 
+/// Record a change to a default in the audit log.
+async fn audit_default(
+    conn: &mut database::DatabaseConnection,
+    user_id: Option<UserId>,
+    id: i64,
+    operation: AuditOperation,
+    before: Option<&models::Default>,
+    after: Option<&models::Default>,
+) -> Result<(), Error> {
+    audit_log::log(
+        conn,
+        user_id,
+        AuditEntity::Default,
+        id,
+        operation,
+        before,
+        after,
+    )
+    .await?;
+    Ok(())
+}
+
 pub async fn create_default(
     conn: &mut database::DatabaseConnection,
+    user_id: Option<UserId>,
     default: models::NewDefault,
 ) -> Result<models::Default, Error> {
     let new_default = defaults::NewDefault::from_front_end(&default);
@@ -65,6 +124,16 @@ pub async fn create_default(
             .map(|x| x.into())
             .map_err(Error::from)?;
 
+        audit_default(
+            conn,
+            user_id,
+            default.id.as_inner(),
+            AuditOperation::Create,
+            None,
+            Some(&default),
+        )
+        .await?;
+
         Ok(default)
     })
     .await
@@ -72,17 +141,32 @@ pub async fn create_default(
 
 pub async fn update_default(
     conn: &mut database::DatabaseConnection,
+    user_id: Option<UserId>,
     old_default: models::Default,
     change_default: models::ChangeDefault,
 ) -> Result<models::Default, Error> {
     let updates = defaults::ChangeDefault::from_front_end(&change_default);
     let old_default_id = old_default.id.as_inner();
     conn.transaction::<_, Error, _>(async move |conn| {
+        let before: Option<models::Default> = defaults::get_default_by_id(conn, old_default_id)
+            .await?
+            .map(|x| x.into());
+
         let default: models::Default = defaults::update_default(conn, old_default_id, updates)
             .await
             .map(|x| x.into())
             .map_err(Error::from)?;
 
+        audit_default(
+            conn,
+            user_id,
+            old_default_id,
+            AuditOperation::Update,
+            before.as_ref(),
+            Some(&default),
+        )
+        .await?;
+
         Ok(default)
     })
     .await
@@ -90,16 +174,147 @@ pub async fn update_default(
 
 pub async fn delete_default(
     conn: &mut database::DatabaseConnection,
+    user_id: Option<UserId>,
     old_default: models::Default,
 ) -> Result<(), Error> {
     let old_default_id = old_default.id.as_inner();
 
     conn.transaction::<_, Error, _>(async move |conn| {
+        let before: Option<models::Default> = defaults::get_default_by_id(conn, old_default_id)
+            .await?
+            .map(|x| x.into());
+        if before.as_ref().is_some_and(|d| d.archived_at.is_none()) {
+            return Err(Error::NotArchived);
+        }
+
         crate::server::database::models::defaults::delete_default(conn, old_default_id)
             .await
             .map_err(Error::from)?;
 
+        audit_default(
+            conn,
+            user_id,
+            old_default_id,
+            AuditOperation::Delete,
+            before.as_ref(),
+            None,
+        )
+        .await?;
+
         Ok(())
     })
     .await
 }
+
+/// Set or clear the archived timestamp of a default.
+async fn set_default_archived(
+    conn: &mut database::DatabaseConnection,
+    user_id: Option<UserId>,
+    old_default: models::Default,
+    archived_at: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<models::Default, Error> {
+    let old_default_id = old_default.id.as_inner();
+    let updates = defaults::ChangeDefault {
+        order: None,
+        regexp: None,
+        name: None,
+        action: None,
+        inserted_at: None,
+        updated_at: Some(chrono::Utc::now()),
+        archived_at: Some(archived_at),
+        group_id: None,
+    };
+    let operation = if archived_at.is_some() {
+        AuditOperation::Archive
+    } else {
+        AuditOperation::Restore
+    };
+
+    conn.transaction::<_, Error, _>(async move |conn| {
+        let before: Option<models::Default> = defaults::get_default_by_id(conn, old_default_id)
+            .await?
+            .map(|x| x.into());
+
+        let default: models::Default = defaults::update_default(conn, old_default_id, updates)
+            .await
+            .map(|x| x.into())
+            .map_err(Error::from)?;
+
+        audit_default(
+            conn,
+            user_id,
+            old_default_id,
+            operation,
+            before.as_ref(),
+            Some(&default),
+        )
+        .await?;
+
+        Ok(default)
+    })
+    .await
+}
+
+/// Stop matching calls against a default until it is restored or purged.
+pub async fn archive_default(
+    conn: &mut database::DatabaseConnection,
+    user_id: Option<UserId>,
+    old_default: models::Default,
+) -> Result<models::Default, Error> {
+    set_default_archived(conn, user_id, old_default, Some(chrono::Utc::now())).await
+}
+
+pub async fn restore_default(
+    conn: &mut database::DatabaseConnection,
+    user_id: Option<UserId>,
+    old_default: models::Default,
+) -> Result<models::Default, Error> {
+    set_default_archived(conn, user_id, old_default, None).await
+}
+
+pub async fn get_archived_defaults(
+    conn: &mut database::DatabaseConnection,
+    scope: &GroupScope,
+) -> Result<Vec<models::Default>, Error> {
+    defaults::get_archived_defaults(conn, scope)
+        .await
+        .map(|x| {
+            x.into_iter()
+                .map(|y| y.into())
+                .collect::<Vec<models::Default>>()
+        })
+        .map_err(database::Error::from)
+        .map_err(Error::from)
+}
+
+/// Permanently delete defaults archived before `cutoff`.  Returns the
+/// number of defaults deleted.
+pub async fn purge_archived_defaults(
+    conn: &mut database::DatabaseConnection,
+    cutoff: chrono::DateTime<chrono::Utc>,
+) -> Result<usize, Error> {
+    conn.transaction::<_, Error, _>(async move |conn| {
+        let ids = defaults::get_default_ids_archived_before(conn, cutoff).await?;
+
+        for &default_id in &ids {
+            let before: Option<models::Default> = defaults::get_default_by_id(conn, default_id)
+                .await?
+                .map(|x| x.into());
+
+            defaults::delete_default(conn, default_id).await?;
+
+            audit_default(
+                conn,
+                None,
+                default_id,
+                AuditOperation::Delete,
+                before.as_ref(),
+                None,
+            )
+            .await?;
+        }
+
+        Ok(ids.len())
+    })
+    .await
+}
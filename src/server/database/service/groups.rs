@@ -0,0 +1,49 @@
+use diesel_async::AsyncConnection;
+use thiserror::Error;
+
+use crate::models::groups as models;
+use crate::server::database::connection as database;
+use crate::server::database::models::groups::{self, GroupScope};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Database error: {0}")]
+    Database(#[from] database::Error),
+    #[error("Diesel error: {0}")]
+    Diesel(#[from] diesel::result::Error),
+}
+
+/// Groups a user with `scope` may put contacts and defaults in.
+pub async fn get_groups_in_scope(
+    conn: &mut database::DatabaseConnection,
+    scope: &GroupScope,
+) -> Result<Vec<models::Group>, Error> {
+    let groups = groups::get_all_groups(conn)
+        .await
+        .map_err(database::Error::from)?;
+
+    Ok(groups
+        .into_iter()
+        .filter(|group| scope.allows(Some(group.id)))
+        .map(models::Group::from)
+        .collect())
+}
+
+/// Make the user a member of exactly the named groups, creating missing
+/// groups.
+pub async fn set_user_groups(
+    conn: &mut database::DatabaseConnection,
+    user_id: i64,
+    names: Vec<String>,
+) -> Result<(), Error> {
+    conn.transaction::<_, Error, _>(async move |conn| {
+        let group_ids: Vec<i64> = groups::get_or_create_groups(conn, &names)
+            .await?
+            .into_iter()
+            .map(|group| group.id)
+            .collect();
+        groups::set_user_groups(conn, user_id, &group_ids).await?;
+        Ok(())
+    })
+    .await
+}
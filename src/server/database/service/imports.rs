@@ -0,0 +1,547 @@
+use diesel_async::AsyncConnection;
+use thiserror::Error;
+
+use crate::models::audit_log::AuditOperation;
+use crate::models::contacts::{self as models, ACTIONS, DEFAULT_ACTION};
+use crate::models::groups::GroupId;
+use crate::models::imports::{CONTACT_CSV_COLUMNS, ContactRow, ImportReport};
+use crate::models::search::{SearchQuery, SearchQueryError};
+use crate::models::tags::parse_tag_names;
+use crate::models::users::UserId;
+use crate::server::database::connection as database;
+use crate::server::database::models::groups::GroupScope;
+use crate::server::database::models::{contact_numbers, contacts};
+use crate::server::database::service::audit_log;
+use crate::server::database::service::contacts as contacts_service;
+use crate::server::ldap::connect as ldap;
+use crate::server::{csv, vcard};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Database error: {0}")]
+    Database(#[from] database::Error),
+    #[error("Diesel error: {0}")]
+    Diesel(#[from] diesel::result::Error),
+    #[error("Contacts error: {0}")]
+    Contacts(#[from] contacts_service::Error),
+    #[error("vCard error: {0}")]
+    VCard(#[from] vcard::Error),
+    #[error("Audit log error: {0}")]
+    AuditLog(#[from] audit_log::Error),
+    #[error("Invalid search: {0}")]
+    InvalidQuery(#[from] SearchQueryError),
+}
+
+/// Strip the punctuation phones and address books add to numbers, so
+/// "+61 (3) 9000-0000" matches "+61390000000".
+pub fn normalize_phone_number(phone_number: &str) -> String {
+    phone_number
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '(' | ')' | '.' | '\t'))
+        .collect()
+}
+
+/// The distinct, normalised numbers of a card with their labels, primary
+/// first.
+pub fn card_numbers(card: &vcard::VCard) -> Vec<(String, Option<String>)> {
+    let mut numbers: Vec<(String, Option<String>)> = vec![];
+    for phone in &card.phone_numbers {
+        let number = normalize_phone_number(&phone.number);
+        if !number.is_empty() && !numbers.iter().any(|(n, _)| *n == number) {
+            numbers.push((number, phone.label.clone()));
+        }
+    }
+    numbers
+}
+
+/// Load every contact matching the search and visible within `scope`, with
+/// its additional numbers.
+async fn get_contacts_with_numbers(
+    conn: &mut database::DatabaseConnection,
+    scope: &GroupScope,
+    query: &SearchQuery,
+    tag: Option<&str>,
+) -> Result<Vec<(models::Contact, Vec<contact_numbers::ContactNumber>)>, Error> {
+    let terms = query.terms()?;
+    let rows = contacts::search_contacts_paginated(
+        conn,
+        scope,
+        &terms,
+        tag,
+        models::ContactSort::Name,
+        None,
+        i64::MAX,
+    )
+    .await?;
+    let contacts = contacts::into_models(conn, rows).await?;
+
+    let ids: Vec<i64> = contacts.iter().map(|c| c.id.as_inner()).collect();
+    let mut numbers = contact_numbers::get_numbers_for_contacts(conn, &ids).await?;
+
+    let contacts = contacts
+        .into_iter()
+        .map(|contact| {
+            let numbers = numbers.remove(&contact.id.as_inner()).unwrap_or_default();
+            (contact, numbers)
+        })
+        .collect();
+
+    Ok(contacts)
+}
+
+/// Export contacts matching the search as vCard 4.0.
+pub async fn export_vcards(
+    conn: &mut database::DatabaseConnection,
+    scope: &GroupScope,
+    query: SearchQuery,
+    tag: Option<String>,
+) -> Result<String, Error> {
+    let contacts = get_contacts_with_numbers(conn, scope, &query, tag.as_deref()).await?;
+
+    let cards: Vec<vcard::VCard> = contacts
+        .into_iter()
+        .map(|(contact, numbers)| {
+            let primary = vcard::VCardPhone {
+                number: contact.phone_number.clone(),
+                label: None,
+            };
+            let extras = numbers.into_iter().map(|n| vcard::VCardPhone {
+                number: n.phone_number,
+                label: n.label,
+            });
+            vcard::VCard {
+                categories: contact.tag_names(),
+                name: contact.name,
+                phone_numbers: std::iter::once(primary).chain(extras).collect(),
+                note: contact.comments,
+                action: Some(contact.action),
+            }
+        })
+        .collect();
+
+    Ok(vcard::encode(&cards))
+}
+
+/// Import a vCard file, creating or updating contacts by phone number.
+///
+/// Existing data wins: blank fields are filled in from the card, but a name,
+/// note or action that differs from what is stored is reported as a conflict
+/// and left alone.  Missing numbers and tags are added.  Cards whose numbers
+/// belong to more than one contact, or to a contact outside `scope`, are
+/// skipped.  New contacts go in the default group of `scope`.  Everything
+/// happens in one transaction, so a database or LDAP failure imports nothing.
+pub async fn import_vcards(
+    conn: &mut database::DatabaseConnection,
+    scope: &GroupScope,
+    base_dn: &str,
+    ldap: &mut ldap::LdapConnection,
+    user_id: Option<UserId>,
+    content: String,
+) -> Result<ImportReport, Error> {
+    let cards = vcard::decode(&content)?;
+    let scope = scope.clone();
+    let group_id = scope.default_group().map(GroupId::new);
+    let base_dn = base_dn.to_string();
+    let ldap = ldap.clone();
+
+    conn.transaction::<_, Error, _>(async move |conn| {
+        let mut ldap = ldap;
+        let mut report = ImportReport::default();
+
+        for card in cards {
+            let title = card.as_title();
+
+            let numbers = card_numbers(&card);
+
+            if numbers.is_empty() {
+                report.conflict(title, "No phone numbers");
+                continue;
+            }
+
+            let action = card.action.clone();
+            if let Some(action) = &action
+                && !ACTIONS.contains(&action.as_str())
+            {
+                report.conflict(title, format!("Unknown action {action}"));
+                continue;
+            }
+
+            let tag_names = parse_tag_names(&card.categories.join(","));
+
+            let mut matched: Vec<models::Contact> = vec![];
+            for (number, _) in &numbers {
+                if let Some(contact) =
+                    contacts_service::get_contact_by_phone_number(conn, number).await?
+                    && !matched.iter().any(|c| c.id == contact.id)
+                {
+                    matched.push(contact);
+                }
+            }
+
+            if matched
+                .iter()
+                .any(|c| !scope.allows(c.group_id.map(|id| id.as_inner())))
+            {
+                report.conflict(title, "Numbers belong to a contact outside your groups");
+                continue;
+            }
+
+            match matched.as_slice() {
+                [] => {
+                    let (phone_number, _) = numbers[0].clone();
+                    let new_contact = models::NewContact {
+                        phone_number,
+                        name: card.name.clone(),
+                        action: action.unwrap_or_else(|| DEFAULT_ACTION.to_string()),
+                        comments: card.note.clone(),
+                        tags: vec![],
+                        temporary_action: None,
+                        group_id,
+                    };
+                    let contact = contacts::create_contact(
+                        conn,
+                        contacts::NewContact::from_front_end(&new_contact),
+                    )
+                    .await?;
+
+                    contacts_service::set_contact_tags(conn, contact.id, &tag_names).await?;
+
+                    for (number, label) in numbers.into_iter().skip(1) {
+                        let new_number = models::NewContactNumber {
+                            contact_id: models::ContactId::new(contact.id),
+                            phone_number: number,
+                            label,
+                        };
+                        contact_numbers::create_contact_number(
+                            conn,
+                            contact_numbers::NewContactNumber::from_front_end(&new_number),
+                        )
+                        .await?;
+                    }
+
+                    let model_contact = contacts_service::get_contact_by_id(
+                        conn,
+                        models::ContactId::new(contact.id),
+                    )
+                    .await?
+                    .ok_or(diesel::result::Error::NotFound)?;
+
+                    contacts_service::audit_contact(
+                        conn,
+                        user_id,
+                        contact.id,
+                        AuditOperation::Create,
+                        None,
+                        Some(&model_contact),
+                    )
+                    .await?;
+
+                    contacts_service::sync_ldap_contact(
+                        conn,
+                        &model_contact.phone_number,
+                        &model_contact,
+                        &base_dn,
+                        &mut ldap,
+                    )
+                    .await?;
+
+                    report.created += 1;
+                }
+                [contact] => {
+                    let contact_id = contact.id.as_inner();
+                    let mut changed = false;
+
+                    let mut updates = contacts::ChangeContact {
+                        phone_number: None,
+                        name: None,
+                        action: None,
+                        comments: None,
+                        inserted_at: None,
+                        updated_at: Some(chrono::Utc::now()),
+                        archived_at: None,
+                        temporary_action: None,
+                        temporary_action_expires_at: None,
+                        group_id: None,
+                    };
+
+                    match (&contact.name, &card.name) {
+                        (None, Some(name)) => updates.name = Some(Some(name.clone())),
+                        (Some(old), Some(new)) if old != new => {
+                            report.conflict(&title, format!("Name differs from existing {old}"));
+                        }
+                        _ => {}
+                    }
+
+                    match (&contact.comments, &card.note) {
+                        (None, Some(note)) => updates.comments = Some(Some(note.clone())),
+                        (Some(old), Some(new)) if old != new => {
+                            report.conflict(&title, "Note differs from existing comments");
+                        }
+                        _ => {}
+                    }
+
+                    if let Some(action) = &action
+                        && *action != contact.action
+                    {
+                        report.conflict(
+                            &title,
+                            format!("Action {action} differs from existing {}", contact.action),
+                        );
+                    }
+
+                    if updates.name.is_some() || updates.comments.is_some() {
+                        contacts::update_contact(conn, contact_id, updates).await?;
+                        changed = true;
+                    }
+
+                    let existing_tags = contact.tag_names();
+                    if tag_names.iter().any(|t| !existing_tags.contains(t)) {
+                        let mut all_tags = existing_tags;
+                        all_tags.extend(tag_names.iter().cloned());
+                        let all_tags = parse_tag_names(&all_tags.join(","));
+                        contacts_service::set_contact_tags(conn, contact_id, &all_tags).await?;
+                        changed = true;
+                    }
+
+                    for (number, label) in numbers {
+                        if contacts::get_contact_by_phone_number(conn, &number)
+                            .await?
+                            .is_some()
+                        {
+                            continue;
+                        }
+                        let new_number = models::NewContactNumber {
+                            contact_id: contact.id,
+                            phone_number: number,
+                            label,
+                        };
+                        contact_numbers::create_contact_number(
+                            conn,
+                            contact_numbers::NewContactNumber::from_front_end(&new_number),
+                        )
+                        .await?;
+                        changed = true;
+                    }
+
+                    if !changed {
+                        report.unchanged += 1;
+                        continue;
+                    }
+
+                    let model_contact = contacts_service::get_contact_by_id(conn, contact.id)
+                        .await?
+                        .ok_or(diesel::result::Error::NotFound)?;
+
+                    contacts_service::audit_contact(
+                        conn,
+                        user_id,
+                        contact.id.as_inner(),
+                        AuditOperation::Update,
+                        Some(contact),
+                        Some(&model_contact),
+                    )
+                    .await?;
+
+                    contacts_service::sync_ldap_contact(
+                        conn,
+                        &contact.phone_number,
+                        &model_contact,
+                        &base_dn,
+                        &mut ldap,
+                    )
+                    .await?;
+
+                    report.updated += 1;
+                }
+                _ => {
+                    let titles: Vec<String> = matched.iter().map(|c| c.as_title()).collect();
+                    report.conflict(
+                        title,
+                        format!("Numbers belong to several contacts: {}", titles.join(", ")),
+                    );
+                }
+            }
+        }
+
+        Ok(report)
+    })
+    .await
+}
+
+/// Export contacts matching the search as CSV, one row per contact.
+pub async fn export_csv(
+    conn: &mut database::DatabaseConnection,
+    scope: &GroupScope,
+    query: SearchQuery,
+    tag: Option<String>,
+) -> Result<String, Error> {
+    let terms = query.terms()?;
+    let rows = contacts::search_contacts_paginated(
+        conn,
+        scope,
+        &terms,
+        tag.as_deref(),
+        models::ContactSort::Name,
+        None,
+        i64::MAX,
+    )
+    .await?;
+
+    let records = rows.into_iter().map(|(contact, count)| {
+        vec![
+            contact.phone_number,
+            contact.name.unwrap_or_default(),
+            contact.action,
+            contact.comments.unwrap_or_default(),
+            count.to_string(),
+            contact.updated_at.to_rfc3339(),
+        ]
+    });
+
+    Ok(csv::encode(
+        std::iter::once(CONTACT_CSV_COLUMNS.map(String::from).to_vec()).chain(records),
+    ))
+}
+
+/// Upsert validated CSV rows by phone number.
+///
+/// Unlike vCard import the file is authoritative: name, action and comments
+/// replace what is stored.  Rows for contacts outside `scope` are skipped,
+/// and new contacts go in the default group of `scope`.  With `dry_run`
+/// nothing is written and the report says what would have happened.
+pub async fn import_contact_rows(
+    conn: &mut database::DatabaseConnection,
+    scope: &GroupScope,
+    base_dn: &str,
+    ldap: &mut ldap::LdapConnection,
+    user_id: Option<UserId>,
+    rows: Vec<ContactRow>,
+    dry_run: bool,
+) -> Result<ImportReport, Error> {
+    let scope = scope.clone();
+    let group_id = scope.default_group().map(GroupId::new);
+    let base_dn = base_dn.to_string();
+    let ldap = ldap.clone();
+
+    conn.transaction::<_, Error, _>(async move |conn| {
+        let mut ldap = ldap;
+        let mut report = ImportReport {
+            dry_run,
+            ..ImportReport::default()
+        };
+
+        for row in rows {
+            let existing =
+                contacts_service::get_contact_by_phone_number(conn, &row.phone_number).await?;
+
+            match existing {
+                Some(contact) if !scope.allows(contact.group_id.map(|id| id.as_inner())) => {
+                    report.conflict(
+                        format!("Line {}", row.line),
+                        "Phone number belongs to a contact outside your groups",
+                    );
+                }
+                None => {
+                    report.created += 1;
+                    if dry_run {
+                        continue;
+                    }
+
+                    let new_contact = models::NewContact {
+                        phone_number: row.phone_number,
+                        name: row.name,
+                        action: row.action,
+                        comments: row.comments,
+                        tags: vec![],
+                        temporary_action: None,
+                        group_id,
+                    };
+                    let contact = contacts::create_contact(
+                        conn,
+                        contacts::NewContact::from_front_end(&new_contact),
+                    )
+                    .await?;
+
+                    let model_contact = contacts_service::get_contact_by_id(
+                        conn,
+                        models::ContactId::new(contact.id),
+                    )
+                    .await?
+                    .ok_or(diesel::result::Error::NotFound)?;
+
+                    contacts_service::audit_contact(
+                        conn,
+                        user_id,
+                        contact.id,
+                        AuditOperation::Create,
+                        None,
+                        Some(&model_contact),
+                    )
+                    .await?;
+
+                    contacts_service::sync_ldap_contact(
+                        conn,
+                        &model_contact.phone_number,
+                        &model_contact,
+                        &base_dn,
+                        &mut ldap,
+                    )
+                    .await?;
+                }
+                Some(contact)
+                    if contact.name == row.name
+                        && contact.action == row.action
+                        && contact.comments == row.comments =>
+                {
+                    report.unchanged += 1;
+                }
+                Some(contact) => {
+                    report.updated += 1;
+                    if dry_run {
+                        continue;
+                    }
+
+                    let updates = contacts::ChangeContact {
+                        phone_number: None,
+                        name: Some(row.name),
+                        action: Some(row.action),
+                        comments: Some(row.comments),
+                        inserted_at: None,
+                        updated_at: Some(chrono::Utc::now()),
+                        archived_at: None,
+                        temporary_action: None,
+                        temporary_action_expires_at: None,
+                        group_id: None,
+                    };
+                    contacts::update_contact(conn, contact.id.as_inner(), updates).await?;
+
+                    let model_contact = contacts_service::get_contact_by_id(conn, contact.id)
+                        .await?
+                        .ok_or(diesel::result::Error::NotFound)?;
+
+                    contacts_service::audit_contact(
+                        conn,
+                        user_id,
+                        contact.id.as_inner(),
+                        AuditOperation::Update,
+                        Some(&contact),
+                        Some(&model_contact),
+                    )
+                    .await?;
+
+                    contacts_service::sync_ldap_contact(
+                        conn,
+                        &contact.phone_number,
+                        &model_contact,
+                        &base_dn,
+                        &mut ldap,
+                    )
+                    .await?;
+                }
+            }
+        }
+
+        Ok(report)
+    })
+    .await
+}
@@ -1,4 +1,8 @@
+pub mod audit_log;
 pub mod contacts;
 pub mod defaults;
+pub mod groups;
+pub mod imports;
 pub mod phone_calls;
+pub mod tags;
 pub mod users;
@@ -1,10 +1,23 @@
+use chrono::{DateTime, Datelike, Days, FixedOffset, NaiveDate, NaiveTime, Utc};
 use diesel_async::AsyncConnection;
+use futures::Stream;
+use serde::Serialize;
 use thiserror::Error;
 
+use crate::models::audit_log::{AuditEntity, AuditOperation};
+use crate::models::common::MaybeSet;
 use crate::models::contacts as contact_models;
+use crate::models::dashboard;
 use crate::models::phone_calls as models;
+use crate::models::search::{SearchQuery, SearchQueryError, SearchTerm};
+use crate::models::users::UserId;
+use crate::server::csv;
 use crate::server::database::connection as database;
+use crate::server::database::models::contacts::{self, Contact};
+use crate::server::database::models::groups::GroupScope;
 use crate::server::database::models::phone_calls;
+use crate::server::database::models::tags::get_tags_for_contacts;
+use crate::server::database::service::audit_log;
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -12,64 +25,160 @@ pub enum Error {
     DatabaseError(#[from] database::Error),
     #[error("Diesel error: {0}")]
     DieselError(#[from] diesel::result::Error),
+    #[error("Invalid search: {0}")]
+    InvalidQuery(#[from] SearchQueryError),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Phone call not found")]
+    NotFound,
+    #[error("No contact has the phone number {0}")]
+    ContactNotFound(String),
+    #[error("Audit log error: {0}")]
+    AuditLog(#[from] audit_log::Error),
+}
+
+/// Convert rows into front end models, loading the tags of every contact
+/// in one query.
+async fn into_models(
+    conn: &mut database::DatabaseConnection,
+    rows: Vec<(phone_calls::PhoneCall, Contact, i64)>,
+) -> Result<Vec<(models::PhoneCall, contact_models::Contact)>, diesel::result::Error> {
+    let ids: Vec<i64> = rows.iter().map(|(_, c, _)| c.id).collect();
+    let tags = get_tags_for_contacts(conn, &ids).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(phone_call, contact, count)| {
+            let contact_tags = tags.get(&contact.id).cloned().unwrap_or_default();
+            (phone_call.into(), contact.into_model(count, contact_tags))
+        })
+        .collect())
 }
 
 pub async fn search_phone_calls(
     conn: &mut database::DatabaseConnection,
+    scope: &GroupScope,
     query: String,
 ) -> Result<Vec<(models::PhoneCall, contact_models::Contact)>, Error> {
-    phone_calls::search_phone_calls(conn, &query)
+    let rows = phone_calls::search_phone_calls(conn, scope, &query)
+        .await
+        .map_err(database::Error::from)?;
+
+    into_models(conn, rows)
         .await
-        .map(|x| {
-            x.into_iter()
-                .map(|(phone_call, contact, count)| (phone_call.into(), contact.into_model(count)))
-                .collect()
-        })
         .map_err(database::Error::from)
         .map_err(Error::from)
 }
 
 pub async fn search_phone_calls_paginated(
     conn: &mut database::DatabaseConnection,
-    query: String,
+    scope: &GroupScope,
+    query: SearchQuery,
+    filter: models::PhoneCallFilter,
     before: Option<(chrono::DateTime<chrono::Utc>, models::PhoneCallId)>,
     page_size: i64,
 ) -> Result<Vec<(models::PhoneCall, contact_models::Contact)>, Error> {
+    let terms = query.terms()?;
     let before_raw = before.map(|(ts, id)| (ts, id.as_inner()));
-    phone_calls::search_phone_calls_paginated(conn, &query, before_raw, page_size)
+    let rows = phone_calls::search_phone_calls_paginated(
+        conn, scope, &terms, &filter, before_raw, page_size,
+    )
+    .await
+    .map_err(database::Error::from)?;
+
+    into_models(conn, rows)
         .await
-        .map(|x| {
-            x.into_iter()
-                .map(|(phone_call, contact, count)| (phone_call.into(), contact.into_model(count)))
-                .collect()
-        })
         .map_err(database::Error::from)
         .map_err(Error::from)
 }
 
 pub async fn get_phone_calls_for_contact(
     conn: &mut database::DatabaseConnection,
+    scope: &GroupScope,
     contact_id: contact_models::ContactId,
+    filter: models::PhoneCallFilter,
     before: Option<(chrono::DateTime<chrono::Utc>, models::PhoneCallId)>,
     page_size: i64,
 ) -> Result<Vec<models::PhoneCall>, Error> {
     let before_raw = before.map(|(ts, id)| (ts, id.as_inner()));
-    phone_calls::get_phone_calls_for_contact(conn, contact_id.as_inner(), before_raw, page_size)
-        .await
-        .map(|rows| rows.into_iter().map(|p| p.into()).collect())
-        .map_err(database::Error::from)
-        .map_err(Error::from)
+    phone_calls::get_phone_calls_for_contact(
+        conn,
+        scope,
+        contact_id.as_inner(),
+        &filter,
+        before_raw,
+        page_size,
+    )
+    .await
+    .map(|rows| rows.into_iter().map(|p| p.into()).collect())
+    .map_err(database::Error::from)
+    .map_err(Error::from)
 }
 
+/// `None` if the call does not exist or its contact is not visible within
+/// `scope`.
 pub async fn get_phone_call_by_id(
     conn: &mut database::DatabaseConnection,
+    scope: &GroupScope,
     id: models::PhoneCallId,
 ) -> Result<Option<models::PhoneCall>, Error> {
-    phone_calls::get_phone_call_by_id(conn, id.as_inner())
-        .await
-        .map(|x| x.map(|y| y.into()))
-        .map_err(database::Error::from)
-        .map_err(Error::from)
+    let Some(phone_call) = phone_calls::get_phone_call_by_id(conn, id.as_inner()).await? else {
+        return Ok(None);
+    };
+
+    let groups = contacts::get_contact_groups(conn, &[phone_call.contact_id]).await?;
+    let visible = groups.iter().any(|(_, group_id)| scope.allows(*group_id));
+
+    Ok(visible.then(|| phone_call.into()))
+}
+
+/// Number of calls from the same number shown on each side of a call.
+const NEIGHBOURING_CALLS: i64 = 5;
+
+/// A call with its contact and the calls from the same number around it.
+/// `None` if the call does not exist or its contact is not visible within
+/// `scope`.
+pub async fn get_phone_call_record(
+    conn: &mut database::DatabaseConnection,
+    scope: &GroupScope,
+    id: models::PhoneCallId,
+) -> Result<Option<models::PhoneCallRecord>, Error> {
+    let Some(phone_call) = phone_calls::get_phone_call_by_id(conn, id.as_inner()).await? else {
+        return Ok(None);
+    };
+
+    let Some(row) = contacts::get_contact_by_id(conn, phone_call.contact_id).await? else {
+        return Ok(None);
+    };
+    if !scope.allows(row.0.group_id) {
+        return Ok(None);
+    }
+    let Some(contact) = contacts::into_models(conn, vec![row]).await?.pop() else {
+        return Ok(None);
+    };
+
+    let (earlier, later) = match &phone_call.source_number {
+        Some(source_number) => {
+            phone_calls::get_neighbouring_phone_calls(
+                conn,
+                scope,
+                source_number,
+                phone_call.inserted_at,
+                phone_call.id,
+                NEIGHBOURING_CALLS,
+            )
+            .await?
+        }
+        // Anonymised calls cannot be matched to others.
+        None => (vec![], vec![]),
+    };
+
+    Ok(Some(models::PhoneCallRecord {
+        phone_call: phone_call.into(),
+        contact,
+        earlier: earlier.into_iter().map(models::PhoneCall::from).collect(),
+        later: later.into_iter().map(models::PhoneCall::from).collect(),
+    }))
 }
 
 pub async fn create_phone_call(
@@ -84,7 +193,380 @@ pub async fn create_phone_call(
             .map(|x| x.into())
             .map_err(Error::from)?;
 
+        contacts::record_phone_call(
+            conn,
+            phone_call.contact_id.as_inner(),
+            phone_call.inserted_at,
+        )
+        .await
+        .map_err(Error::from)?;
+
         Ok(phone_call)
     })
     .await
 }
+
+/// Load the current state of a call inside a transaction, for the audit log.
+async fn load_phone_call(
+    conn: &mut database::DatabaseConnection,
+    id: i64,
+) -> Result<models::PhoneCall, Error> {
+    phone_calls::get_phone_call_by_id(conn, id)
+        .await?
+        .map(models::PhoneCall::from)
+        .ok_or(Error::NotFound)
+}
+
+/// Move a call onto the contact that owns `phone_number`, e.g. when a number
+/// was mistyped during an import.  The call counts of both contacts are
+/// updated.
+pub async fn move_phone_call(
+    conn: &mut database::DatabaseConnection,
+    user_id: Option<UserId>,
+    id: models::PhoneCallId,
+    phone_number: String,
+) -> Result<models::PhoneCall, Error> {
+    let id = id.as_inner();
+
+    conn.transaction::<_, Error, _>(async move |conn| {
+        let before = load_phone_call(conn, id).await?;
+
+        let (contact, _count) = contacts::get_contact_by_phone_number(conn, &phone_number)
+            .await?
+            .ok_or(Error::ContactNotFound(phone_number))?;
+
+        let change = models::ChangePhoneCall {
+            action: MaybeSet::NoChange,
+            contact_id: MaybeSet::Set(contact_models::ContactId::new(contact.id)),
+            destination_number: MaybeSet::NoChange,
+            source_number: MaybeSet::NoChange,
+            contact_name: MaybeSet::Set(contact.name),
+        };
+        let after: models::PhoneCall = phone_calls::update_phone_call(
+            conn,
+            id,
+            &phone_calls::ChangePhoneCall::from_front_end(&change),
+        )
+        .await?
+        .into();
+
+        contacts::refresh_call_statistics(
+            conn,
+            &[before.contact_id.as_inner(), after.contact_id.as_inner()],
+        )
+        .await?;
+
+        audit_log::log(
+            conn,
+            user_id,
+            AuditEntity::PhoneCall,
+            id,
+            AuditOperation::Update,
+            Some(&before),
+            Some(&after),
+        )
+        .await?;
+
+        Ok(after)
+    })
+    .await
+}
+
+pub async fn delete_phone_call(
+    conn: &mut database::DatabaseConnection,
+    user_id: Option<UserId>,
+    id: models::PhoneCallId,
+) -> Result<(), Error> {
+    let id = id.as_inner();
+
+    conn.transaction::<_, Error, _>(async move |conn| {
+        let before = load_phone_call(conn, id).await?;
+
+        phone_calls::delete_phone_call(conn, id).await?;
+
+        contacts::refresh_call_statistics(conn, &[before.contact_id.as_inner()]).await?;
+
+        audit_log::log(
+            conn,
+            user_id,
+            AuditEntity::PhoneCall,
+            id,
+            AuditOperation::Delete,
+            Some(&before),
+            None,
+        )
+        .await?;
+
+        Ok(())
+    })
+    .await
+}
+
+/// What happens to calls older than the retention period.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum RetentionMode {
+    Delete,
+    /// Drop the numbers and contact name, keep the action and time so that
+    /// the call statistics stay the same.
+    Anonymise,
+}
+
+/// Delete or anonymise every call before `cutoff`.  With `dry_run` nothing
+/// is changed.  Returns the number of calls affected.
+pub async fn apply_call_retention(
+    conn: &mut database::DatabaseConnection,
+    cutoff: DateTime<Utc>,
+    mode: RetentionMode,
+    dry_run: bool,
+) -> Result<usize, Error> {
+    conn.transaction::<_, Error, _>(async move |conn| match (mode, dry_run) {
+        (RetentionMode::Delete, true) => {
+            let count = phone_calls::count_phone_calls_before(conn, cutoff).await?;
+            Ok(count as usize)
+        }
+        (RetentionMode::Delete, false) => {
+            let mut contact_ids = phone_calls::delete_phone_calls_before(conn, cutoff).await?;
+            let count = contact_ids.len();
+
+            contact_ids.sort_unstable();
+            contact_ids.dedup();
+            contacts::refresh_call_statistics(conn, &contact_ids).await?;
+
+            Ok(count)
+        }
+        (RetentionMode::Anonymise, true) => {
+            let count = phone_calls::count_identifiable_phone_calls_before(conn, cutoff).await?;
+            Ok(count as usize)
+        }
+        (RetentionMode::Anonymise, false) => {
+            let count = phone_calls::anonymise_phone_calls_before(conn, cutoff).await?;
+            Ok(count)
+        }
+    })
+    .await
+}
+
+async fn count_by_action(
+    conn: &mut database::DatabaseConnection,
+    scope: &GroupScope,
+    since: DateTime<Utc>,
+) -> Result<Vec<dashboard::ActionCount>, Error> {
+    let rows = phone_calls::count_phone_calls_by_action(conn, scope, since).await?;
+    Ok(rows
+        .into_iter()
+        .map(|(action, count)| dashboard::ActionCount { action, count })
+        .collect())
+}
+
+/// Calls per action for the current day, week and month, per day for the
+/// last 90 days, and the busiest and newest callers.  Days start at local
+/// midnight for `offset`, weeks on Monday.  Only calls and contacts visible
+/// within `scope` are included.
+pub async fn get_dashboard(
+    conn: &mut database::DatabaseConnection,
+    scope: &GroupScope,
+    now: DateTime<Utc>,
+    offset: FixedOffset,
+) -> Result<dashboard::CallDashboard, Error> {
+    let today = now.with_timezone(&offset).date_naive();
+    let start_of = |date: NaiveDate| (date.and_time(NaiveTime::MIN) - offset).and_utc();
+
+    let week_start = today - Days::new(u64::from(today.weekday().num_days_from_monday()));
+    let month_start = today.with_day(1).unwrap_or(today);
+    let first_day = today - Days::new(89);
+
+    let today_counts = count_by_action(conn, scope, start_of(today)).await?;
+    let week_counts = count_by_action(conn, scope, start_of(week_start)).await?;
+    let month_counts = count_by_action(conn, scope, start_of(month_start)).await?;
+
+    let daily = phone_calls::count_phone_calls_by_day(
+        conn,
+        scope,
+        start_of(first_day),
+        offset.local_minus_utc(),
+    )
+    .await?
+    .into_iter()
+    .map(|row| dashboard::DailyActionCount {
+        date: row.day,
+        action: row.action,
+        count: row.count,
+    })
+    .collect();
+
+    let top = phone_calls::get_top_callers(conn, scope, now - Days::new(30), 10).await?;
+    let ids: Vec<i64> = top.iter().map(|(id, _)| *id).collect();
+    let rows = contacts::get_contacts_by_ids(conn, &ids).await?;
+    let mut top_contacts = contacts::into_models(conn, rows).await?;
+    let top_callers = top
+        .into_iter()
+        .filter_map(|(id, count)| {
+            let index = top_contacts.iter().position(|c| c.id.as_inner() == id)?;
+            Some((top_contacts.swap_remove(index), count))
+        })
+        .collect();
+
+    let rows = contacts::get_new_contacts(conn, scope, now - Days::new(7), 10).await?;
+    let new_numbers = contacts::into_models(conn, rows).await?;
+
+    Ok(dashboard::CallDashboard {
+        today: today_counts,
+        this_week: week_counts,
+        this_month: month_counts,
+        daily,
+        first_day,
+        last_day: today,
+        top_callers,
+        new_numbers,
+    })
+}
+
+/// Calls per action in each local hour of the week for `offset`, for one
+/// contact or for every call of the contacts visible within `scope`.
+pub async fn get_call_heatmap(
+    conn: &mut database::DatabaseConnection,
+    scope: &GroupScope,
+    contact_id: Option<contact_models::ContactId>,
+    offset: FixedOffset,
+) -> Result<dashboard::CallHeatmap, Error> {
+    let rows = phone_calls::count_phone_calls_by_hour(
+        conn,
+        scope,
+        contact_id.map(|id| id.as_inner()),
+        offset.local_minus_utc(),
+    )
+    .await?;
+
+    let hourly = rows
+        .into_iter()
+        .filter_map(|row| {
+            Some(dashboard::HourlyActionCount {
+                weekday: u32::try_from(row.weekday).ok()?,
+                hour: u32::try_from(row.hour).ok()?,
+                action: row.action,
+                count: row.count,
+            })
+        })
+        .collect();
+
+    Ok(dashboard::CallHeatmap { hourly })
+}
+
+/// Number of calls read from the database at a time while exporting.
+const EXPORT_PAGE_SIZE: i64 = 500;
+
+/// One line of the NDJSON export, keyed as [`models::PHONE_CALL_CSV_COLUMNS`].
+#[derive(Serialize)]
+struct ExportedCall<'a> {
+    id: i64,
+    inserted_at: DateTime<Utc>,
+    contact_id: i64,
+    contact_name: Option<&'a str>,
+    action: &'a str,
+    source_number: Option<&'a str>,
+    destination_number: Option<&'a str>,
+}
+
+fn encode_calls(
+    format: models::PhoneCallExportFormat,
+    calls: &[phone_calls::PhoneCall],
+) -> Result<String, Error> {
+    match format {
+        models::PhoneCallExportFormat::Csv => Ok(csv::encode(calls.iter().map(|call| {
+            vec![
+                call.id.to_string(),
+                call.inserted_at.to_rfc3339(),
+                call.contact_id.to_string(),
+                call.contact_name.clone().unwrap_or_default(),
+                call.action.clone(),
+                call.source_number.clone().unwrap_or_default(),
+                call.destination_number.clone().unwrap_or_default(),
+            ]
+        }))),
+        models::PhoneCallExportFormat::Ndjson => {
+            let mut out = String::new();
+            for call in calls {
+                let line = ExportedCall {
+                    id: call.id,
+                    inserted_at: call.inserted_at,
+                    contact_id: call.contact_id,
+                    contact_name: call.contact_name.as_deref(),
+                    action: &call.action,
+                    source_number: call.source_number.as_deref(),
+                    destination_number: call.destination_number.as_deref(),
+                };
+                out.push_str(&serde_json::to_string(&line)?);
+                out.push('\n');
+            }
+            Ok(out)
+        }
+    }
+}
+
+struct ExportState {
+    conn: database::DatabaseConnection,
+    scope: GroupScope,
+    terms: Vec<SearchTerm>,
+    filter: models::PhoneCallFilter,
+    format: models::PhoneCallExportFormat,
+    before: Option<(DateTime<Utc>, i64)>,
+    started: bool,
+    finished: bool,
+}
+
+/// The next chunk of the export, `None` after the last page.
+async fn export_page(mut state: ExportState) -> Result<Option<(String, ExportState)>, Error> {
+    if state.finished {
+        return Ok(None);
+    }
+
+    let mut out = String::new();
+    if !state.started && state.format == models::PhoneCallExportFormat::Csv {
+        out.push_str(&csv::encode([models::PHONE_CALL_CSV_COLUMNS]));
+    }
+    state.started = true;
+
+    let rows = phone_calls::search_phone_calls_paginated(
+        &mut state.conn,
+        &state.scope,
+        &state.terms,
+        &state.filter,
+        state.before,
+        EXPORT_PAGE_SIZE,
+    )
+    .await
+    .map_err(database::Error::from)?;
+
+    state.finished = rows.len() < EXPORT_PAGE_SIZE as usize;
+    state.before = rows.last().map(|(call, _, _)| (call.inserted_at, call.id));
+
+    let calls: Vec<phone_calls::PhoneCall> = rows.into_iter().map(|(call, _, _)| call).collect();
+    out.push_str(&encode_calls(state.format, &calls)?);
+
+    Ok(Some((out, state)))
+}
+
+/// The calls matching `query` and `filter`, newest first, as chunks of CSV
+/// or NDJSON.  Calls are read a page at a time along the same
+/// `(inserted_at, id)` cursor as the call log, so the whole log is never held
+/// in memory.  Only calls of contacts visible within `scope` are exported.
+pub fn export_phone_calls(
+    conn: database::DatabaseConnection,
+    scope: GroupScope,
+    query: SearchQuery,
+    filter: models::PhoneCallFilter,
+    format: models::PhoneCallExportFormat,
+) -> Result<impl Stream<Item = Result<String, Error>> + Send + 'static, Error> {
+    let state = ExportState {
+        conn,
+        scope,
+        terms: query.terms()?,
+        filter,
+        format,
+        before: None,
+        started: false,
+        finished: false,
+    };
+
+    Ok(futures::stream::try_unfold(state, export_page))
+}
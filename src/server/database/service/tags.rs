@@ -0,0 +1,132 @@
+use diesel_async::AsyncConnection;
+use thiserror::Error;
+
+use crate::models::tags as models;
+use crate::server::database::connection as database;
+use crate::server::database::models::{contacts, tags};
+use crate::server::database::service::contacts::sync_ldap_contact;
+use crate::server::ldap::connect as ldap;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Database error: {0}")]
+    Database(#[from] database::Error),
+    #[error("Diesel error: {0}")]
+    Diesel(#[from] diesel::result::Error),
+    #[error("Contacts error: {0}")]
+    Contacts(#[from] crate::server::database::service::contacts::Error),
+}
+
+pub async fn get_all_tags(
+    conn: &mut database::DatabaseConnection,
+) -> Result<Vec<models::Tag>, Error> {
+    tags::get_all_tags(conn)
+        .await
+        .map(|x| x.into_iter().map(|y| y.into()).collect())
+        .map_err(database::Error::from)
+        .map_err(Error::from)
+}
+
+pub async fn get_tag_by_id(
+    conn: &mut database::DatabaseConnection,
+    id: models::TagId,
+) -> Result<Option<models::Tag>, Error> {
+    tags::get_tag_by_id(conn, id.as_inner())
+        .await
+        .map(|x| x.map(|y| y.into()))
+        .map_err(database::Error::from)
+        .map_err(Error::from)
+}
+
+/// Rewrite the LDAP entries of every contact with the tag, as a change of the
+/// tag's action may change whether they are published.
+async fn sync_ldap_tagged_contacts(
+    conn: &mut database::DatabaseConnection,
+    contact_ids: &[i64],
+    base_dn: &str,
+    ldap: &mut ldap::LdapConnection,
+) -> Result<(), Error> {
+    let rows = contacts::get_contacts_by_ids(conn, contact_ids).await?;
+    let contacts = contacts::into_models(conn, rows).await?;
+
+    for contact in contacts {
+        sync_ldap_contact(conn, &contact.phone_number, &contact, base_dn, ldap).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn create_tag(
+    conn: &mut database::DatabaseConnection,
+    tag: models::NewTag,
+) -> Result<models::Tag, Error> {
+    let new_tag = tags::NewTag::from_front_end(&tag);
+
+    conn.transaction::<_, Error, _>(async move |conn| {
+        let tag: models::Tag = tags::create_tag(conn, new_tag)
+            .await
+            .map(|x| x.into())
+            .map_err(Error::from)?;
+
+        Ok(tag)
+    })
+    .await
+}
+
+pub async fn update_tag(
+    conn: &mut database::DatabaseConnection,
+    base_dn: &str,
+    ldap: &mut ldap::LdapConnection,
+    old_tag: models::Tag,
+    change_tag: models::ChangeTag,
+) -> Result<models::Tag, Error> {
+    let updates = tags::ChangeTag::from_front_end(&change_tag);
+    let old_tag_id = old_tag.id.as_inner();
+    let base_dn = base_dn.to_string();
+    let ldap = ldap.clone();
+
+    conn.transaction::<_, Error, _>(async move |conn| {
+        let mut ldap = ldap;
+
+        let tag: models::Tag = tags::update_tag(conn, old_tag_id, updates)
+            .await
+            .map(|x| x.into())
+            .map_err(Error::from)?;
+
+        if tag.action != old_tag.action {
+            let contact_ids = tags::get_contact_ids_for_tag(conn, old_tag_id).await?;
+            sync_ldap_tagged_contacts(conn, &contact_ids, &base_dn, &mut ldap).await?;
+        }
+
+        Ok(tag)
+    })
+    .await
+}
+
+pub async fn delete_tag(
+    conn: &mut database::DatabaseConnection,
+    base_dn: &str,
+    ldap: &mut ldap::LdapConnection,
+    old_tag: models::Tag,
+) -> Result<(), Error> {
+    let old_tag_id = old_tag.id.as_inner();
+    let base_dn = base_dn.to_string();
+    let ldap = ldap.clone();
+
+    conn.transaction::<_, Error, _>(async move |conn| {
+        let mut ldap = ldap;
+
+        let contact_ids = tags::get_contact_ids_for_tag(conn, old_tag_id).await?;
+
+        tags::delete_tag(conn, old_tag_id)
+            .await
+            .map_err(Error::from)?;
+
+        if old_tag.action.is_some() {
+            sync_ldap_tagged_contacts(conn, &contact_ids, &base_dn, &mut ldap).await?;
+        }
+
+        Ok(())
+    })
+    .await
+}
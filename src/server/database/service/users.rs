@@ -1,9 +1,11 @@
 use diesel_async::AsyncConnection;
 use thiserror::Error;
 
+use crate::models::audit_log::{AuditEntity, AuditOperation};
 use crate::models::users as models;
 use crate::server::database::connection as database;
 use crate::server::database::models::users;
+use crate::server::database::service::audit_log;
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -11,6 +13,8 @@ pub enum Error {
     DatabaseError(#[from] database::Error),
     #[error("Diesel error: {0}")]
     DieselError(#[from] diesel::result::Error),
+    #[error("Audit log error: {0}")]
+    AuditLog(#[from] audit_log::Error),
 }
 
 pub async fn get_user_by_id(
@@ -38,8 +42,31 @@ pub async fn get_users(
         .map_err(Error::from)
 }
 
+/// Record a change to a user in the audit log.
+async fn audit_user(
+    conn: &mut database::DatabaseConnection,
+    user_id: Option<models::UserId>,
+    id: i64,
+    operation: AuditOperation,
+    before: Option<&models::User>,
+    after: Option<&models::User>,
+) -> Result<(), Error> {
+    audit_log::log(
+        conn,
+        user_id,
+        AuditEntity::User,
+        id,
+        operation,
+        before,
+        after,
+    )
+    .await?;
+    Ok(())
+}
+
 pub async fn create_user(
     conn: &mut database::DatabaseConnection,
+    user_id: Option<models::UserId>,
     user: models::NewUser,
     hashed_password: &str,
 ) -> Result<models::User, Error> {
@@ -51,6 +78,16 @@ pub async fn create_user(
             .map(|x| x.into())
             .map_err(Error::from)?;
 
+        audit_user(
+            conn,
+            user_id,
+            user.id.as_inner(),
+            AuditOperation::Create,
+            None,
+            Some(&user),
+        )
+        .await?;
+
         Ok(user)
     })
     .await
@@ -58,6 +95,7 @@ pub async fn create_user(
 
 pub async fn update_user(
     conn: &mut database::DatabaseConnection,
+    user_id: Option<models::UserId>,
     old_user: models::User,
     change_user: models::ChangeUser,
     hashed_password: Option<String>,
@@ -66,11 +104,25 @@ pub async fn update_user(
     let old_user_id = old_user.id.as_inner();
 
     conn.transaction::<_, Error, _>(async move |conn| {
+        let before: Option<models::User> = users::get_user_by_id(conn, old_user_id)
+            .await?
+            .map(|x| x.into());
+
         let user: models::User = users::update_user(conn, old_user_id, updates)
             .await
             .map(|x| x.into())
             .map_err(Error::from)?;
 
+        audit_user(
+            conn,
+            user_id,
+            old_user_id,
+            AuditOperation::Update,
+            before.as_ref(),
+            Some(&user),
+        )
+        .await?;
+
         Ok(user)
     })
     .await
@@ -78,15 +130,30 @@ pub async fn update_user(
 
 pub async fn delete_user(
     conn: &mut database::DatabaseConnection,
+    user_id: Option<models::UserId>,
     old_user: models::User,
 ) -> Result<(), Error> {
     let old_user_id = old_user.id.as_inner();
 
     conn.transaction::<_, Error, _>(async move |conn| {
+        let before: Option<models::User> = users::get_user_by_id(conn, old_user_id)
+            .await?
+            .map(|x| x.into());
+
         crate::server::database::models::users::delete_user(conn, old_user_id)
             .await
             .map_err(Error::from)?;
 
+        audit_user(
+            conn,
+            user_id,
+            old_user_id,
+            AuditOperation::Delete,
+            before.as_ref(),
+            None,
+        )
+        .await?;
+
         Ok(())
     })
     .await
@@ -15,9 +15,9 @@ use tokio::sync::broadcast;
 use dioxus::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::models::contacts::{Contact, NewContact};
+use crate::models::contacts::{Contact, DEFAULT_ACTION, NewContact};
 use crate::models::defaults::DefaultList;
-use crate::models::phone_calls::{NewPhoneCall, PhoneCall};
+use crate::models::phone_calls::{DecisionSource, NewPhoneCall, PhoneCall};
 use crate::server::database::connection as database;
 use crate::server::database::service::contacts;
 use crate::server::database::service::defaults;
@@ -128,6 +128,36 @@ impl IntoResponse for Error {
     }
 }
 
+/// The contact created for a number no contact owns, including numbers of
+/// archived contacts, named and actioned by the first matching default.
+fn new_contact_for_call(
+    defaults: &DefaultList,
+    phone_number: &str,
+) -> (NewContact, DecisionSource) {
+    let default = defaults.search_phone_number(phone_number);
+
+    let decision_source = match default {
+        Some(_) => DecisionSource::Default,
+        None => DecisionSource::Fallback,
+    };
+    let name = default.and_then(|d| d.name.clone());
+    let action = default
+        .map(|d| d.action.clone())
+        .unwrap_or_else(|| DEFAULT_ACTION.to_string());
+
+    let contact = NewContact {
+        phone_number: phone_number.to_string(),
+        name,
+        action,
+        comments: None,
+        tags: vec![],
+        temporary_action: None,
+        group_id: None,
+    };
+
+    (contact, decision_source)
+}
+
 pub async fn post_handler(
     Extension(authentication): Extension<Arc<Authentication>>,
     Extension(db): Extension<database::DatabasePool>,
@@ -150,36 +180,34 @@ pub async fn post_handler(
     let (phone_call, contact) = conn
         .transaction::<_, Error, _>(async move |conn| {
             let mut ldap_conn = ldap_conn;
-            let contact = match contact {
-                Some(contact) => contact,
+            let (contact, decision_source) = match contact {
+                Some(contact) => {
+                    let decision_source = contact.decision_source_at(chrono::Utc::now());
+                    (contact, decision_source)
+                }
                 None => {
                     let defaults = defaults::get_all_defaults(conn)
                         .await
                         .map(DefaultList::new)?;
 
-                    let default = defaults.search_phone_number(&request.phone_number);
-
-                    let name = default.and_then(|d| d.name.clone());
-                    let action = default
-                        .map(|d| d.action.clone())
-                        .unwrap_or_else(|| "allow".to_string());
-
-                    let request = NewContact {
-                        phone_number: request.phone_number.clone(),
-                        name,
-                        action,
-                        comments: None,
-                    };
+                    let (new_contact, decision_source) =
+                        new_contact_for_call(&defaults, &request.phone_number);
 
-                    contacts::create_contact(conn, &base_dn, &mut ldap_conn, request).await?
+                    // Created by the system, not by a logged in user.
+                    let contact =
+                        contacts::create_contact(conn, &base_dn, &mut ldap_conn, None, new_contact)
+                            .await?;
+                    (contact, decision_source)
                 }
             };
 
             let new_phone_call = NewPhoneCall {
-                action: contact.action.clone(),
+                action: contact.effective_action().to_string(),
                 contact_id: contact.id,
                 destination_number: Some(request.destination_number.clone()),
                 source_number: request.phone_number.clone(),
+                contact_name: contact.name.clone(),
+                decision_source,
             };
 
             let phone_call = phone_calls::create_phone_call(conn, new_phone_call).await?;
@@ -198,3 +226,41 @@ pub async fn post_handler(
 
     Ok(Json(details))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::defaults::{Default, DefaultId};
+
+    fn default(regexp: &str, name: &str, action: &str) -> Default {
+        Default {
+            id: DefaultId::new(1),
+            order: None,
+            regexp: Some(regexp.to_string()),
+            name: Some(name.to_string()),
+            action: action.to_string(),
+            inserted_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            archived_at: None,
+            group_id: None,
+        }
+    }
+
+    #[test]
+    fn test_new_contact_for_call() {
+        let defaults = DefaultList::new(vec![default("^1300", "Business", "voicemail")]);
+
+        // E.g. an additional number of an archived contact, which no longer
+        // matches any contact.
+        let (contact, source) = new_contact_for_call(&defaults, "1300123456");
+        assert_eq!(contact.phone_number, "1300123456");
+        assert_eq!(contact.name.as_deref(), Some("Business"));
+        assert_eq!(contact.action, "voicemail");
+        assert_eq!(source, DecisionSource::Default);
+
+        let (contact, source) = new_contact_for_call(&defaults, "0412345678");
+        assert_eq!(contact.name, None);
+        assert_eq!(contact.action, "allow");
+        assert_eq!(source, DecisionSource::Fallback);
+    }
+}
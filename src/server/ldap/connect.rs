@@ -6,6 +6,7 @@ use std::time::Duration;
 use url::Url;
 
 use crate::server::ldap::manager::LdapManager;
+use crate::server::ldap::tls::{TlsConfig, TlsMode};
 
 #[derive(Clone)]
 pub struct LdapPool {
@@ -33,11 +34,17 @@ pub async fn connect_ldap() -> LdapPool {
     let ldap_base_dn = env::var("LDAP_BASE_DN").expect("LDAP_BASE_DN must be set");
     let ldap_username = env::var("LDAP_USERNAME").expect("LDAP_USERNAME must be set");
     let ldap_password = env::var("LDAP_PASSWORD").expect("LDAP_PASSWORD must be set");
-    let url = format!("ldap://{}:{}", ldap_server, ldap_port);
+    let tls = TlsConfig::get_from_env();
+    let url = format!("{}://{}:{}", tls.mode.scheme(), ldap_server, ldap_port);
     let url: Url = url.parse().expect("Invalid LDAP URL");
 
+    if tls.mode == TlsMode::None {
+        tracing::warn!("LDAP_TLS is none, the LDAP bind password is sent in cleartext");
+    }
+
     let manager = LdapManager {
         url: url.to_string(),
+        settings: tls.settings(),
         bind_dn: ldap_username.clone(),
         bind_pw: ldap_password.clone(),
     };
@@ -55,7 +55,6 @@ pub fn and(filters: &[String]) -> String {
 }
 
 /// Build a safe OR filter: (|(a=1)(b=2))
-#[allow(dead_code)]
 pub fn or(filters: &[String]) -> String {
     let inner = filters.join("");
     format!("(|{})", inner)
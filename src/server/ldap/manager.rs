@@ -1,8 +1,9 @@
 use bb8::ManageConnection;
-use ldap3::{LdapConnAsync, Scope};
+use ldap3::{LdapConnAsync, LdapConnSettings, Scope};
 
 pub struct LdapManager {
     pub url: String,
+    pub settings: LdapConnSettings,
     pub bind_dn: String,
     pub bind_pw: String,
 }
@@ -12,7 +13,8 @@ impl ManageConnection for LdapManager {
     type Error = ldap3::LdapError;
 
     async fn connect(&self) -> Result<Self::Connection, Self::Error> {
-        let (conn, mut ldap) = LdapConnAsync::new(&self.url).await?;
+        let (conn, mut ldap) =
+            LdapConnAsync::with_settings(self.settings.clone(), &self.url).await?;
         ldap3::drive!(conn);
 
         ldap.simple_bind(&self.bind_dn, &self.bind_pw)
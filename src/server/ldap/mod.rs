@@ -2,3 +2,4 @@ pub mod connect;
 mod filters;
 mod manager;
 pub mod query;
+mod tls;
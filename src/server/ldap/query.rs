@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use itertools::Itertools;
 use ldap3::{Mod, ResultEntry, Scope, SearchEntry};
@@ -6,7 +6,10 @@ use tap::Pipe;
 use thiserror::Error;
 
 use super::connect::LdapConnection;
-use crate::{models::contacts::Contact, server::ldap::filters};
+use crate::{
+    models::contacts::{Contact, ContactNumber},
+    server::ldap::filters,
+};
 
 #[derive(Debug, Error)]
 pub enum Error {
@@ -20,29 +23,47 @@ pub enum Error {
     Pool(#[from] bb8::RunError<ldap3::LdapError>),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Dn(String);
 
+impl Dn {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
 #[derive(Debug)]
 #[allow(dead_code)]
 pub struct LdapContact {
     dn: Dn,
+    uid: Option<String>,
     sn: Option<String>,
     cn: Option<String>,
-    telephone_number: Option<String>,
+    telephone_numbers: Vec<String>,
+    mobiles: Vec<String>,
 }
 
 impl LdapContact {
     pub fn dn(&self) -> &Dn {
         &self.dn
     }
+
+    pub fn uid(&self) -> Option<&str> {
+        self.uid.as_deref()
+    }
+
+    pub fn telephone_numbers(&self) -> &[String] {
+        &self.telephone_numbers
+    }
 }
 
 #[derive(Debug)]
 pub struct AddLdapContact {
+    pub uid: String,
     pub sn: Option<String>,
     pub cn: Option<String>,
-    pub telephone_number: String,
+    pub telephone_numbers: Vec<String>,
+    pub mobiles: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -50,7 +71,8 @@ pub struct UpdateLdapContact {
     pub dn: Dn,
     pub sn: Option<String>,
     pub cn: Option<String>,
-    pub telephone_number: Option<String>,
+    pub telephone_numbers: Vec<String>,
+    pub mobiles: Vec<String>,
 }
 
 impl From<ResultEntry> for LdapContact {
@@ -59,22 +81,54 @@ impl From<ResultEntry> for LdapContact {
 
         LdapContact {
             dn: Dn(entry.dn.clone()),
+            uid: entry.attrs.get("uid").and_then(|v| v.first()).cloned(),
             sn: entry.attrs.get("sn").and_then(|v| v.first()).cloned(),
             cn: entry.attrs.get("cn").and_then(|v| v.first()).cloned(),
-            telephone_number: entry
+            telephone_numbers: entry
                 .attrs
                 .get("telephoneNumber")
-                .and_then(|v| v.first())
-                .cloned(),
+                .cloned()
+                .unwrap_or_default(),
+            mobiles: entry.attrs.get("mobile").cloned().unwrap_or_default(),
         }
     }
 }
 
+/// Split the primary and additional numbers of a contact into the
+/// `telephoneNumber` and `mobile` attribute values.
+fn contact_numbers(contact: &Contact, numbers: &[ContactNumber]) -> (Vec<String>, Vec<String>) {
+    let mut telephone_numbers = vec![contact.phone_number.clone()];
+    let mut mobiles = vec![];
+
+    for number in numbers {
+        if number.is_mobile() {
+            mobiles.push(number.phone_number.clone());
+        } else {
+            telephone_numbers.push(number.phone_number.clone());
+        }
+    }
+
+    (telephone_numbers, mobiles)
+}
+
+/// Entries are named after the contact id, so that a person with several
+/// numbers is a single entry with multi-valued `telephoneNumber`/`mobile`.
+fn contact_dn(contact: &Contact, base_dn: &str) -> Dn {
+    uid_dn(&contact.id.to_string(), base_dn)
+}
+
+fn uid_dn(uid: &str, base_dn: &str) -> Dn {
+    Dn(format!("uid={},{}", filters::escape_dn_value(uid), base_dn))
+}
+
+/// Uses the action in effect now, so contacts are synced again when a
+/// temporary action expires.
 pub fn include_contact_in_ldap(contact: &Contact) -> bool {
     let tests = [
+        contact.archived_at.is_none(),
         contact.name.is_some(),
         contact.phone_number != "anonymous",
-        contact.action == "allow",
+        contact.effective_action() == "allow",
     ];
 
     tests.into_iter().all(|f| f)
@@ -86,23 +140,31 @@ async fn add_ldap_contact(
     conn: &mut LdapConnection,
 ) -> Result<(), Error> {
     // get connection will deadlock if called twice from same thread.
-    let dn = format!(
-        "telephoneNumber={},{}",
-        filters::escape_dn_value(&request.telephone_number),
-        base_dn
-    );
+    let dn = uid_dn(&request.uid, base_dn).0;
     let attrs = vec![
+        ("uid".to_string(), HashSet::from([request.uid])),
         ("cn".to_string(), HashSet::from_iter(request.cn.into_iter())),
         ("sn".to_string(), HashSet::from_iter(request.sn.into_iter())),
         (
             "telephoneNumber".to_string(),
-            HashSet::from([request.telephone_number]),
+            HashSet::from_iter(request.telephone_numbers.into_iter()),
+        ),
+        (
+            "mobile".to_string(),
+            HashSet::from_iter(request.mobiles.into_iter()),
         ),
         (
             "objectClass".to_string(),
-            HashSet::from(["person".to_string()]),
+            HashSet::from([
+                "person".to_string(),
+                "organizationalPerson".to_string(),
+                "inetOrgPerson".to_string(),
+            ]),
         ),
-    ];
+    ]
+    .into_iter()
+    .filter(|(_, values)| !values.is_empty())
+    .collect::<Vec<_>>();
     conn.add(&dn, attrs).await?.success()?;
 
     Ok(())
@@ -110,61 +172,44 @@ async fn add_ldap_contact(
 
 async fn update_ldap_contact(
     request: UpdateLdapContact,
-    base_dn: &str,
     conn: &mut LdapConnection,
 ) -> Result<(), Error> {
-    let old_dn = &request.dn.0;
-
-    // If the phone number changed we must rename the entry via ModifyDN before
-    // touching any other attributes, because telephoneNumber forms the RDN and
-    // cannot be changed with a plain Modify operation.
-    let current_dn = if let Some(ref new_number) = request.telephone_number {
-        let new_rdn = format!("telephoneNumber={}", filters::escape_dn_value(new_number));
-        // Extract the existing RDN (everything before the first ',').
-        let old_rdn = old_dn.split_once(',').map(|(rdn, _)| rdn).unwrap_or(old_dn);
-        if old_rdn != new_rdn {
-            conn.modifydn(old_dn, &new_rdn, true, None)
-                .await?
-                .success()?;
-            format!("{},{}", new_rdn, base_dn)
-        } else {
-            old_dn.clone()
-        }
-    } else {
-        old_dn.clone()
-    };
-
-    // Modify only cn/sn — telephoneNumber is handled by ModifyDN above and
-    // must never appear in a Modify on an entry where it forms the RDN.
+    // The RDN is the uid, so every other attribute can be replaced in place.
     let mods = vec![
         Mod::Replace("cn".to_string(), HashSet::from_iter(request.cn.into_iter())),
         Mod::Replace("sn".to_string(), HashSet::from_iter(request.sn.into_iter())),
+        Mod::Replace(
+            "telephoneNumber".to_string(),
+            HashSet::from_iter(request.telephone_numbers.into_iter()),
+        ),
+        Mod::Replace(
+            "mobile".to_string(),
+            HashSet::from_iter(request.mobiles.into_iter()),
+        ),
     ];
-    conn.modify(&current_dn, mods).await?.success()?;
+    conn.modify(&request.dn.0, mods).await?.success()?;
 
     Ok(())
 }
 
-async fn delete_ldap_contact(dn: &Dn, conn: &mut LdapConnection) -> Result<(), Error> {
+pub async fn delete_ldap_contact(dn: &Dn, conn: &mut LdapConnection) -> Result<(), Error> {
     // get connection will deadlock if called twice from same thread.
     conn.delete(&dn.0).await?.success()?;
 
     Ok(())
 }
 
-pub async fn get_contact(
-    phone_number: &str,
+async fn search_contact(
+    filter: &str,
     base_dn: &str,
     conn: &mut LdapConnection,
 ) -> Result<Option<LdapContact>, Error> {
-    let filter = filters::eq("telephoneNumber", phone_number);
-
     let (results, _) = conn
         .search(
             base_dn,
             Scope::Subtree,
-            &filter,
-            &vec!["sn", "cn", "telephoneNumber"],
+            filter,
+            &vec!["uid", "sn", "cn", "telephoneNumber", "mobile"],
         )
         .await?
         .success()?;
@@ -180,43 +225,226 @@ pub async fn get_contact(
         .map_err(|_err| Error::LdapTooManyResults)
 }
 
+/// Every person entry under `base_dn`.  Entries created before entries were
+/// named by uid only have the `person` object class, so match on that rather
+/// than `inetOrgPerson`.
+pub async fn get_all_contacts(
+    base_dn: &str,
+    conn: &mut LdapConnection,
+) -> Result<Vec<LdapContact>, Error> {
+    let (results, _) = conn
+        .search(
+            base_dn,
+            Scope::Subtree,
+            &filters::eq("objectClass", "person"),
+            &vec!["uid", "sn", "cn", "telephoneNumber", "mobile"],
+        )
+        .await?
+        .success()?;
+
+    Ok(results.into_iter().map(LdapContact::from).collect())
+}
+
+/// Pair each contact, given as its uid and primary number, with its entry:
+/// by uid, or for entries created before entries were named by uid, by
+/// `telephoneNumber`.  Each entry is given to at most one contact.
+pub fn match_ldap_contacts(
+    contacts: &[(String, &str)],
+    entries: &[LdapContact],
+) -> Vec<Option<usize>> {
+    let uids: HashSet<&str> = contacts.iter().map(|(uid, _)| uid.as_str()).collect();
+    let mut by_uid: HashMap<&str, usize> = HashMap::new();
+    let mut by_number: HashMap<&str, usize> = HashMap::new();
+    for (index, entry) in entries.iter().enumerate() {
+        match entry.uid() {
+            Some(uid) if uids.contains(uid) => {
+                by_uid.insert(uid, index);
+            }
+            _ => {
+                for number in entry.telephone_numbers() {
+                    by_number.entry(number).or_insert(index);
+                }
+            }
+        }
+    }
+
+    let mut matched = vec![false; entries.len()];
+    contacts
+        .iter()
+        .map(|(uid, phone_number)| {
+            let index = by_uid
+                .get(uid.as_str())
+                .or_else(|| by_number.get(phone_number))
+                .copied()
+                .filter(|index| !matched[*index]);
+            if let Some(index) = index {
+                matched[index] = true;
+            }
+            index
+        })
+        .collect()
+}
+
+/// The entry `contact` should have, `None` if it should not be in LDAP.
+pub fn expected_ldap_contact(
+    contact: &Contact,
+    numbers: &[ContactNumber],
+) -> Option<AddLdapContact> {
+    if !include_contact_in_ldap(contact) {
+        return None;
+    }
+
+    let (telephone_numbers, mobiles) = contact_numbers(contact, numbers);
+    Some(AddLdapContact {
+        uid: contact.id.to_string(),
+        cn: contact.name.clone(),
+        sn: contact.name.clone(),
+        telephone_numbers,
+        mobiles,
+    })
+}
+
+/// Names of the attributes of `actual` that differ from `expected`.  The
+/// order of multi-valued attributes is ignored.
+pub fn ldap_contact_differences(
+    expected: &AddLdapContact,
+    base_dn: &str,
+    actual: &LdapContact,
+) -> Vec<&'static str> {
+    let same_values =
+        |a: &[String], b: &[String]| a.iter().collect::<HashSet<_>>() == b.iter().collect();
+
+    [
+        ("dn", uid_dn(&expected.uid, base_dn) == actual.dn),
+        ("cn", expected.cn == actual.cn),
+        ("sn", expected.sn == actual.sn),
+        (
+            "telephoneNumber",
+            same_values(&expected.telephone_numbers, &actual.telephone_numbers),
+        ),
+        ("mobile", same_values(&expected.mobiles, &actual.mobiles)),
+    ]
+    .into_iter()
+    .filter(|(_, same)| !same)
+    .map(|(attribute, _)| attribute)
+    .collect()
+}
+
+/// Write `expected`, replacing the entry at `existing` if given.  An entry
+/// with another DN is deleted and added again under the contact's uid.
+pub async fn put_ldap_contact(
+    expected: AddLdapContact,
+    existing: Option<&Dn>,
+    base_dn: &str,
+    conn: &mut LdapConnection,
+) -> Result<(), Error> {
+    let dn = uid_dn(&expected.uid, base_dn);
+
+    match existing {
+        Some(existing) if *existing == dn => {
+            let request = UpdateLdapContact {
+                dn,
+                sn: expected.sn,
+                cn: expected.cn,
+                telephone_numbers: expected.telephone_numbers,
+                mobiles: expected.mobiles,
+            };
+            update_ldap_contact(request, conn).await
+        }
+        Some(existing) => {
+            delete_ldap_contact(existing, conn).await?;
+            add_ldap_contact(expected, base_dn, conn).await
+        }
+        None => add_ldap_contact(expected, base_dn, conn).await,
+    }
+}
+
+/// Find the entry holding `phone_number` as either a `telephoneNumber` or a
+/// `mobile`.
+pub async fn get_contact(
+    phone_number: &str,
+    base_dn: &str,
+    conn: &mut LdapConnection,
+) -> Result<Option<LdapContact>, Error> {
+    let filter = filters::or(&[
+        filters::eq("telephoneNumber", phone_number),
+        filters::eq("mobile", phone_number),
+    ]);
+
+    search_contact(&filter, base_dn, conn).await
+}
+
+/// Find the entry for `contact`, either by its uid or, for entries created
+/// before entries were named by uid, by its previous primary number.
+pub async fn get_contact_entry(
+    phone_number: &str,
+    contact: &Contact,
+    base_dn: &str,
+    conn: &mut LdapConnection,
+) -> Result<Option<LdapContact>, Error> {
+    let filter = filters::or(&[
+        filters::eq("uid", &contact.id.to_string()),
+        filters::eq("telephoneNumber", phone_number),
+    ]);
+
+    search_contact(&filter, base_dn, conn).await
+}
+
+/// Add, update or remove the entry of a contact.  Fails if the entry cannot
+/// be looked up, so that the database change is rolled back rather than left
+/// out of step with LDAP.
 pub async fn update_ldap_contact_from_contact(
     phone_number: &str,
     contact: &Contact,
+    numbers: &[ContactNumber],
     base_dn: &str,
     conn: &mut LdapConnection,
 ) -> Result<(), Error> {
-    match get_contact(phone_number, base_dn, conn).await {
-        Ok(Some(ldap_contact)) => {
+    match get_contact_entry(phone_number, contact, base_dn, conn).await? {
+        Some(ldap_contact) => {
             if include_contact_in_ldap(contact) {
-                let request = UpdateLdapContact {
-                    dn: ldap_contact.dn().clone(),
-                    cn: contact.name.clone(),
-                    sn: contact.name.clone(),
-                    telephone_number: Some(contact.phone_number.clone()),
-                };
-                update_ldap_contact(request, base_dn, conn).await?;
+                let dn = contact_dn(contact, base_dn);
+                let (telephone_numbers, mobiles) = contact_numbers(contact, numbers);
+
+                if *ldap_contact.dn() == dn {
+                    let request = UpdateLdapContact {
+                        dn,
+                        cn: contact.name.clone(),
+                        sn: contact.name.clone(),
+                        telephone_numbers,
+                        mobiles,
+                    };
+                    update_ldap_contact(request, conn).await?;
+                } else {
+                    // Entry still uses the old telephoneNumber RDN, replace it
+                    // with one named after the contact.
+                    delete_ldap_contact(ldap_contact.dn(), conn).await?;
+                    let request = AddLdapContact {
+                        uid: contact.id.to_string(),
+                        cn: contact.name.clone(),
+                        sn: contact.name.clone(),
+                        telephone_numbers,
+                        mobiles,
+                    };
+                    add_ldap_contact(request, base_dn, conn).await?;
+                }
             } else {
                 delete_ldap_contact(ldap_contact.dn(), conn).await?;
             }
         }
-        Ok(None) => {
+        None => {
             if include_contact_in_ldap(contact) {
+                let (telephone_numbers, mobiles) = contact_numbers(contact, numbers);
                 let request = AddLdapContact {
+                    uid: contact.id.to_string(),
                     cn: contact.name.clone(),
                     sn: contact.name.clone(),
-                    telephone_number: contact.phone_number.clone(),
+                    telephone_numbers,
+                    mobiles,
                 };
                 add_ldap_contact(request, base_dn, conn).await?;
             }
         }
-        Err(err) => {
-            tracing::error!(
-                "Error getting LDAP contact for phone number {}: {}",
-                phone_number,
-                err
-            );
-        }
     }
 
     Ok(())
@@ -237,3 +465,86 @@ pub async fn delete_ldap_contact_from_phone_number(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_ldap_contact_differences() {
+        let expected = AddLdapContact {
+            uid: "12".to_string(),
+            sn: Some("Jo".to_string()),
+            cn: Some("Jo".to_string()),
+            telephone_numbers: strings(&["0299998888", "0388887777"]),
+            mobiles: vec![],
+        };
+        let mut actual = LdapContact {
+            dn: Dn("uid=12,ou=people,dc=example".to_string()),
+            uid: Some("12".to_string()),
+            sn: Some("Jo".to_string()),
+            cn: Some("Jo".to_string()),
+            telephone_numbers: strings(&["0388887777", "0299998888"]),
+            mobiles: vec![],
+        };
+
+        let base_dn = "ou=people,dc=example";
+        assert!(ldap_contact_differences(&expected, base_dn, &actual).is_empty());
+
+        actual.dn = Dn("telephoneNumber=0299998888,ou=people,dc=example".to_string());
+        actual.cn = None;
+        actual.mobiles = strings(&["0411111111"]);
+        assert_eq!(
+            ldap_contact_differences(&expected, base_dn, &actual),
+            vec!["dn", "cn", "mobile"]
+        );
+    }
+
+    #[test]
+    fn test_match_baseline_entry() {
+        // As created before entries were named by uid: `person` only, no uid.
+        let baseline = LdapContact {
+            dn: Dn("telephoneNumber=0299998888,ou=people,dc=example".to_string()),
+            uid: None,
+            sn: Some("Jo".to_string()),
+            cn: Some("Jo".to_string()),
+            telephone_numbers: strings(&["0299998888"]),
+            mobiles: vec![],
+        };
+        let current = LdapContact {
+            dn: Dn("uid=13,ou=people,dc=example".to_string()),
+            uid: Some("13".to_string()),
+            sn: Some("Sam".to_string()),
+            cn: Some("Sam".to_string()),
+            telephone_numbers: strings(&["0388887777"]),
+            mobiles: vec![],
+        };
+        let entries = [baseline, current];
+
+        let contacts = [
+            ("12".to_string(), "0299998888"),
+            ("13".to_string(), "0388887777"),
+            ("14".to_string(), "0299998888"),
+        ];
+        assert_eq!(
+            match_ldap_contacts(&contacts, &entries),
+            vec![Some(0), Some(1), None]
+        );
+
+        let expected = AddLdapContact {
+            uid: "12".to_string(),
+            sn: Some("Jo".to_string()),
+            cn: Some("Jo".to_string()),
+            telephone_numbers: strings(&["0299998888"]),
+            mobiles: vec![],
+        };
+        assert_eq!(
+            ldap_contact_differences(&expected, "ou=people,dc=example", &entries[0]),
+            vec!["dn"]
+        );
+    }
+}
@@ -0,0 +1,166 @@
+//! TLS for the LDAP connection.
+//!
+//! Configured from the environment:
+//!
+//! - `LDAP_TLS`: `none` (default), `ldaps` to connect to an `ldaps://` URL,
+//!   or `starttls` to upgrade a plain `ldap://` connection before binding.
+//! - `LDAP_TLS_CA_FILE`: PEM bundle of CA certificates to trust in addition
+//!   to the system ones.
+//! - `LDAP_TLS_CERT_FILE` and `LDAP_TLS_KEY_FILE`: PEM client certificate
+//!   and PKCS#8 private key, for servers that require one.
+//! - `LDAP_TLS_VERIFY_HOSTNAME`: `false` to accept a certificate issued for
+//!   another host name. The chain is still verified.
+
+use std::env;
+use std::fs;
+
+use ldap3::LdapConnSettings;
+use native_tls::{Certificate, Identity, TlsConnector};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TlsMode {
+    None,
+    Ldaps,
+    StartTls,
+}
+
+impl TlsMode {
+    pub fn scheme(self) -> &'static str {
+        match self {
+            TlsMode::None | TlsMode::StartTls => "ldap",
+            TlsMode::Ldaps => "ldaps",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    pub mode: TlsMode,
+    pub ca_file: Option<String>,
+    pub client_cert: Option<(String, String)>,
+    pub verify_hostname: bool,
+}
+
+fn read_file(name: &str, path: &str) -> Vec<u8> {
+    fs::read(path).unwrap_or_else(|err| panic!("{name}: cannot read {path}: {err}"))
+}
+
+/// Split a PEM bundle into its certificates, as [`Certificate::from_pem`]
+/// only reads the first one.
+fn split_pem_certificates(pem: &str) -> Vec<String> {
+    const BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+    const END: &str = "-----END CERTIFICATE-----";
+
+    let mut certificates = Vec::new();
+    let mut rest = pem;
+    while let Some(start) = rest.find(BEGIN) {
+        let Some(len) = rest[start..].find(END) else {
+            break;
+        };
+        let end = start + len + END.len();
+        certificates.push(format!("{}\n", &rest[start..end]));
+        rest = &rest[end..];
+    }
+    certificates
+}
+
+impl TlsConfig {
+    pub fn get_from_env() -> TlsConfig {
+        let mode = match env::var("LDAP_TLS").as_deref() {
+            Err(_) | Ok("none") => TlsMode::None,
+            Ok("ldaps") => TlsMode::Ldaps,
+            Ok("starttls") => TlsMode::StartTls,
+            Ok(_) => panic!("LDAP_TLS must be none, ldaps or starttls"),
+        };
+
+        let client_cert = match (
+            env::var("LDAP_TLS_CERT_FILE").ok(),
+            env::var("LDAP_TLS_KEY_FILE").ok(),
+        ) {
+            (Some(cert), Some(key)) => Some((cert, key)),
+            (None, None) => None,
+            _ => panic!("LDAP_TLS_CERT_FILE and LDAP_TLS_KEY_FILE must be set together"),
+        };
+
+        let verify_hostname = env::var("LDAP_TLS_VERIFY_HOSTNAME")
+            .map(|value| {
+                value
+                    .parse()
+                    .expect("LDAP_TLS_VERIFY_HOSTNAME must be true or false")
+            })
+            .unwrap_or(true);
+
+        TlsConfig {
+            mode,
+            ca_file: env::var("LDAP_TLS_CA_FILE").ok(),
+            client_cert,
+            verify_hostname,
+        }
+    }
+
+    fn connector(&self) -> TlsConnector {
+        let mut builder = TlsConnector::builder();
+
+        if let Some(ca_file) = &self.ca_file {
+            let pem = read_file("LDAP_TLS_CA_FILE", ca_file);
+            let pem = String::from_utf8(pem).expect("LDAP_TLS_CA_FILE must be a PEM file");
+            let certificates = split_pem_certificates(&pem);
+            if certificates.is_empty() {
+                panic!("LDAP_TLS_CA_FILE contains no certificates");
+            }
+            for certificate in certificates {
+                let certificate = Certificate::from_pem(certificate.as_bytes())
+                    .expect("LDAP_TLS_CA_FILE contains an invalid certificate");
+                builder.add_root_certificate(certificate);
+            }
+        }
+
+        if let Some((cert_file, key_file)) = &self.client_cert {
+            let cert = read_file("LDAP_TLS_CERT_FILE", cert_file);
+            let key = read_file("LDAP_TLS_KEY_FILE", key_file);
+            let identity = Identity::from_pkcs8(&cert, &key).expect(
+                "LDAP_TLS_CERT_FILE and LDAP_TLS_KEY_FILE must be a PEM certificate and PKCS#8 key",
+            );
+            builder.identity(identity);
+        }
+
+        builder.danger_accept_invalid_hostnames(!self.verify_hostname);
+        builder.build().expect("Invalid LDAP TLS configuration")
+    }
+
+    /// Connection settings for [`ldap3::LdapConnAsync::with_settings`].
+    pub fn settings(&self) -> LdapConnSettings {
+        let settings = LdapConnSettings::new().set_starttls(self.mode == TlsMode::StartTls);
+        match self.mode {
+            TlsMode::None => settings,
+            TlsMode::Ldaps | TlsMode::StartTls => settings.set_connector(self.connector()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_pem_certificates() {
+        let pem = "\
+subject=CN=Root
+-----BEGIN CERTIFICATE-----
+AAAA
+-----END CERTIFICATE-----
+
+-----BEGIN CERTIFICATE-----
+BBBB
+-----END CERTIFICATE-----
+";
+        assert_eq!(
+            split_pem_certificates(pem),
+            vec![
+                "-----BEGIN CERTIFICATE-----\nAAAA\n-----END CERTIFICATE-----\n",
+                "-----BEGIN CERTIFICATE-----\nBBBB\n-----END CERTIFICATE-----\n",
+            ]
+        );
+        assert!(split_pem_certificates("").is_empty());
+    }
+}
@@ -1,12 +1,18 @@
 use dioxus::prelude::*;
 
+mod archive;
 pub mod auth;
+mod call_export;
+pub mod csv;
 pub mod database;
 mod handlers;
 mod incoming_calls;
 pub mod ldap;
 mod oidc;
+pub mod retention;
 mod session_store;
+mod temporary_actions;
+pub mod vcard;
 
 use axum::{Extension, routing::get, routing::post};
 use handlers::{dioxus_handler, health_check};
@@ -38,6 +44,27 @@ pub fn init(app: fn() -> Element) {
 
         let authentication = Arc::new(Authentication::get_from_env());
 
+        tokio::task::spawn(archive::continuously_purge_archived(
+            database.clone(),
+            archive::Config::get_from_env(),
+            tokio::time::Duration::from_secs(60 * 60),
+        ));
+
+        if let Some(config) = retention::Config::get_from_env() {
+            tokio::task::spawn(retention::continuously_apply_retention(
+                database.clone(),
+                ldap.clone(),
+                config,
+                tokio::time::Duration::from_secs(60 * 60),
+            ));
+        }
+
+        tokio::task::spawn(temporary_actions::continuously_expire_temporary_actions(
+            database.clone(),
+            ldap.clone(),
+            tokio::time::Duration::from_secs(60),
+        ));
+
         let (incoming_call, _) = broadcast::channel::<(PhoneCall, Contact)>(2);
 
         let session_layer = {
@@ -81,6 +108,10 @@ pub fn init(app: fn() -> Element) {
             // .serve_static_assets()
             .serve_dioxus_application(cfg, app)
             .route("/api/incoming_call/", post(incoming_calls::post_handler))
+            .route(
+                crate::models::phone_calls::PHONE_CALL_EXPORT_PATH,
+                get(call_export::get_handler),
+            )
             .route("/_health", get(health_check))
             .route("/_dioxus", get(dioxus_handler))
             .pipe(add_oidc_middleware)
@@ -69,6 +69,10 @@ pub enum Error {
     // Database error
     #[error("Database error: {0}")]
     Database(#[from] database::connection::Error),
+
+    // Group membership error
+    #[error("Groups error: {0}")]
+    Groups(#[from] database::service::groups::Error),
 }
 
 impl Client {
@@ -189,6 +193,11 @@ impl Client {
                 .map_err(database::connection::Error::Diesel)?
         };
 
+        // Every other group in the claim decides which contacts and
+        // defaults the user can see.
+        let group_names: Vec<String> = groups.into_iter().filter(|g| g != "admin").collect();
+        database::service::groups::set_user_groups(&mut conn, user.id, group_names).await?;
+
         Ok(user)
     }
 }
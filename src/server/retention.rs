@@ -0,0 +1,160 @@
+//! Scheduled retention of the call log.
+//!
+//! Configured from the environment:
+//!
+//! - `CALL_RETENTION_DAYS`: calls older than this are deleted or anonymised.
+//!   Retention is off when unset.
+//! - `CALL_RETENTION_MODE`: `delete` (default) or `anonymise`.
+//! - `CALL_RETENTION_PURGE_CONTACTS`: `true` to also delete contacts created
+//!   by incoming calls that are left without name, comments or calls.
+//! - `CALL_RETENTION_DRY_RUN`: `true` to only log what would be done.
+
+use std::env;
+use std::ops::Deref;
+
+use thiserror::Error;
+
+use crate::server::database::connection::{self as database, DatabasePool};
+use crate::server::database::service::contacts;
+use crate::server::database::service::phone_calls::{self, RetentionMode};
+use crate::server::ldap::connect::LdapPool;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Database error: {0}")]
+    Database(#[from] database::Error),
+    #[error("LDAP pool error: {0}")]
+    LdapPool(#[from] bb8::RunError<ldap3::LdapError>),
+    #[error("Contacts error: {0}")]
+    Contacts(#[from] contacts::Error),
+    #[error("Phone calls error: {0}")]
+    PhoneCalls(#[from] phone_calls::Error),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    pub retention: chrono::Duration,
+    pub mode: RetentionMode,
+    pub purge_contacts: bool,
+    pub dry_run: bool,
+}
+
+fn env_flag(name: &str) -> bool {
+    env::var(name)
+        .map(|value| {
+            value
+                .parse()
+                .unwrap_or_else(|_| panic!("{name} must be true or false"))
+        })
+        .unwrap_or(false)
+}
+
+impl Config {
+    /// Returns `None` when `CALL_RETENTION_DAYS` is not set.
+    pub fn get_from_env() -> Option<Config> {
+        let days: i64 = env::var("CALL_RETENTION_DAYS")
+            .ok()?
+            .parse()
+            .expect("CALL_RETENTION_DAYS must be a number of days");
+
+        let mode = match env::var("CALL_RETENTION_MODE").as_deref() {
+            Err(_) | Ok("delete") => RetentionMode::Delete,
+            Ok("anonymise") => RetentionMode::Anonymise,
+            Ok(_) => panic!("CALL_RETENTION_MODE must be delete or anonymise"),
+        };
+
+        Some(Config {
+            retention: chrono::Duration::days(days),
+            mode,
+            purge_contacts: env_flag("CALL_RETENTION_PURGE_CONTACTS"),
+            dry_run: env_flag("CALL_RETENTION_DRY_RUN"),
+        })
+    }
+}
+
+/// What one run did, or would have done in a dry run.
+#[derive(Debug, Clone, Copy)]
+pub struct Report {
+    pub mode: RetentionMode,
+    pub calls: usize,
+    pub contacts: usize,
+    pub dry_run: bool,
+}
+
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let verb = match self.mode {
+            RetentionMode::Delete => "deleted",
+            RetentionMode::Anonymise => "anonymised",
+        };
+        write!(
+            f,
+            "{} calls {verb}, {} unused contacts purged",
+            self.calls, self.contacts
+        )?;
+        if self.dry_run {
+            write!(f, " (dry run, nothing was changed)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Delete or anonymise calls older than the retention period, then purge
+/// contacts left unused if configured.
+pub async fn apply_retention(
+    pool: &DatabasePool,
+    ldap: &LdapPool,
+    config: &Config,
+) -> Result<Report, Error> {
+    let mut conn = pool.get().await.map_err(database::Error::from)?;
+    let cutoff = chrono::Utc::now() - config.retention;
+
+    let calls =
+        phone_calls::apply_call_retention(&mut conn, cutoff, config.mode, config.dry_run).await?;
+
+    let contacts = if config.purge_contacts {
+        // Deleted calls no longer count; anonymised ones still do.
+        let calls_since = match config.mode {
+            RetentionMode::Delete => cutoff,
+            RetentionMode::Anonymise => chrono::DateTime::<chrono::Utc>::MIN_UTC,
+        };
+        let mut ldap_conn = ldap.get().await?.deref().clone();
+        contacts::purge_unused_contacts(
+            &mut conn,
+            ldap.base_dn(),
+            &mut ldap_conn,
+            calls_since,
+            config.dry_run,
+        )
+        .await?
+    } else {
+        0
+    };
+
+    Ok(Report {
+        mode: config.mode,
+        calls,
+        contacts,
+        dry_run: config.dry_run,
+    })
+}
+
+/// Run [`apply_retention`] every `period`, forever.
+pub async fn continuously_apply_retention(
+    pool: DatabasePool,
+    ldap: LdapPool,
+    config: Config,
+    period: tokio::time::Duration,
+) {
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+        match apply_retention(&pool, &ldap, &config).await {
+            Ok(report) if report.dry_run || report.calls > 0 || report.contacts > 0 => {
+                tracing::info!("Call retention: {report}");
+            }
+            Ok(_) => {}
+            Err(err) => tracing::error!("Error applying call retention: {err}"),
+        }
+    }
+}
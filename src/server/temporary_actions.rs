@@ -0,0 +1,57 @@
+//! Scheduled expiry of temporary contact actions.
+//!
+//! Calls are matched against the effective action at the time of the call,
+//! so this only needs to run often enough to keep LDAP in step.
+
+use std::ops::Deref;
+
+use thiserror::Error;
+
+use crate::server::database::connection::{self as database, DatabasePool};
+use crate::server::database::service::contacts;
+use crate::server::ldap::connect::LdapPool;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Database error: {0}")]
+    Database(#[from] database::Error),
+    #[error("LDAP pool error: {0}")]
+    LdapPool(#[from] bb8::RunError<ldap3::LdapError>),
+    #[error("Contacts error: {0}")]
+    Contacts(#[from] contacts::Error),
+}
+
+/// Clear every temporary action that has expired.
+pub async fn expire_temporary_actions(pool: &DatabasePool, ldap: &LdapPool) -> Result<(), Error> {
+    let mut conn = pool.get().await.map_err(database::Error::from)?;
+    let mut ldap_conn = ldap.get().await?.deref().clone();
+
+    let count = contacts::expire_temporary_actions(
+        &mut conn,
+        ldap.base_dn(),
+        &mut ldap_conn,
+        chrono::Utc::now(),
+    )
+    .await?;
+
+    if count > 0 {
+        tracing::info!("Expired {count} temporary contact actions");
+    }
+
+    Ok(())
+}
+
+/// Run [`expire_temporary_actions`] every `period`, forever.
+pub async fn continuously_expire_temporary_actions(
+    pool: DatabasePool,
+    ldap: LdapPool,
+    period: tokio::time::Duration,
+) {
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+        if let Err(err) = expire_temporary_actions(&pool, &ldap).await {
+            tracing::error!("Error expiring temporary actions: {err}");
+        }
+    }
+}
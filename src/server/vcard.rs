@@ -0,0 +1,407 @@
+//! Minimal vCard reader and writer.
+//!
+//! Writes vCard 4.0 (RFC 6350). Reads 2.1, 3.0 and 4.0 as produced by phone
+//! backups, but only the properties phone_db cares about: `FN`/`N`, `TEL`,
+//! `NOTE`, `CATEGORIES` and our own `X-PHONE-DB-ACTION`.
+
+use thiserror::Error;
+
+const ACTION_PROPERTY: &str = "X-PHONE-DB-ACTION";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VCardPhone {
+    pub number: String,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VCard {
+    pub name: Option<String>,
+    pub phone_numbers: Vec<VCardPhone>,
+    pub note: Option<String>,
+    pub categories: Vec<String>,
+    pub action: Option<String>,
+}
+
+impl VCard {
+    pub fn as_title(&self) -> String {
+        match (&self.name, self.phone_numbers.first()) {
+            (Some(name), _) => name.clone(),
+            (None, Some(phone)) => phone.number.clone(),
+            (None, None) => "(unnamed)".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("Line {0}: END:VCARD without BEGIN:VCARD")]
+    UnexpectedEnd(usize),
+    #[error("Line {0}: BEGIN:VCARD inside another vCard")]
+    NestedBegin(usize),
+    #[error("Missing END:VCARD")]
+    MissingEnd,
+}
+
+fn escape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ',' => out.push_str("\\,"),
+            ';' => out.push_str("\\;"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => out.push('\n'),
+                Some(other) => out.push(other),
+                None => out.push('\\'),
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Split on `sep` where it is not escaped with a backslash.
+fn split_unescaped(value: &str, sep: char) -> Vec<String> {
+    let mut parts = vec![];
+    let mut current = String::new();
+    let mut escaped = false;
+    for c in value.chars() {
+        if escaped {
+            current.push('\\');
+            current.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == sep {
+            parts.push(std::mem::take(&mut current));
+        } else {
+            current.push(c);
+        }
+    }
+    if escaped {
+        current.push('\\');
+    }
+    parts.push(current);
+    parts
+}
+
+/// Fold a content line at 75 octets, as required by RFC 6350 3.2.
+fn fold_line(line: &str, out: &mut String) {
+    let mut width = 0;
+    for c in line.chars() {
+        let len = c.len_utf8();
+        if width + len > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += len;
+    }
+    out.push_str("\r\n");
+}
+
+pub fn encode(cards: &[VCard]) -> String {
+    let mut out = String::new();
+
+    for card in cards {
+        let mut lines = vec!["BEGIN:VCARD".to_string(), "VERSION:4.0".to_string()];
+
+        // FN is mandatory, fall back to the phone number.
+        let name = card.as_title();
+        lines.push(format!("FN:{}", escape_text(&name)));
+        if let Some(name) = &card.name {
+            lines.push(format!("N:{};;;;", escape_text(name)));
+        }
+
+        for (i, phone) in card.phone_numbers.iter().enumerate() {
+            let mut types = vec!["voice".to_string()];
+            if let Some(label) = &phone.label {
+                types.push(if label.eq_ignore_ascii_case("mobile") {
+                    "cell".to_string()
+                } else {
+                    label.to_lowercase()
+                });
+            }
+            let pref = if i == 0 { ";PREF=1" } else { "" };
+            lines.push(format!(
+                "TEL;VALUE=uri;TYPE=\"{}\"{}:tel:{}",
+                types.join(","),
+                pref,
+                phone.number
+            ));
+        }
+
+        if let Some(note) = &card.note {
+            lines.push(format!("NOTE:{}", escape_text(note)));
+        }
+
+        if !card.categories.is_empty() {
+            let categories: Vec<String> = card.categories.iter().map(|c| escape_text(c)).collect();
+            lines.push(format!("CATEGORIES:{}", categories.join(",")));
+        }
+
+        if let Some(action) = &card.action {
+            lines.push(format!("{ACTION_PROPERTY}:{}", escape_text(action)));
+        }
+
+        lines.push("END:VCARD".to_string());
+
+        for line in lines {
+            fold_line(&line, &mut out);
+        }
+    }
+
+    out
+}
+
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn parse(line: &str) -> Option<Property> {
+        // The value starts at the first colon outside a quoted parameter.
+        let mut in_quotes = false;
+        let mut split = None;
+        for (i, c) in line.char_indices() {
+            match c {
+                '"' => in_quotes = !in_quotes,
+                ':' if !in_quotes => {
+                    split = Some(i);
+                    break;
+                }
+                _ => {}
+            }
+        }
+        let split = split?;
+        let (head, value) = (&line[..split], &line[split + 1..]);
+
+        let mut head = head.split(';');
+        let name = head.next()?;
+        // Drop any group prefix, e.g. "item1.TEL".
+        let name = name.rsplit('.').next().unwrap_or(name).to_uppercase();
+
+        let params = head
+            .map(|param| match param.split_once('=') {
+                Some((key, value)) => (key.to_uppercase(), value.trim_matches('"').to_string()),
+                // vCard 2.1 allows bare types, e.g. "TEL;CELL:".
+                None => ("TYPE".to_string(), param.to_string()),
+            })
+            .collect();
+
+        Some(Property {
+            name,
+            params,
+            value: value.to_string(),
+        })
+    }
+
+    fn types(&self) -> Vec<String> {
+        self.params
+            .iter()
+            .filter(|(key, _)| key == "TYPE")
+            .flat_map(|(_, value)| value.split(','))
+            .map(|t| t.trim().to_lowercase())
+            .filter(|t| !t.is_empty())
+            .collect()
+    }
+}
+
+fn phone_label(types: &[String]) -> Option<String> {
+    types
+        .iter()
+        .filter(|t| !matches!(t.as_str(), "voice" | "pref" | "text" | "msg"))
+        .map(|t| match t.as_str() {
+            "cell" => "mobile".to_string(),
+            t => t.to_string(),
+        })
+        .next()
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let value = value.trim().to_string();
+    if value.is_empty() { None } else { Some(value) }
+}
+
+/// Join folded lines back into logical content lines.
+fn unfold(text: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = vec![];
+    for (number, line) in text.lines().enumerate() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if let Some(rest) = line.strip_prefix([' ', '\t'])
+            && let Some((_, last)) = lines.last_mut()
+        {
+            last.push_str(rest);
+            continue;
+        }
+        lines.push((number + 1, line.to_string()));
+    }
+    lines
+}
+
+pub fn decode(text: &str) -> Result<Vec<VCard>, Error> {
+    let mut cards = vec![];
+    let mut current: Option<(VCard, Option<String>)> = None;
+
+    for (number, line) in unfold(text) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some(property) = Property::parse(&line) else {
+            continue;
+        };
+
+        match property.name.as_str() {
+            "BEGIN" if property.value.eq_ignore_ascii_case("VCARD") => {
+                if current.is_some() {
+                    return Err(Error::NestedBegin(number));
+                }
+                current = Some((VCard::default(), None));
+            }
+            "END" if property.value.eq_ignore_ascii_case("VCARD") => {
+                let Some((mut card, structured_name)) = current.take() else {
+                    return Err(Error::UnexpectedEnd(number));
+                };
+                if card.name.is_none() {
+                    card.name = structured_name;
+                }
+                cards.push(card);
+            }
+            _ => {
+                let Some((card, structured_name)) = current.as_mut() else {
+                    continue;
+                };
+                match property.name.as_str() {
+                    "FN" => card.name = non_empty(unescape_text(&property.value)),
+                    "N" => {
+                        // family;given;additional;prefix;suffix
+                        let parts = split_unescaped(&property.value, ';');
+                        let family = parts.first().map(|s| unescape_text(s)).unwrap_or_default();
+                        let given = parts.get(1).map(|s| unescape_text(s)).unwrap_or_default();
+                        *structured_name = non_empty(format!("{given} {family}"));
+                    }
+                    "TEL" => {
+                        let value = property.value.trim();
+                        let value = value.strip_prefix("tel:").unwrap_or(value);
+                        if let Some(number) = non_empty(value.to_string()) {
+                            card.phone_numbers.push(VCardPhone {
+                                number,
+                                label: phone_label(&property.types()),
+                            });
+                        }
+                    }
+                    "NOTE" => card.note = non_empty(unescape_text(&property.value)),
+                    "CATEGORIES" => {
+                        card.categories.extend(
+                            split_unescaped(&property.value, ',')
+                                .into_iter()
+                                .filter_map(|c| non_empty(unescape_text(&c))),
+                        );
+                    }
+                    ACTION_PROPERTY => card.action = non_empty(unescape_text(&property.value)),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    if current.is_some() {
+        return Err(Error::MissingEnd);
+    }
+
+    Ok(cards)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let card = VCard {
+            name: Some("Smith, John; Jr".to_string()),
+            phone_numbers: vec![
+                VCardPhone {
+                    number: "+61412345678".to_string(),
+                    label: None,
+                },
+                VCardPhone {
+                    number: "0398765432".to_string(),
+                    label: Some("mobile".to_string()),
+                },
+            ],
+            note: Some(
+                "Line one\nLine two with a very long piece of text that needs folding across lines"
+                    .to_string(),
+            ),
+            categories: vec!["family".to_string(), "school".to_string()],
+            action: Some("voicemail".to_string()),
+        };
+
+        let text = encode(std::slice::from_ref(&card));
+        assert!(text.starts_with("BEGIN:VCARD\r\nVERSION:4.0\r\n"));
+        assert!(text.lines().all(|l| l.len() <= 75));
+
+        let decoded = decode(&text).unwrap();
+        assert_eq!(decoded, vec![card]);
+    }
+
+    #[test]
+    fn test_decode_phone_backup() {
+        let text = "BEGIN:VCARD\n\
+                    VERSION:2.1\n\
+                    N:Citizen;Jane;;;\n\
+                    TEL;CELL;PREF:+61 400 000 000\n\
+                    item1.TEL;type=HOME:(03) 9000 0000\n\
+                    END:VCARD\n\
+                    BEGIN:VCARD\n\
+                    VERSION:3.0\n\
+                    FN:Plumber\n\
+                    TEL;TYPE=WORK,VOICE:1300 000 000\n\
+                    CATEGORIES:tradie\n\
+                    END:VCARD\n";
+
+        let cards = decode(text).unwrap();
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[0].name.as_deref(), Some("Jane Citizen"));
+        assert_eq!(
+            cards[0].phone_numbers,
+            vec![
+                VCardPhone {
+                    number: "+61 400 000 000".to_string(),
+                    label: Some("mobile".to_string()),
+                },
+                VCardPhone {
+                    number: "(03) 9000 0000".to_string(),
+                    label: Some("home".to_string()),
+                },
+            ]
+        );
+        assert_eq!(cards[1].name.as_deref(), Some("Plumber"));
+        assert_eq!(cards[1].phone_numbers[0].label.as_deref(), Some("work"));
+        assert_eq!(cards[1].categories, vec!["tradie".to_string()]);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(decode("END:VCARD\n"), Err(Error::UnexpectedEnd(1)));
+        assert_eq!(decode("BEGIN:VCARD\nFN:x\n"), Err(Error::MissingEnd));
+    }
+}
@@ -0,0 +1,299 @@
+use std::ops::Deref;
+
+use chrono::Local;
+use dioxus::prelude::*;
+use dioxus_fullstack::ServerFnError;
+use dioxus_router::navigator;
+use tap::Pipe;
+
+use crate::{
+    Route,
+    components::{
+        archive::{ActiveDialog, ArchiveDialog, ListDialogReference},
+        buttons::ChangeButton,
+    },
+    functions::{
+        contacts::{get_archived_contacts, get_contact_by_id, restore_contact},
+        defaults::{get_archived_defaults, get_default_by_id, restore_default},
+    },
+    models::{contacts::Contact, defaults::Default},
+    use_user,
+};
+
+#[component]
+fn ArchivedContactRow(
+    contact: Contact,
+    on_restore: Callback<()>,
+    error: Signal<Option<String>>,
+) -> Element {
+    let contact_id = contact.id;
+    let navigator = navigator();
+
+    rsx! {
+        tr {
+            td { {contact.phone_number.clone()} }
+            td {
+                if let Some(name) = &contact.name {
+                    {name.clone()}
+                }
+            }
+            td {
+                if let Some(archived_at) = contact.archived_at {
+                    {archived_at.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()}
+                }
+            }
+            td {
+                div { class: "flex gap-2",
+                    ChangeButton {
+                        on_click: {
+                            let contact = contact.clone();
+                            move |_| {
+                                let contact = contact.clone();
+                                spawn(async move {
+                                    match restore_contact(contact).await {
+                                        Ok(_) => {
+                                            error.set(None);
+                                            on_restore(());
+                                        }
+                                        Err(err) => error.set(Some(err.to_string())),
+                                    }
+                                });
+                            }
+                        },
+                        "Restore"
+                    }
+                    ChangeButton {
+                        on_click: move |_| {
+                            navigator
+                                .push(Route::ArchiveList {
+                                    dialog: ListDialogReference::DeleteContact {
+                                        contact_id,
+                                    },
+                                });
+                        },
+                        "Delete"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn ArchivedDefaultRow(
+    default: Default,
+    on_restore: Callback<()>,
+    error: Signal<Option<String>>,
+) -> Element {
+    let default_id = default.id;
+    let navigator = navigator();
+
+    rsx! {
+        tr {
+            td {
+                if let Some(regexp) = &default.regexp {
+                    {regexp.clone()}
+                }
+            }
+            td {
+                if let Some(name) = &default.name {
+                    {name.clone()}
+                }
+            }
+            td {
+                if let Some(archived_at) = default.archived_at {
+                    {archived_at.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()}
+                }
+            }
+            td {
+                div { class: "flex gap-2",
+                    ChangeButton {
+                        on_click: {
+                            let default = default.clone();
+                            move |_| {
+                                let default = default.clone();
+                                spawn(async move {
+                                    match restore_default(default).await {
+                                        Ok(_) => {
+                                            error.set(None);
+                                            on_restore(());
+                                        }
+                                        Err(err) => error.set(Some(err.to_string())),
+                                    }
+                                });
+                            }
+                        },
+                        "Restore"
+                    }
+                    ChangeButton {
+                        on_click: move |_| {
+                            navigator
+                                .push(Route::ArchiveList {
+                                    dialog: ListDialogReference::DeleteDefault {
+                                        default_id,
+                                    },
+                                });
+                        },
+                        "Delete"
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn ArchiveList(dialog: ReadSignal<Option<ListDialogReference>>) -> Element {
+    let user = use_user().ok().flatten();
+
+    let Some(_user) = user.as_ref() else {
+        return rsx! {
+            p { class: "alert alert-error", "You are not logged in." }
+        };
+    };
+
+    let error: Signal<Option<String>> = use_signal(|| None);
+
+    let dialog: Resource<Result<ActiveDialog, ServerFnError>> = use_resource(move || async move {
+        let Some(dialog) = dialog() else {
+            return Ok(ActiveDialog::Idle);
+        };
+        match dialog {
+            ListDialogReference::DeleteContact { contact_id } => {
+                let contact = get_contact_by_id(contact_id)
+                    .await?
+                    .ok_or(ServerFnError::new("Cannot find contact"))?;
+                ActiveDialog::DeleteContact(contact).pipe(Ok)
+            }
+            ListDialogReference::DeleteDefault { default_id } => {
+                let default = get_default_by_id(default_id)
+                    .await?
+                    .ok_or(ServerFnError::new("Cannot find default"))?;
+                ActiveDialog::DeleteDefault(default).pipe(Ok)
+            }
+            ListDialogReference::Idle => Ok(ActiveDialog::Idle),
+        }
+    });
+
+    let navigator = navigator();
+    let mut contacts: Resource<Result<Vec<Contact>, ServerFnError>> =
+        use_resource(move || async move { get_archived_contacts().await });
+    let mut defaults: Resource<Result<Vec<Default>, ServerFnError>> =
+        use_resource(move || async move { get_archived_defaults().await });
+
+    rsx! {
+        div { class: "ml-2 mr-2",
+            p { class: "alert alert-info mb-2",
+                "Archived contacts and defaults are not used to match calls. They are permanently deleted after the retention period."
+            }
+            if let Some(err) = error() {
+                div { class: "alert alert-error mb-2", "Error: " {err} }
+            }
+
+            h2 { class: "text-xl font-bold mb-2", "Contacts" }
+            match contacts.read().deref() {
+                Some(Err(err)) => rsx! {
+                    div { class: "alert alert-error",
+                        "Error loading contacts: "
+                        {err.to_string()}
+                    }
+                },
+                Some(Ok(list)) if list.is_empty() => rsx! {
+                    p { class: "alert alert-info", "No archived contacts." }
+                },
+                Some(Ok(list)) => rsx! {
+                    table { class: "table table-striped mb-4",
+                        thead {
+                            tr {
+                                th { "Phone Number" }
+                                th { "Name" }
+                                th { "Archived" }
+                                th {}
+                            }
+                        }
+                        tbody {
+                            for contact in list.iter() {
+                                ArchivedContactRow {
+                                    key: "{contact.id}",
+                                    contact: contact.clone(),
+                                    on_restore: move |()| contacts.restart(),
+                                    error,
+                                }
+                            }
+                        }
+                    }
+                },
+                None => rsx! {
+                    p { class: "alert alert-info", "Loading..." }
+                },
+            }
+
+            h2 { class: "text-xl font-bold mb-2", "Defaults" }
+            match defaults.read().deref() {
+                Some(Err(err)) => rsx! {
+                    div { class: "alert alert-error",
+                        "Error loading defaults: "
+                        {err.to_string()}
+                    }
+                },
+                Some(Ok(list)) if list.is_empty() => rsx! {
+                    p { class: "alert alert-info", "No archived defaults." }
+                },
+                Some(Ok(list)) => rsx! {
+                    table { class: "table table-striped mb-4",
+                        thead {
+                            tr {
+                                th { "Regexp" }
+                                th { "Name" }
+                                th { "Archived" }
+                                th {}
+                            }
+                        }
+                        tbody {
+                            for default in list.iter() {
+                                ArchivedDefaultRow {
+                                    key: "{default.id}",
+                                    default: default.clone(),
+                                    on_restore: move |()| defaults.restart(),
+                                    error,
+                                }
+                            }
+                        }
+                    }
+                },
+                None => rsx! {
+                    p { class: "alert alert-info", "Loading..." }
+                },
+            }
+        }
+
+        match dialog.read().deref() {
+            Some(Err(err)) => rsx! {
+                div { class: "alert alert-error",
+                    "Error loading dialog: "
+                    {err.to_string()}
+                }
+            },
+            Some(Ok(dialog)) => rsx! {
+                ArchiveDialog {
+                    dialog: dialog.clone(),
+                    on_delete: move |()| {
+                        contacts.restart();
+                        defaults.restart();
+                    },
+                    on_close: move |()| {
+                        navigator
+                            .push(Route::ArchiveList {
+                                dialog: ListDialogReference::Idle,
+                            });
+                    },
+                }
+            },
+            None => {
+                rsx! {
+                    p { class: "alert alert-info", "Loading..." }
+                }
+            }
+        }
+    }
+}
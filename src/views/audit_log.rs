@@ -0,0 +1,175 @@
+use std::ops::Deref;
+
+use chrono::NaiveDate;
+use dioxus::prelude::*;
+use dioxus_fullstack::ServerFnError;
+
+use crate::{
+    Route,
+    components::{audit_log::AuditLogTable, buttons::NavButton},
+    functions::{audit_log::search_audit_log, users::get_users},
+    models::{
+        audit_log::{AuditEntityRef, AuditLogEntry, AuditLogFilter, AuditLogId},
+        users::UserId,
+    },
+    use_user,
+};
+
+const PAGE_SIZE: i64 = 50;
+
+#[component]
+pub fn AuditLogList(
+    user_id: ReadSignal<Option<UserId>>,
+    entity: ReadSignal<Option<AuditEntityRef>>,
+    from_date: ReadSignal<Option<NaiveDate>>,
+    to_date: ReadSignal<Option<NaiveDate>>,
+    before_id: ReadSignal<Option<AuditLogId>>,
+) -> Element {
+    let user = use_user().ok().flatten();
+    let Some(user) = user.as_ref() else {
+        return rsx! {
+            p { class: "alert alert-error", "You are not logged in." }
+        };
+    };
+    if !user.is_admin {
+        return rsx! {
+            p { class: "alert alert-error", "You are not an admin." }
+        };
+    }
+
+    let users = use_resource(|| async { get_users().await });
+
+    let filter = use_memo(move || AuditLogFilter {
+        user_id: user_id(),
+        entity: entity(),
+        from_date: from_date(),
+        to_date: to_date(),
+    });
+
+    let list: Resource<Result<Vec<AuditLogEntry>, ServerFnError>> = use_resource(
+        move || async move { search_audit_log(filter(), before_id(), PAGE_SIZE).await },
+    );
+
+    // Any change to the filters restarts paging from the newest entry.
+    let search = move |filter: AuditLogFilter| {
+        navigator().replace(Route::AuditLogList {
+            user_id: filter.user_id,
+            entity: filter.entity,
+            from_date: filter.from_date,
+            to_date: filter.to_date,
+            before_id: None,
+        });
+    };
+
+    let entity_text = entity().map(|e| e.to_string()).unwrap_or_default();
+
+    rsx! {
+        div { class: "ml-2 mr-2",
+            div { class: "flex flex-wrap gap-2 mb-2",
+                select {
+                    class: "select",
+                    onchange: move |e| {
+                        search(AuditLogFilter {
+                            user_id: e.value().parse().ok(),
+                            ..filter()
+                        });
+                    },
+                    option { value: "", selected: user_id().is_none(), "All users" }
+                    if let Some(Ok(users)) = users() {
+                        for u in users {
+                            option {
+                                value: "{u.id}",
+                                selected: user_id() == Some(u.id),
+                                {u.username}
+                            }
+                        }
+                    }
+                }
+                input {
+                    class: "input",
+                    r#type: "text",
+                    value: entity_text,
+                    placeholder: "e.g. contact-12",
+                    onchange: move |e| {
+                        search(AuditLogFilter {
+                            entity: e.value().parse().ok(),
+                            ..filter()
+                        });
+                    },
+                }
+                input {
+                    class: "input",
+                    r#type: "date",
+                    value: from_date().map(|d| d.to_string()).unwrap_or_default(),
+                    onchange: move |e| {
+                        search(AuditLogFilter {
+                            from_date: e.value().parse().ok(),
+                            ..filter()
+                        });
+                    },
+                }
+                input {
+                    class: "input",
+                    r#type: "date",
+                    value: to_date().map(|d| d.to_string()).unwrap_or_default(),
+                    onchange: move |e| {
+                        search(AuditLogFilter {
+                            to_date: e.value().parse().ok(),
+                            ..filter()
+                        });
+                    },
+                }
+            }
+        }
+
+        match list.read().deref() {
+            Some(Err(err)) => rsx! {
+                div { class: "alert alert-error",
+                    "Error loading audit log: "
+                    {err.to_string()}
+                }
+            },
+            Some(Ok(entries)) => {
+                let has_next = entries.len() > PAGE_SIZE as usize;
+                let visible = entries[..entries.len().min(PAGE_SIZE as usize)].to_vec();
+                let last_id = visible.last().map(|entry| entry.id);
+
+                rsx! {
+                    div { class: "ml-2 mr-2 sm:ml-0 sm:mr-0",
+                        AuditLogTable { entries: visible }
+
+                        // ── Pagination ───────────────────────────────────────
+                        div { class: "flex gap-4 mt-4 ml-2",
+                            if before_id().is_some() {
+                                NavButton {
+                                    on_click: move |_| { navigator().go_back(); },
+                                    "Previous"
+                                }
+                            }
+                            if has_next {
+                                if let Some(last_id) = last_id {
+                                    NavButton {
+                                        on_click: move |_| {
+                                            let filter = filter();
+                                            navigator().push(Route::AuditLogList {
+                                                user_id: filter.user_id,
+                                                entity: filter.entity,
+                                                from_date: filter.from_date,
+                                                to_date: filter.to_date,
+                                                before_id: Some(last_id),
+                                            });
+                                        },
+                                        "Next"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            None => rsx! {
+                p { class: "alert alert-info", "Loading..." }
+            },
+        }
+    }
+}
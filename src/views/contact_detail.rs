@@ -2,7 +2,7 @@
 
 use std::ops::Deref;
 
-use chrono::{DateTime, Local, Utc};
+use chrono::{DateTime, Local, NaiveDate, Utc};
 use dioxus::prelude::*;
 use dioxus_fullstack::ServerFnError;
 use tap::Pipe;
@@ -11,23 +11,61 @@ use crate::{
     Route,
     components::{
         Markdown,
+        audit_log::AuditLogSection,
         buttons::{ChangeButton, DeleteButton, NavButton},
-        contacts::{ActiveDialog, ContactDialog, ListDialogReference, Operation},
+        contacts::{
+            ActiveDialog, ContactDialog, ListDialogReference, Operation, TagBadges,
+            TemporaryActionCountdown,
+        },
+        dashboard::CallHeatmapChart,
+        notes::NoteRevisionsSection,
+        phone_calls::PhoneCallFilterBar,
+    },
+    functions::{
+        contacts::{
+            get_contact_by_id, get_contact_number_by_id, get_contact_numbers, restore_contact,
+        },
+        phone_calls::get_phone_calls_for_contact,
     },
-    functions::{contacts::get_contact_by_id, phone_calls::get_phone_calls_for_contact},
     models::{
-        contacts::{Contact, ContactId},
-        phone_calls::{PhoneCall, PhoneCallId},
+        audit_log::AuditEntityRef,
+        contacts::{Contact, ContactId, ContactSort},
+        phone_calls::{PhoneCall, PhoneCallFilter, PhoneCallId},
+        search::SearchQuery,
     },
     use_user,
 };
 
 const PAGE_SIZE: i64 = 50;
 
+fn detail_route(
+    contact_id: ContactId,
+    dialog: ListDialogReference,
+    filter: PhoneCallFilter,
+    before: Option<(DateTime<Utc>, PhoneCallId)>,
+) -> Route {
+    Route::ContactDetail {
+        contact_id,
+        dialog,
+        from_date: filter.from_date,
+        to_date: filter.to_date,
+        action: filter.action,
+        dest: filter.dest,
+        before_ts: before.map(|(ts, _)| ts),
+        before_id: before.map(|(_, id)| id),
+    }
+}
+
 #[component]
 fn CallRow(call: PhoneCall) -> Element {
+    let id = call.id;
+
     rsx! {
-        tr { class: "border-blue-300 mt-2 mb-2 p-2 border-2 w-full sm:w-auto sm:border-none inline-block sm:table-row",
+        tr {
+            class: "hover:bg-gray-500 cursor-pointer border-blue-300 mt-2 mb-2 p-2 border-2 w-full sm:w-auto sm:border-none inline-block sm:table-row",
+            onclick: move |_| {
+                navigator().push(Route::PhoneCallDetail { phone_call_id: id });
+            },
             td { class: "block sm:table-cell border-blue-300 sm:border-t-2",
                 {
                     call.inserted_at
@@ -38,7 +76,9 @@ fn CallRow(call: PhoneCall) -> Element {
             }
             td { class: "block sm:table-cell border-blue-300 sm:border-t-2",
                 span { class: "sm:hidden", "Source: " }
-                {call.source_number.clone()}
+                if let Some(source) = &call.source_number {
+                    {source.clone()}
+                }
             }
             td { class: "block sm:table-cell border-blue-300 sm:border-t-2",
                 span { class: "sm:hidden", "Destination: " }
@@ -58,6 +98,10 @@ fn CallRow(call: PhoneCall) -> Element {
 pub fn ContactDetail(
     contact_id: ContactId,
     dialog: ReadSignal<Option<ListDialogReference>>,
+    from_date: ReadSignal<Option<NaiveDate>>,
+    to_date: ReadSignal<Option<NaiveDate>>,
+    action: ReadSignal<Option<String>>,
+    dest: ReadSignal<Option<String>>,
     before_ts: ReadSignal<Option<DateTime<Utc>>>,
     before_id: ReadSignal<Option<PhoneCallId>>,
 ) -> Element {
@@ -71,6 +115,12 @@ pub fn ContactDetail(
     let mut contact_resource =
         use_resource(move || async move { get_contact_by_id(contact_id).await });
 
+    let mut numbers_resource =
+        use_resource(move || async move { get_contact_numbers(contact_id).await });
+
+    let mut history_version = use_signal(|| 0u32);
+    let mut restore_error: Signal<Option<String>> = use_signal(|| None);
+
     // Resolve dialog state from URL param, loading the contact if needed.
     let dialog_resource: Resource<Result<ActiveDialog, ServerFnError>> =
         use_resource(move || async move {
@@ -84,20 +134,49 @@ pub fn ContactDetail(
                         .ok_or(ServerFnError::new("Cannot find contact"))?;
                     ActiveDialog::Change(Operation::Update { contact }).pipe(Ok)
                 }
-                ListDialogReference::Delete { contact_id } => {
+                ListDialogReference::Archive { contact_id } => {
                     let contact = get_contact_by_id(contact_id)
                         .await?
                         .ok_or(ServerFnError::new("Cannot find contact"))?;
-                    ActiveDialog::Delete(contact).pipe(Ok)
+                    ActiveDialog::Archive(contact).pipe(Ok)
                 }
-                ListDialogReference::Create | ListDialogReference::Idle => Ok(ActiveDialog::Idle),
+                ListDialogReference::CreateNumber { contact_id } => {
+                    let contact = get_contact_by_id(contact_id)
+                        .await?
+                        .ok_or(ServerFnError::new("Cannot find contact"))?;
+                    ActiveDialog::CreateNumber(contact).pipe(Ok)
+                }
+                ListDialogReference::DeleteNumber { contact_number_id } => {
+                    let number = get_contact_number_by_id(contact_number_id)
+                        .await?
+                        .ok_or(ServerFnError::new("Cannot find phone number"))?;
+                    let contact = get_contact_by_id(number.contact_id)
+                        .await?
+                        .ok_or(ServerFnError::new("Cannot find contact"))?;
+                    ActiveDialog::DeleteNumber(contact, number).pipe(Ok)
+                }
+                ListDialogReference::Create
+                | ListDialogReference::Merge { .. }
+                | ListDialogReference::Bulk { .. }
+                | ListDialogReference::Import
+                | ListDialogReference::Export
+                | ListDialogReference::Idle => Ok(ActiveDialog::Idle),
             }
         });
 
+    let filter = use_memo(move || PhoneCallFilter {
+        from_date: from_date(),
+        to_date: to_date(),
+        action: action(),
+        dest: dest(),
+        utc_offset_seconds: Local::now().offset().local_minus_utc(),
+    });
+
     // Fetch one extra row to detect whether a next page exists.
     let calls_resource: Resource<Result<Vec<PhoneCall>, ServerFnError>> =
         use_resource(move || async move {
-            get_phone_calls_for_contact(contact_id, before_ts(), before_id(), PAGE_SIZE).await
+            get_phone_calls_for_contact(contact_id, filter(), before_ts(), before_id(), PAGE_SIZE)
+                .await
         });
 
     rsx! {
@@ -111,6 +190,45 @@ pub fn ContactDetail(
                                 th { class: "pr-4 text-left", "Phone Number" }
                                 td { {contact.phone_number.clone()} }
                             }
+                            tr {
+                                th { class: "pr-4 text-left", "Other Numbers" }
+                                td {
+                                    match numbers_resource() {
+                                        Some(Ok(numbers)) => rsx! {
+                                            for number in numbers {
+                                                div { class: "flex gap-2 items-center",
+                                                    span { {number.phone_number.clone()} }
+                                                    if let Some(label) = &number.label {
+                                                        span { class: "badge", {label.clone()} }
+                                                    }
+                                                    DeleteButton {
+                                                        on_click: move |_| {
+                                                            navigator()
+                                                                .push(
+                                                                    detail_route(
+                                                                        contact_id,
+                                                                        ListDialogReference::DeleteNumber {
+                                                                            contact_number_id: number.id,
+                                                                        },
+                                                                        filter(),
+                                                                        before_ts().zip(before_id()),
+                                                                    ),
+                                                                );
+                                                        },
+                                                        "Remove"
+                                                    }
+                                                }
+                                            }
+                                        },
+                                        Some(Err(err)) => rsx! {
+                                            span { class: "alert alert-error", {err.to_string()} }
+                                        },
+                                        None => rsx! {
+                                            span { "Loading..." }
+                                        },
+                                    }
+                                }
+                            }
                             tr {
                                 th { class: "pr-4 text-left", "Name" }
                                 td {
@@ -121,7 +239,28 @@ pub fn ContactDetail(
                             }
                             tr {
                                 th { class: "pr-4 text-left", "Action" }
-                                td { {contact.action.clone()} }
+                                td {
+                                    {contact.action.clone()}
+                                    if contact.effective_action() != contact.current_action_at(Utc::now()) {
+                                        {format!(" (overridden by tag: {})", contact.effective_action())}
+                                    }
+                                }
+                            }
+                            if let Some(temporary) = contact.active_temporary_action(Utc::now()) {
+                                tr {
+                                    th { class: "pr-4 text-left", "Temporary Action" }
+                                    td {
+                                        {temporary.action.clone()}
+                                        " "
+                                        TemporaryActionCountdown { expires_at: temporary.expires_at }
+                                    }
+                                }
+                            }
+                            tr {
+                                th { class: "pr-4 text-left", "Tags" }
+                                td {
+                                    TagBadges { tags: contact.tags.clone() }
+                                }
                             }
                             tr {
                                 th { class: "pr-4 text-left", "Comments" }
@@ -135,40 +274,117 @@ pub fn ContactDetail(
                                 th { class: "pr-4 text-left", "Total Calls" }
                                 td { {contact.phone_call_count.to_string()} }
                             }
+                            tr {
+                                th { class: "pr-4 text-left", "First Call" }
+                                td {
+                                    if let Some(first_call_at) = contact.first_call_at {
+                                        {first_call_at.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()}
+                                    }
+                                }
+                            }
+                            tr {
+                                th { class: "pr-4 text-left", "Last Call" }
+                                td {
+                                    if let Some(last_call_at) = contact.last_call_at {
+                                        {last_call_at.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()}
+                                    }
+                                }
+                            }
                         }
                     }
 
-                    div { class: "flex flex-wrap gap-2",
-                        ChangeButton {
-                            on_click: move |_| {
-                                navigator().push(Route::ContactDetail {
-                                    contact_id,
-                                    dialog: ListDialogReference::Update { contact_id },
-                                    before_ts: before_ts(),
-                                    before_id: before_id(),
-                                });
-                            },
-                            "Edit"
+                    if let Some(archived_at) = contact.archived_at {
+                        div { class: "alert alert-warning mb-4",
+                            {format!(
+                                "Archived on {}. It is not used to match calls until restored.",
+                                archived_at.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+                            )}
                         }
-                        DeleteButton {
-                            on_click: move |_| {
-                                navigator().push(Route::ContactDetail {
-                                    contact_id,
-                                    dialog: ListDialogReference::Delete { contact_id },
-                                    before_ts: before_ts(),
-                                    before_id: before_id(),
-                                });
-                            },
-                            "Delete"
+                    }
+                    if let Some(err) = restore_error() {
+                        div { class: "alert alert-error mb-4", "Error: " {err} }
+                    }
+
+                    div { class: "flex flex-wrap gap-2",
+                        if contact.archived_at.is_some() {
+                            ChangeButton {
+                                on_click: {
+                                    let contact = contact.clone();
+                                    move |_| {
+                                        let contact = contact.clone();
+                                        spawn(async move {
+                                            match restore_contact(contact).await {
+                                                Ok(_) => {
+                                                    restore_error.set(None);
+                                                    contact_resource.restart();
+                                                    history_version += 1;
+                                                }
+                                                Err(err) => restore_error.set(Some(err.to_string())),
+                                            }
+                                        });
+                                    }
+                                },
+                                "Restore"
+                            }
+                        } else {
+                            ChangeButton {
+                                on_click: move |_| {
+                                    navigator()
+                                        .push(
+                                            detail_route(
+                                                contact_id,
+                                                ListDialogReference::Update { contact_id },
+                                                filter(),
+                                                before_ts().zip(before_id()),
+                                            ),
+                                        );
+                                },
+                                "Edit"
+                            }
+                            DeleteButton {
+                                on_click: move |_| {
+                                    navigator()
+                                        .push(
+                                            detail_route(
+                                                contact_id,
+                                                ListDialogReference::Archive { contact_id },
+                                                filter(),
+                                                before_ts().zip(before_id()),
+                                            ),
+                                        );
+                                },
+                                "Archive"
+                            }
+                            ChangeButton {
+                                on_click: move |_| {
+                                    navigator()
+                                        .push(
+                                            detail_route(
+                                                contact_id,
+                                                ListDialogReference::CreateNumber {
+                                                    contact_id,
+                                                },
+                                                filter(),
+                                                before_ts().zip(before_id()),
+                                            ),
+                                        );
+                                },
+                                "Add Number"
+                            }
                         }
                         NavButton {
                             on_click: move |_| {
                                 navigator().push(Route::ContactList {
                                     dialog: ListDialogReference::Idle,
-                                    q: String::new(),
+                                    q: SearchQuery::default(),
+                                    tag: String::new(),
+                                    sort: ContactSort::default(),
                                     before_id: None,
                                     before_name: None,
                                     before_name_null: false,
+                                    before_count: None,
+                                    before_ts: None,
+                                    before_phone_number: None,
                                 });
                             },
                             "Back to Contacts"
@@ -188,6 +404,16 @@ pub fn ContactDetail(
         }
 
         // ── Call history table ───────────────────────────────────────────────
+        div { class: "ml-2 mr-2 sm:ml-0 sm:mr-0",
+            PhoneCallFilterBar {
+                filter: filter(),
+                on_change: move |filter| {
+                    // Any change to the filters restarts paging from the newest call.
+                    navigator()
+                        .replace(detail_route(contact_id, ListDialogReference::Idle, filter, None));
+                },
+            }
+        }
         match calls_resource.read().deref() {
             None => rsx! {
                 p { class: "alert alert-info", "Loading calls..." }
@@ -236,12 +462,15 @@ pub fn ContactDetail(
                                                 let ts = last.inserted_at;
                                                 let id = last.id;
                                                 move |_| {
-                                                    navigator().push(Route::ContactDetail {
-                                                        contact_id,
-                                                        dialog: ListDialogReference::Idle,
-                                                        before_ts: Some(ts),
-                                                        before_id: Some(id),
-                                                    });
+                                                    navigator()
+                                                        .push(
+                                                            detail_route(
+                                                                contact_id,
+                                                                ListDialogReference::Idle,
+                                                                filter(),
+                                                                Some((ts, id)),
+                                                            ),
+                                                        );
                                                 }
                                             },
                                             "Next"
@@ -255,6 +484,30 @@ pub fn ContactDetail(
             },
         }
 
+        // ── Call heatmap ─────────────────────────────────────────────────────
+        div { class: "ml-2 mr-2 mt-4 sm:ml-0 sm:mr-0",
+            h2 { class: "text-lg font-bold", "Calls by hour of the week" }
+            CallHeatmapChart { contact_id: Some(contact_id) }
+        }
+
+        // ── Notes history ────────────────────────────────────────────────────
+        NoteRevisionsSection {
+            key: "{contact_id}",
+            contact_id,
+            version: history_version,
+            on_restore: move |_contact: Contact| {
+                contact_resource.restart();
+                history_version += 1;
+            },
+        }
+
+        // ── Change history ───────────────────────────────────────────────────
+        AuditLogSection {
+            key: "{contact_id}",
+            entity: AuditEntityRef::from(contact_id),
+            version: history_version,
+        }
+
         // ── Edit / Delete dialog ─────────────────────────────────────────────
         match dialog_resource.read().deref() {
             Some(Err(err)) => rsx! {
@@ -269,30 +522,42 @@ pub fn ContactDetail(
                     on_change: move |contact: Contact| {
                         // Refresh the detail card with the updated data.
                         contact_resource.restart();
-                        navigator().push(Route::ContactDetail {
-                            contact_id: contact.id,
-                            dialog: ListDialogReference::Idle,
-                            before_ts: before_ts(),
-                            before_id: before_id(),
-                        });
+                        numbers_resource.restart();
+                        history_version += 1;
+                        navigator()
+                            .push(
+                                detail_route(
+                                    contact.id,
+                                    ListDialogReference::Idle,
+                                    filter(),
+                                    before_ts().zip(before_id()),
+                                ),
+                            );
                     },
-                    on_delete: move |_contact| {
-                        // Contact is gone — navigate back to the list.
-                        navigator().push(Route::ContactList {
-                            dialog: ListDialogReference::Idle,
-                            q: String::new(),
-                            before_id: None,
-                            before_name: None,
-                            before_name_null: false,
-                        });
+                    on_archive: move |_contact| {
+                        // Stay on the contact so the archive can be undone.
+                        contact_resource.restart();
+                        history_version += 1;
+                        navigator()
+                            .push(
+                                detail_route(
+                                    contact_id,
+                                    ListDialogReference::Idle,
+                                    filter(),
+                                    before_ts().zip(before_id()),
+                                ),
+                            );
                     },
                     on_close: move |()| {
-                        navigator().push(Route::ContactDetail {
-                            contact_id,
-                            dialog: ListDialogReference::Idle,
-                            before_ts: before_ts(),
-                            before_id: before_id(),
-                        });
+                        navigator()
+                            .push(
+                                detail_route(
+                                    contact_id,
+                                    ListDialogReference::Idle,
+                                    filter(),
+                                    before_ts().zip(before_id()),
+                                ),
+                            );
                     },
                 }
             },
@@ -12,23 +12,73 @@ use crate::{
     components::{
         Markdown,
         buttons::{ChangeButton, CreateButton, NavButton},
-        contacts::{ActiveDialog, ContactDialog, ListDialogReference, Operation},
+        contacts::{
+            ActiveDialog, ContactAction, ContactDialog, ListDialogReference, Operation, TagBadges,
+        },
+    },
+    functions::{
+        contacts::{
+            get_contact_by_id, get_contacts_by_ids, preview_merge_contacts,
+            search_contacts_paginated,
+        },
+        tags::get_all_tags,
+    },
+    models::{
+        contacts::{Contact, ContactCursor, ContactId, ContactSort},
+        search::SearchQuery,
     },
-    functions::contacts::{get_contact_by_id, search_contacts_paginated},
-    models::contacts::{Contact, ContactId},
     use_user,
 };
 
 const PAGE_SIZE: i64 = 50;
 
+/// The list page after `last`, carrying its sort key.
+fn next_page(q: SearchQuery, tag: String, sort: ContactSort, last: &Contact) -> Route {
+    let mut before_name = None;
+    let mut before_name_null = false;
+    let mut before_count = None;
+    let mut before_ts = None;
+    let mut before_phone_number = None;
+
+    match ContactCursor::after(sort, last) {
+        ContactCursor::Name(name, _) => {
+            before_name_null = name.is_none();
+            before_name = name;
+        }
+        ContactCursor::CallCount(count, _) => before_count = Some(count),
+        ContactCursor::LastCall(ts, _) => before_ts = ts,
+        ContactCursor::Updated(ts, _) => before_ts = Some(ts),
+        ContactCursor::PhoneNumber(phone_number, _) => before_phone_number = Some(phone_number),
+    }
+
+    Route::ContactList {
+        dialog: ListDialogReference::Idle,
+        q,
+        tag,
+        sort,
+        before_id: Some(last.id),
+        before_name,
+        before_name_null,
+        before_count,
+        before_ts,
+        before_phone_number,
+    }
+}
+
 #[component]
 fn EntryRow(
     contact: Contact,
     selected: Signal<Option<ContactId>>,
-    q: String,
+    mut checked: Signal<Vec<ContactId>>,
+    q: SearchQuery,
+    tag: String,
+    sort: ContactSort,
     before_id: Option<ContactId>,
     before_name: Option<String>,
     before_name_null: bool,
+    before_count: Option<i64>,
+    before_ts: Option<chrono::DateTime<chrono::Utc>>,
+    before_phone_number: Option<String>,
 ) -> Element {
     let id = contact.id;
     let navigator = navigator();
@@ -37,6 +87,22 @@ fn EntryRow(
         tr {
             class: "hover:bg-gray-500 border-blue-300 mt-2 mb-2 p-2 border-2 w-full sm:w-auto sm:border-none inline-block sm:table-row",
             onclick: move |_| { selected.set(Some(id)) },
+            td {
+                class: "block sm:table-cell border-blue-300 sm:border-t-2",
+                onclick: move |e| e.stop_propagation(),
+                input {
+                    r#type: "checkbox",
+                    class: "checkbox",
+                    checked: checked.read().contains(&id),
+                    oninput: move |e| {
+                        if e.checked() {
+                            checked.write().push(id);
+                        } else {
+                            checked.write().retain(|x| *x != id);
+                        }
+                    },
+                }
+            }
             td { class: "block sm:table-cell border-blue-300 sm:border-t-2",
                 {contact.phone_number.clone()}
             }
@@ -47,7 +113,10 @@ fn EntryRow(
             }
             td { class: "block sm:table-cell border-blue-300 sm:border-t-2",
                 span { class: "sm:hidden", "Action: " }
-                {contact.action.clone()}
+                ContactAction { contact: contact.clone() }
+            }
+            td { class: "block sm:table-cell border-blue-300 sm:border-t-2",
+                TagBadges { tags: contact.tags.clone() }
             }
             td { class: "block sm:table-cell border-blue-300 sm:border-t-2",
                 if let Some(comments) = &contact.comments {
@@ -62,7 +131,7 @@ fn EntryRow(
 
         if selected() == Some(id) {
             tr {
-                td { colspan: "6", class: "block sm:table-cell",
+                td { colspan: "8", class: "block sm:table-cell",
                     div { class: "flex gap-2",
                         NavButton {
                             on_click: move |_| {
@@ -70,6 +139,10 @@ fn EntryRow(
                                     .push(Route::ContactDetail {
                                         contact_id: id,
                                         dialog: ListDialogReference::Idle,
+                                        from_date: None,
+                                        to_date: None,
+                                        action: None,
+                                        dest: None,
                                         before_ts: None,
                                         before_id: None,
                                     });
@@ -79,7 +152,9 @@ fn EntryRow(
                         ChangeButton {
                             on_click: {
                                 let q = q.clone();
+                                let tag = tag.clone();
                                 let before_name = before_name.clone();
+                                let before_phone_number = before_phone_number.clone();
                                 move |_| {
                                     navigator
                                         .push(Route::ContactList {
@@ -87,9 +162,14 @@ fn EntryRow(
                                                 contact_id: id,
                                             },
                                             q: q.clone(),
+                                            tag: tag.clone(),
+                                            sort,
                                             before_id,
                                             before_name: before_name.clone(),
                                             before_name_null,
+                                            before_count,
+                                            before_ts,
+                                            before_phone_number: before_phone_number.clone(),
                                         });
                                 }
                             },
@@ -98,21 +178,28 @@ fn EntryRow(
                         ChangeButton {
                             on_click: {
                                 let q = q.clone();
+                                let tag = tag.clone();
                                 let before_name = before_name.clone();
+                                let before_phone_number = before_phone_number.clone();
                                 move |_| {
                                     navigator
                                         .push(Route::ContactList {
-                                            dialog: ListDialogReference::Delete {
+                                            dialog: ListDialogReference::Archive {
                                                 contact_id: id,
                                             },
                                             q: q.clone(),
+                                            tag: tag.clone(),
+                                            sort,
                                             before_id,
                                             before_name: before_name.clone(),
                                             before_name_null,
+                                            before_count,
+                                            before_ts,
+                                            before_phone_number: before_phone_number.clone(),
                                         });
                                 }
                             },
-                            "Delete"
+                            "Archive"
                         }
                     }
                 }
@@ -124,10 +211,15 @@ fn EntryRow(
 #[component]
 pub fn ContactList(
     dialog: ReadSignal<Option<ListDialogReference>>,
-    q: ReadSignal<String>,
+    q: ReadSignal<SearchQuery>,
+    tag: ReadSignal<String>,
+    sort: ReadSignal<ContactSort>,
     before_id: ReadSignal<Option<ContactId>>,
     before_name: ReadSignal<Option<String>>,
     before_name_null: ReadSignal<bool>,
+    before_count: ReadSignal<Option<i64>>,
+    before_ts: ReadSignal<Option<chrono::DateTime<chrono::Utc>>>,
+    before_phone_number: ReadSignal<Option<String>>,
 ) -> Element {
     let user = use_user().ok().flatten();
 
@@ -138,6 +230,7 @@ pub fn ContactList(
     };
 
     let selected: Signal<Option<ContactId>> = use_signal(|| None);
+    let mut checked: Signal<Vec<ContactId>> = use_signal(Vec::new);
 
     let dialog_resource: Resource<Result<ActiveDialog, ServerFnError>> =
         use_resource(move || async move {
@@ -152,64 +245,238 @@ pub fn ContactList(
                         .ok_or(ServerFnError::new("Cannot find contact"))?;
                     ActiveDialog::Change(Operation::Update { contact }).pipe(Ok)
                 }
-                ListDialogReference::Delete { contact_id } => {
+                ListDialogReference::Archive { contact_id } => {
                     let contact = get_contact_by_id(contact_id)
                         .await?
                         .ok_or(ServerFnError::new("Cannot find contact"))?;
-                    ActiveDialog::Delete(contact).pipe(Ok)
+                    ActiveDialog::Archive(contact).pipe(Ok)
                 }
-                ListDialogReference::Idle => Ok(ActiveDialog::Idle),
+                ListDialogReference::Merge { contact_ids } => {
+                    let preview = preview_merge_contacts(contact_ids).await?;
+                    ActiveDialog::Merge(preview).pipe(Ok)
+                }
+                ListDialogReference::Bulk { contact_ids } => {
+                    let contacts = get_contacts_by_ids(contact_ids).await?;
+                    ActiveDialog::Bulk(contacts).pipe(Ok)
+                }
+                ListDialogReference::Import => Ok(ActiveDialog::Import),
+                ListDialogReference::Export => {
+                    let tag = Some(tag()).filter(|t| !t.is_empty());
+                    Ok(ActiveDialog::Export { query: q(), tag })
+                }
+                ListDialogReference::CreateNumber { .. }
+                | ListDialogReference::DeleteNumber { .. }
+                | ListDialogReference::Idle => Ok(ActiveDialog::Idle),
             }
         });
 
+    let all_tags = use_resource(move || async move { get_all_tags().await });
+
     // Fetch PAGE_SIZE + 1 rows so we can detect whether a next page exists.
     let mut list: Resource<Result<Vec<Contact>, ServerFnError>> =
         use_resource(move || async move {
-            search_contacts_paginated(
-                q(),
+            let tag = Some(tag()).filter(|t| !t.is_empty());
+            let cursor = ContactCursor::from_route(
+                sort(),
                 before_id(),
                 before_name(),
                 before_name_null(),
-                PAGE_SIZE,
+                before_count(),
+                before_ts(),
+                before_phone_number(),
             )
-            .await
+            .map_err(|err| ServerFnError::new(err.to_string()))?;
+            search_contacts_paginated(q(), tag, sort(), cursor, PAGE_SIZE).await
         });
 
     rsx! {
         div { class: "ml-2 mr-2",
-            div { class: "mb-2",
+            div { class: "mb-2 flex flex-wrap gap-2",
                 CreateButton {
                     on_click: move |_| {
                         navigator().push(Route::ContactList {
                             dialog: ListDialogReference::Create,
                             q: q(),
+                            tag: tag(),
+                            sort: sort(),
                             before_id: before_id(),
                             before_name: before_name(),
                             before_name_null: before_name_null(),
+                            before_count: before_count(),
+                            before_ts: before_ts(),
+                            before_phone_number: before_phone_number(),
                         });
                     },
                     "Create"
                 }
+                NavButton {
+                    on_click: move |_| {
+                        navigator().push(Route::ContactList {
+                            dialog: ListDialogReference::Import,
+                            q: q(),
+                            tag: tag(),
+                            sort: sort(),
+                            before_id: before_id(),
+                            before_name: before_name(),
+                            before_name_null: before_name_null(),
+                            before_count: before_count(),
+                            before_ts: before_ts(),
+                            before_phone_number: before_phone_number(),
+                        });
+                    },
+                    "Import"
+                }
+                NavButton {
+                    on_click: move |_| {
+                        navigator().push(Route::ContactList {
+                            dialog: ListDialogReference::Export,
+                            q: q(),
+                            tag: tag(),
+                            sort: sort(),
+                            before_id: before_id(),
+                            before_name: before_name(),
+                            before_name_null: before_name_null(),
+                            before_count: before_count(),
+                            before_ts: before_ts(),
+                            before_phone_number: before_phone_number(),
+                        });
+                    },
+                    "Export"
+                }
+            }
+
+            if !checked.read().is_empty() {
+                div { class: "mb-2 flex flex-wrap gap-2 items-center",
+                    span { {format!("{} selected", checked.read().len())} }
+                    ChangeButton {
+                        on_click: move |_| {
+                            navigator().push(Route::ContactList {
+                                dialog: ListDialogReference::Bulk {
+                                    contact_ids: checked(),
+                                },
+                                q: q(),
+                                tag: tag(),
+                                sort: sort(),
+                                before_id: before_id(),
+                                before_name: before_name(),
+                                before_name_null: before_name_null(),
+                                before_count: before_count(),
+                                before_ts: before_ts(),
+                                before_phone_number: before_phone_number(),
+                            });
+                        },
+                        "Bulk edit"
+                    }
+                    if checked.read().len() >= 2 {
+                        ChangeButton {
+                            on_click: move |_| {
+                                navigator().push(Route::ContactList {
+                                    dialog: ListDialogReference::Merge {
+                                        contact_ids: checked(),
+                                    },
+                                    q: q(),
+                                    tag: tag(),
+                                    sort: sort(),
+                                    before_id: before_id(),
+                                    before_name: before_name(),
+                                    before_name_null: before_name_null(),
+                                    before_count: before_count(),
+                                    before_ts: before_ts(),
+                                    before_phone_number: before_phone_number(),
+                                });
+                            },
+                            "Merge"
+                        }
+                    }
+                    NavButton { on_click: move |_| checked.set(Vec::new()), "Clear" }
+                }
             }
 
             div { class: "mb-2",
                 input {
                     class: "form-control",
                     r#type: "text",
-                    value: q(),
+                    value: q().to_string(),
                     oninput: move |e| {
                         // Reset cursor when query changes; replace so typing
                         // doesn't pile up in the back stack.
                         navigator().replace(Route::ContactList {
                             dialog: ListDialogReference::Idle,
-                            q: e.value(),
+                            q: SearchQuery::from(e.value()),
+                            tag: tag(),
+                            sort: sort(),
                             before_id: None,
                             before_name: None,
                             before_name_null: false,
+                            before_count: None,
+                            before_ts: None,
+                            before_phone_number: None,
                         });
                     },
                     placeholder: "Search...",
                 }
+                p { class: "text-sm opacity-70",
+                    "Filters: action:voicemail name:none calls:>5 tag:family after:2026-01-01 before:2026-02-01 dest:0299998888"
+                }
+                if let Err(err) = q().terms() {
+                    p { class: "alert alert-warning", {err.to_string()} }
+                }
+            }
+
+            div { class: "mb-2 flex flex-wrap gap-2",
+                select {
+                    class: "select",
+                    value: tag(),
+                    onchange: move |e| {
+                        navigator().replace(Route::ContactList {
+                            dialog: ListDialogReference::Idle,
+                            q: q(),
+                            tag: e.value(),
+                            sort: sort(),
+                            before_id: None,
+                            before_name: None,
+                            before_name_null: false,
+                            before_count: None,
+                            before_ts: None,
+                            before_phone_number: None,
+                        });
+                    },
+                    option { value: "", "All tags" }
+                    if let Some(Ok(tags)) = all_tags() {
+                        for t in tags {
+                            option {
+                                value: t.name.clone(),
+                                selected: t.name == tag(),
+                                {t.name.clone()}
+                            }
+                        }
+                    }
+                }
+                select {
+                    class: "select",
+                    value: sort().to_string(),
+                    onchange: move |e| {
+                        navigator().replace(Route::ContactList {
+                            dialog: ListDialogReference::Idle,
+                            q: q(),
+                            tag: tag(),
+                            sort: e.value().parse().unwrap_or_default(),
+                            before_id: None,
+                            before_name: None,
+                            before_name_null: false,
+                            before_count: None,
+                            before_ts: None,
+                            before_phone_number: None,
+                        });
+                    },
+                    for s in ContactSort::ALL {
+                        option {
+                            value: s.to_string(),
+                            selected: s == sort(),
+                            {s.title()}
+                        }
+                    }
+                }
             }
         }
 
@@ -234,9 +501,28 @@ pub fn ContactList(
                             table { class: "block sm:table",
                                 thead { class: "hidden sm:table-header-group",
                                     tr {
+                                        th {
+                                            input {
+                                                r#type: "checkbox",
+                                                class: "checkbox",
+                                                title: "Select all on this page",
+                                                checked: visible.iter().all(|c| checked.read().contains(&c.id)),
+                                                oninput: {
+                                                    let ids: Vec<ContactId> = visible.iter().map(|c| c.id).collect();
+                                                    move |e: FormEvent| {
+                                                        let mut checked = checked.write();
+                                                        checked.retain(|x| !ids.contains(x));
+                                                        if e.checked() {
+                                                            checked.extend(ids.iter().copied());
+                                                        }
+                                                    }
+                                                },
+                                            }
+                                        }
                                         th { "Phone Number" }
                                         th { "Name" }
                                         th { "Actions" }
+                                        th { "Tags" }
                                         th { "Comments" }
                                         th { "Calls" }
                                     }
@@ -248,10 +534,16 @@ pub fn ContactList(
                                             // key: "{contact.id}",
                                             contact: contact.clone(),
                                             selected,
+                                            checked,
                                             q: q(),
+                                            tag: tag(),
+                                            sort: sort(),
                                             before_id: before_id(),
                                             before_name: before_name(),
                                             before_name_null: before_name_null(),
+                                            before_count: before_count(),
+                                            before_ts: before_ts(),
+                                            before_phone_number: before_phone_number(),
                                         }
                                     }
                                 }
@@ -269,18 +561,9 @@ pub fn ContactList(
                                     if let Some(last) = visible.last() {
                                         NavButton {
                                             on_click: {
-                                                let last_id = last.id;
-                                                let last_name = last.name.clone();
-                                                let last_name_null = last.name.is_none();
-                                                let q_val = q();
+                                                let route = next_page(q(), tag(), sort(), last);
                                                 move |_| {
-                                                    navigator().push(Route::ContactList {
-                                                        dialog: ListDialogReference::Idle,
-                                                        q: q_val.clone(),
-                                                        before_id: Some(last_id),
-                                                        before_name: last_name.clone(),
-                                                        before_name_null: last_name_null,
-                                                    });
+                                                    navigator().push(route.clone());
                                                 }
                                             },
                                             "Next"
@@ -309,16 +592,31 @@ pub fn ContactList(
             Some(Ok(dialog)) => rsx! {
                 ContactDialog {
                     dialog: dialog.clone(),
-                    on_change: move |_contact: Contact| { list.restart() },
-                    on_delete: move |_contact| list.restart(),
+                    on_change: move |_contact: Contact| {
+                        checked.set(Vec::new());
+                        list.restart();
+                    },
+                    on_archive: move |contact: Contact| {
+                        checked.write().retain(|x| *x != contact.id);
+                        list.restart();
+                    },
+                    on_bulk_change: move |_count: usize| {
+                        checked.set(Vec::new());
+                        list.restart();
+                    },
                     on_close: move |()| {
                         navigator()
                             .push(Route::ContactList {
                                 dialog: ListDialogReference::Idle,
                                 q: q(),
+                                tag: tag(),
+                                sort: sort(),
                                 before_id: before_id(),
                                 before_name: before_name(),
                                 before_name_null: before_name_null(),
+                                before_count: before_count(),
+                                before_ts: before_ts(),
+                                before_phone_number: before_phone_number(),
                             });
                     },
                 }
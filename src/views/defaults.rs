@@ -63,10 +63,10 @@ fn EntryRow(default: Default, selected: Signal<Option<DefaultId>>) -> Element {
                             on_click: move |_| {
                                 navigator
                                     .push(Route::DefaultList {
-                                        dialog: ListDialogReference::Delete { default_id: id },
+                                        dialog: ListDialogReference::Archive { default_id: id },
                                     });
                             },
-                            "Delete"
+                            "Archive"
                         }
                     }
                 }
@@ -101,11 +101,11 @@ pub fn DefaultList(dialog: ReadSignal<Option<ListDialogReference>>) -> Element {
                     .ok_or(ServerFnError::new("Cannot find default"))?;
                 ActiveDialog::Change(Operation::Update { default }).pipe(Ok)
             }
-            ListDialogReference::Delete { default_id } => {
+            ListDialogReference::Archive { default_id } => {
                 let default = get_default_by_id(default_id)
                     .await?
                     .ok_or(ServerFnError::new("Cannot find default"))?;
-                ActiveDialog::Delete(default).pipe(Ok)
+                ActiveDialog::Archive(default).pipe(Ok)
             }
             ListDialogReference::Idle => Ok(ActiveDialog::Idle),
         }
@@ -190,7 +190,7 @@ pub fn DefaultList(dialog: ReadSignal<Option<ListDialogReference>>) -> Element {
                 DefaultDialog {
                     dialog: dialog.clone(),
                     on_change: move |_default: Default| { list.restart() },
-                    on_delete: move |_default| list.restart(),
+                    on_archive: move |_default| list.restart(),
                     on_close: move |()| {
                         navigator
                             .push(Route::DefaultList {
@@ -0,0 +1,22 @@
+use dioxus::prelude::*;
+
+use crate::{components::dashboard::CallHeatmapChart, use_user};
+
+/// When calls come in, over every call.
+#[component]
+pub fn Heatmap() -> Element {
+    let user = use_user().ok().flatten();
+
+    if user.is_none() {
+        return rsx! {
+            p { class: "alert alert-error", "You are not logged in." }
+        };
+    }
+
+    rsx! {
+        div { class: "ml-2 mr-2",
+            h1 { class: "text-lg font-bold", "Calls by hour of the week" }
+            CallHeatmapChart { contact_id: None }
+        }
+    }
+}
@@ -1,7 +1,11 @@
 use dioxus::prelude::*;
 use dioxus_router::navigator;
 
-use crate::{Route, components::buttons::NavButton, use_user};
+use crate::{
+    Route,
+    components::{buttons::NavButton, dashboard::Dashboard},
+    use_user,
+};
 
 #[component]
 pub fn Home() -> Element {
@@ -11,11 +15,10 @@ pub fn Home() -> Element {
     rsx! {
         div {
             h1 { class: "text-green-500", "Welcome to Penguin Phone DB" }
-            p { "This is a  web application written in Rust using the Dioxus framework." }
-            p { "Do not eat." }
 
             if let Some(user) = user {
-                p { class: "text-green-300", "Welcome, {user.full_name}!" }
+                p { class: "text-green-300 mb-4", "Welcome, {user.full_name}!" }
+                Dashboard {}
             } else {
                 p { class: "text-red-600", "Please log in to continue." }
                 NavButton {
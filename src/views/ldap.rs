@@ -0,0 +1,105 @@
+use dioxus::prelude::*;
+use dioxus_fullstack::ServerFnError;
+
+use crate::{
+    components::buttons::{ActionButton, ChangeButton},
+    functions::ldap::reconcile_ldap,
+    models::ldap::LdapReconcileReport,
+    use_user,
+};
+
+/// Check LDAP against the contacts, and fix any differences on request.
+#[component]
+pub fn LdapReconcile() -> Element {
+    let user = use_user().ok().flatten();
+    let Some(user) = user.as_ref() else {
+        return rsx! {
+            p { class: "alert alert-error", "You are not logged in." }
+        };
+    };
+    if !user.is_admin {
+        return rsx! {
+            p { class: "alert alert-error", "You are not an admin." }
+        };
+    }
+
+    let mut running = use_signal(|| false);
+    let mut report: Signal<Option<Result<LdapReconcileReport, ServerFnError>>> =
+        use_signal(|| None);
+
+    let mut run = move |fix: bool| {
+        if running() {
+            return;
+        }
+        spawn(async move {
+            running.set(true);
+            report.set(Some(reconcile_ldap(fix).await));
+            running.set(false);
+        });
+    };
+
+    rsx! {
+        div { class: "ml-2 mr-2",
+            h1 { class: "text-lg font-bold", "LDAP reconciliation" }
+            p { class: "mb-4",
+                "Compares every contact with the person entries in LDAP. Fixing adds missing entries, updates mismatched ones and deletes entries without a contact."
+            }
+            div { class: "flex gap-2 mb-4",
+                ActionButton { on_click: move |()| run(false), "Check" }
+                ChangeButton { on_click: move |()| run(true), "Fix" }
+            }
+
+            if running() {
+                p { class: "alert alert-info", "Running..." }
+            }
+
+            match report() {
+                None => rsx! {},
+                Some(Err(err)) => rsx! {
+                    div { class: "alert alert-error", "Error: " {err.to_string()} }
+                },
+                Some(Ok(report)) => rsx! {
+                    p { class: "mb-2", {report.to_string()} }
+                    if report.drift.is_empty() {
+                        p { class: "alert alert-success", "LDAP matches the contacts." }
+                    } else {
+                        table { class: "w-full",
+                            thead {
+                                tr {
+                                    th { "Kind" }
+                                    th { "Contact" }
+                                    th { "Phone number" }
+                                    th { "DN" }
+                                    th { "Attributes" }
+                                }
+                            }
+                            tbody {
+                                for drift in report.drift.iter() {
+                                    tr {
+                                        td { class: "border-blue-300 border-t-2", {drift.kind.to_string()} }
+                                        td { class: "border-blue-300 border-t-2",
+                                            if let Some(contact_id) = drift.contact_id {
+                                                {contact_id.to_string()}
+                                            }
+                                        }
+                                        td { class: "border-blue-300 border-t-2",
+                                            if let Some(phone_number) = &drift.phone_number {
+                                                {phone_number.clone()}
+                                            }
+                                        }
+                                        td { class: "border-blue-300 border-t-2",
+                                            if let Some(dn) = &drift.dn {
+                                                {dn.clone()}
+                                            }
+                                        }
+                                        td { class: "border-blue-300 border-t-2", {drift.attributes.join(", ")} }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                },
+            }
+        }
+    }
+}
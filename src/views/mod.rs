@@ -16,5 +16,23 @@ pub use contact_detail::ContactDetail;
 mod phone_calls;
 pub use phone_calls::PhoneCallList;
 
+mod phone_call_detail;
+pub use phone_call_detail::PhoneCallDetail;
+
+mod heatmap;
+pub use heatmap::Heatmap;
+
 mod defaults;
 pub use defaults::DefaultList;
+
+mod tags;
+pub use tags::TagList;
+
+mod archive;
+pub use archive::ArchiveList;
+
+mod audit_log;
+pub use audit_log::AuditLogList;
+
+mod ldap;
+pub use ldap::LdapReconcile;
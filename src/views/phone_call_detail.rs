@@ -0,0 +1,197 @@
+#![allow(non_snake_case)]
+
+use chrono::Local;
+use dioxus::prelude::*;
+
+use crate::{
+    Route,
+    components::{
+        buttons::NavButton,
+        contacts::{ContactSummary, ListDialogReference},
+    },
+    functions::phone_calls::get_phone_call_record,
+    models::phone_calls::{PhoneCall, PhoneCallId},
+    use_user,
+};
+
+fn format_time(phone_call: &PhoneCall) -> String {
+    phone_call
+        .inserted_at
+        .with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+/// A call from the same number, linking to its own page.
+#[component]
+fn NeighbourRow(phone_call: PhoneCall) -> Element {
+    let id = phone_call.id;
+
+    rsx! {
+        tr {
+            class: "hover:bg-gray-500 cursor-pointer",
+            onclick: move |_| {
+                navigator().push(Route::PhoneCallDetail { phone_call_id: id });
+            },
+            td { class: "border-blue-300 border-t-2 pr-4", {format_time(&phone_call)} }
+            td { class: "border-blue-300 border-t-2 pr-4", {phone_call.action.clone()} }
+            td { class: "border-blue-300 border-t-2",
+                if let Some(dest) = &phone_call.destination_number {
+                    {dest.clone()}
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn PhoneCallDetail(phone_call_id: ReadSignal<PhoneCallId>) -> Element {
+    let user = use_user().ok().flatten();
+    let Some(_user) = user.as_ref() else {
+        return rsx! {
+            p { class: "alert alert-error", "You are not logged in." }
+        };
+    };
+
+    let record = use_resource(move || async move { get_phone_call_record(phone_call_id()).await });
+
+    match record() {
+        None => rsx! {
+            div { class: "alert alert-info", "Loading..." }
+        },
+        Some(Err(err)) => rsx! {
+            div { class: "alert alert-error", "Error: " {err.to_string()} }
+        },
+        Some(Ok(None)) => rsx! {
+            div { class: "alert alert-error", "Phone call not found." }
+        },
+        Some(Ok(Some(record))) => {
+            let phone_call = record.phone_call.clone();
+            let contact = record.contact.clone();
+            let permalink = Route::PhoneCallDetail {
+                phone_call_id: phone_call.id,
+            }
+            .to_string();
+
+            rsx! {
+                div { class: "ml-2 mr-2 mb-4",
+                    h1 { class: "text-lg font-bold",
+                        {format!("Call {}", phone_call.id)}
+                    }
+                    p { class: "mb-4",
+                        "Permalink: "
+                        a { class: "link", href: permalink.clone(), {permalink.clone()} }
+                    }
+                    table { class: "table table-striped mb-4",
+                        tbody {
+                            tr {
+                                th { class: "pr-4 text-left", "Time" }
+                                td { {format_time(&phone_call)} }
+                            }
+                            tr {
+                                th { class: "pr-4 text-left", "Source" }
+                                td {
+                                    match &phone_call.source_number {
+                                        Some(source) => rsx! {
+                                            {source.clone()}
+                                        },
+                                        None => rsx! { "Anonymised" },
+                                    }
+                                }
+                            }
+                            tr {
+                                th { class: "pr-4 text-left", "Destination" }
+                                td {
+                                    if let Some(dest) = &phone_call.destination_number {
+                                        {dest.clone()}
+                                    }
+                                }
+                            }
+                            tr {
+                                th { class: "pr-4 text-left", "Outcome" }
+                                td { {phone_call.action.clone()} }
+                            }
+                            tr {
+                                th { class: "pr-4 text-left", "Decided by" }
+                                td {
+                                    match phone_call.decision_source {
+                                        Some(source) => rsx! {
+                                            {source.description()}
+                                        },
+                                        None => rsx! { "Not recorded" },
+                                    }
+                                }
+                            }
+                            tr {
+                                th { class: "pr-4 text-left", "Contact name at the time" }
+                                td {
+                                    if let Some(name) = &phone_call.contact_name {
+                                        {name.clone()}
+                                    }
+                                }
+                            }
+                            tr {
+                                th { class: "pr-4 text-left", "Last changed" }
+                                td {
+                                    {
+                                        phone_call
+                                            .updated_at
+                                            .with_timezone(&Local)
+                                            .format("%Y-%m-%d %H:%M:%S")
+                                            .to_string()
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    h2 { class: "text-lg font-bold", "Contact now" }
+                    div { class: "mb-2",
+                        ContactSummary { contact: contact.clone() }
+                    }
+                    NavButton {
+                        on_click: move |_| {
+                            navigator()
+                                .push(Route::ContactDetail {
+                                    contact_id: contact.id,
+                                    dialog: ListDialogReference::Idle,
+                                    from_date: None,
+                                    to_date: None,
+                                    action: None,
+                                    dest: None,
+                                    before_ts: None,
+                                    before_id: None,
+                                });
+                        },
+                        "View contact"
+                    }
+
+                    h2 { class: "text-lg font-bold mt-4", "Calls from the same number" }
+                    if record.earlier.is_empty() && record.later.is_empty() {
+                        p { "No other calls." }
+                    } else {
+                        table { class: "w-full",
+                            tbody {
+                                for neighbour in record.later.iter().rev() {
+                                    NeighbourRow { key: "{neighbour.id}", phone_call: neighbour.clone() }
+                                }
+                                tr { class: "font-bold",
+                                    td { class: "border-blue-300 border-t-2 pr-4",
+                                        {format_time(&phone_call)}
+                                    }
+                                    td { class: "border-blue-300 border-t-2 pr-4",
+                                        {phone_call.action.clone()}
+                                    }
+                                    td { class: "border-blue-300 border-t-2", "This call" }
+                                }
+                                for neighbour in record.earlier.iter() {
+                                    NeighbourRow { key: "{neighbour.id}", phone_call: neighbour.clone() }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
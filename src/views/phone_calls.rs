@@ -2,7 +2,7 @@
 
 use std::ops::Deref;
 
-use chrono::{DateTime, Local, Utc};
+use chrono::{DateTime, Local, NaiveDate, Utc};
 use dioxus::prelude::*;
 use dioxus_fullstack::ServerFnError;
 use tap::Pipe;
@@ -10,25 +10,50 @@ use tap::Pipe;
 use crate::{
     Route,
     components::{
-        buttons::{ChangeButton, NavButton},
+        buttons::{ChangeButton, DeleteButton, NavButton},
         contacts::{ActiveDialog, ContactDialog, ContactSummary, ListDialogReference, Operation},
+        phone_calls::{
+            PhoneCallDialog, PhoneCallExportLinks, PhoneCallFilterBar, PhoneCallOperation,
+        },
     },
     functions::{contacts::get_contact_by_id, phone_calls::search_phone_calls_paginated},
     models::{
         contacts::Contact,
-        phone_calls::{PhoneCall, PhoneCallId},
+        phone_calls::{PhoneCall, PhoneCallFilter, PhoneCallId},
+        search::SearchQuery,
     },
     use_user,
 };
 
 const PAGE_SIZE: i64 = 50;
 
+fn list_route(
+    dialog: ListDialogReference,
+    q: SearchQuery,
+    filter: PhoneCallFilter,
+    before: Option<(DateTime<Utc>, PhoneCallId)>,
+) -> Route {
+    Route::PhoneCallList {
+        dialog,
+        q,
+        from_date: filter.from_date,
+        to_date: filter.to_date,
+        action: filter.action,
+        dest: filter.dest,
+        before_ts: before.map(|(ts, _)| ts),
+        before_id: before.map(|(_, id)| id),
+    }
+}
+
 #[component]
 fn EntryRow(
     phone_call: PhoneCall,
     contact: Contact,
     selected: Signal<Option<PhoneCallId>>,
-    q: String,
+    operation: Signal<Option<PhoneCallOperation>>,
+    is_admin: bool,
+    q: SearchQuery,
+    filter: PhoneCallFilter,
 ) -> Element {
     let id = phone_call.id;
 
@@ -57,7 +82,9 @@ fn EntryRow(
             }
             td { class: "block sm:table-cell border-blue-300 sm:border-t-2",
                 span { class: "sm:hidden", "Source: " }
-                {phone_call.source_number.clone()}
+                if let Some(source) = &phone_call.source_number {
+                    {source.clone()}
+                }
             }
             td { class: "block sm:table-cell border-blue-300 sm:border-t-2",
                 span { class: "sm:hidden", "Destination: " }
@@ -77,29 +104,64 @@ fn EntryRow(
                                     .push(Route::ContactDetail {
                                         contact_id: contact.id,
                                         dialog: crate::components::contacts::ListDialogReference::Idle,
+                                        from_date: None,
+                                        to_date: None,
+                                        action: None,
+                                        dest: None,
                                         before_ts: None,
                                         before_id: None,
                                     });
                             },
                             "View"
                         }
+                        NavButton {
+                            on_click: move |_| {
+                                navigator().push(Route::PhoneCallDetail { phone_call_id: id });
+                            },
+                            "Call details"
+                        }
                         ChangeButton {
                             on_click: {
                                 let q = q.clone();
+                                let filter = filter.clone();
                                 move |_| {
                                     navigator()
-                                        .push(Route::PhoneCallList {
-                                            dialog: ListDialogReference::Update {
-                                                contact_id: contact.id,
-                                            },
-                                            q: q.clone(),
-                                            before_ts: None,
-                                            before_id: None,
-                                        });
+                                        .push(
+                                            list_route(
+                                                ListDialogReference::Update {
+                                                    contact_id: contact.id,
+                                                },
+                                                q.clone(),
+                                                filter.clone(),
+                                                None,
+                                            ),
+                                        );
                                 }
                             },
                             "Edit"
                         }
+                        if is_admin {
+                            ChangeButton {
+                                on_click: {
+                                    let phone_call = phone_call.clone();
+                                    move |_| {
+                                        operation
+                                            .set(Some(PhoneCallOperation::Move(phone_call.clone())));
+                                    }
+                                },
+                                "Move call"
+                            }
+                            DeleteButton {
+                                on_click: {
+                                    let phone_call = phone_call.clone();
+                                    move |_| {
+                                        operation
+                                            .set(Some(PhoneCallOperation::Delete(phone_call.clone())));
+                                    }
+                                },
+                                "Delete call"
+                            }
+                        }
                     }
                 }
             }
@@ -110,19 +172,25 @@ fn EntryRow(
 #[component]
 pub fn PhoneCallList(
     dialog: ReadSignal<Option<ListDialogReference>>,
-    q: ReadSignal<String>,
+    q: ReadSignal<SearchQuery>,
+    from_date: ReadSignal<Option<NaiveDate>>,
+    to_date: ReadSignal<Option<NaiveDate>>,
+    action: ReadSignal<Option<String>>,
+    dest: ReadSignal<Option<String>>,
     before_ts: ReadSignal<Option<DateTime<Utc>>>,
     before_id: ReadSignal<Option<PhoneCallId>>,
 ) -> Element {
     let user = use_user().ok().flatten();
 
-    let Some(_user) = user.as_ref() else {
+    let Some(user) = user.as_ref() else {
         return rsx! {
             p { class: "alert alert-error", "You are not logged in." }
         };
     };
 
+    let is_admin = user.is_admin;
     let selected: Signal<Option<PhoneCallId>> = use_signal(|| None);
+    let mut operation: Signal<Option<PhoneCallOperation>> = use_signal(|| None);
 
     let dialog_resource: Resource<Result<ActiveDialog, ServerFnError>> =
         use_resource(move || async move {
@@ -137,20 +205,34 @@ pub fn PhoneCallList(
                         .ok_or(ServerFnError::new("Cannot find contact"))?;
                     ActiveDialog::Change(Operation::Update { contact }).pipe(Ok)
                 }
-                ListDialogReference::Delete { contact_id } => {
+                ListDialogReference::Archive { contact_id } => {
                     let contact = get_contact_by_id(contact_id)
                         .await?
                         .ok_or(ServerFnError::new("Cannot find contact"))?;
-                    ActiveDialog::Delete(contact).pipe(Ok)
+                    ActiveDialog::Archive(contact).pipe(Ok)
                 }
-                ListDialogReference::Idle => Ok(ActiveDialog::Idle),
+                ListDialogReference::Merge { .. }
+                | ListDialogReference::Bulk { .. }
+                | ListDialogReference::CreateNumber { .. }
+                | ListDialogReference::DeleteNumber { .. }
+                | ListDialogReference::Import
+                | ListDialogReference::Export
+                | ListDialogReference::Idle => Ok(ActiveDialog::Idle),
             }
         });
 
+    let filter = use_memo(move || PhoneCallFilter {
+        from_date: from_date(),
+        to_date: to_date(),
+        action: action(),
+        dest: dest(),
+        utc_offset_seconds: Local::now().offset().local_minus_utc(),
+    });
+
     // Fetch PAGE_SIZE + 1 rows so we can detect whether a next page exists.
     let mut list: Resource<Result<Vec<(PhoneCall, Contact)>, ServerFnError>> =
         use_resource(move || async move {
-            search_phone_calls_paginated(q(), before_ts(), before_id(), PAGE_SIZE).await
+            search_phone_calls_paginated(q(), filter(), before_ts(), before_id(), PAGE_SIZE).await
         });
 
     rsx! {
@@ -159,20 +241,38 @@ pub fn PhoneCallList(
                 input {
                     class: "form-control",
                     r#type: "text",
-                    value: q(),
+                    value: q().to_string(),
                     oninput: move |e| {
                         // Push a new URL when the query changes, resetting the cursor.
                         // Using replace() so typing doesn't pile up in the back stack.
-                        navigator().replace(Route::PhoneCallList {
-                            dialog: ListDialogReference::Idle,
-                            q: e.value(),
-                            before_ts: None,
-                            before_id: None,
-                        });
+                        navigator()
+                            .replace(
+                                list_route(
+                                    ListDialogReference::Idle,
+                                    SearchQuery::from(e.value()),
+                                    filter(),
+                                    None,
+                                ),
+                            );
                     },
                     placeholder: "Search...",
                 }
+                p { class: "text-sm opacity-70",
+                    "Filters: action:voicemail name:none calls:>5 tag:family after:2026-01-01 before:2026-02-01 dest:0299998888"
+                }
+                if let Err(err) = q().terms() {
+                    p { class: "alert alert-warning", {err.to_string()} }
+                }
+            }
+            PhoneCallFilterBar {
+                filter: filter(),
+                on_change: move |filter| {
+                    // Any change to the filters restarts paging from the newest call.
+                    navigator()
+                        .replace(list_route(ListDialogReference::Idle, q(), filter, None));
+                },
             }
+            PhoneCallExportLinks { q: q(), filter: filter() }
         }
 
         match list.read().deref() {
@@ -209,7 +309,10 @@ pub fn PhoneCallList(
                                             phone_call: phone_call.clone(),
                                             contact: contact.clone(),
                                             selected,
+                                            operation,
+                                            is_admin,
                                             q: q(),
+                                            filter: filter(),
                                         }
                                     }
                                 }
@@ -231,12 +334,15 @@ pub fn PhoneCallList(
                                                 let id = last_call.id;
                                                 let q_val = q();
                                                 move |_| {
-                                                    navigator().push(Route::PhoneCallList {
-                                                        dialog: ListDialogReference::Idle,
-                                                        q: q_val.clone(),
-                                                        before_ts: Some(ts),
-                                                        before_id: Some(id),
-                                                    });
+                                                    navigator()
+                                                        .push(
+                                                            list_route(
+                                                                ListDialogReference::Idle,
+                                                                q_val.clone(),
+                                                                filter(),
+                                                                Some((ts, id)),
+                                                            ),
+                                                        );
                                                 }
                                             },
                                             "Next"
@@ -255,6 +361,12 @@ pub fn PhoneCallList(
             }
         }
 
+        PhoneCallDialog {
+            operation,
+            on_change: move |()| list.restart(),
+            on_close: move |()| operation.set(None),
+        }
+
         match dialog_resource.read().deref() {
             Some(Err(err)) => rsx! {
                 div { class: "alert alert-error",
@@ -266,15 +378,17 @@ pub fn PhoneCallList(
                 ContactDialog {
                     dialog: dialog.clone(),
                     on_change: move |_contact: Contact| { list.restart() },
-                    on_delete: move |_contact| list.restart(),
+                    on_archive: move |_contact| list.restart(),
                     on_close: move |()| {
                         navigator()
-                            .push(Route::PhoneCallList {
-                                dialog: ListDialogReference::Idle,
-                                q: q(),
-                                before_ts: before_ts(),
-                                before_id: before_id(),
-                            });
+                            .push(
+                                list_route(
+                                    ListDialogReference::Idle,
+                                    q(),
+                                    filter(),
+                                    before_ts().zip(before_id()),
+                                ),
+                            );
                     },
                 }
             },
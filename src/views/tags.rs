@@ -0,0 +1,206 @@
+use std::ops::Deref;
+
+use dioxus::prelude::*;
+use dioxus_fullstack::ServerFnError;
+use dioxus_router::navigator;
+use tap::Pipe;
+
+use crate::{
+    Route,
+    components::{
+        buttons::{ChangeButton, CreateButton, NavButton},
+        contacts,
+        tags::{ActiveDialog, ListDialogReference, Operation, TagDialog},
+    },
+    functions::tags::{get_all_tags, get_tag_by_id},
+    models::{
+        contacts::ContactSort,
+        search::SearchQuery,
+        tags::{Tag, TagId},
+    },
+    use_user,
+};
+
+#[component]
+fn EntryRow(tag: Tag, selected: Signal<Option<TagId>>) -> Element {
+    let id = tag.id;
+
+    let navigator = navigator();
+    rsx! {
+        tr {
+            class: "hover:bg-gray-500 border-blue-300 mt-2 mb-2 p-2 border-2 w-full sm:w-auto sm:border-none inline-block sm:table-row",
+            onclick: move |_| { selected.set(Some(id)) },
+            td { class: "block sm:table-cell border-blue-300 sm:border-t-2", {tag.name.clone()} }
+            td { class: "block sm:table-cell border-blue-300 sm:border-t-2",
+                span { class: "sm:hidden", "Action: " }
+                if let Some(action) = &tag.action {
+                    {action.clone()}
+                }
+            }
+        }
+
+        if selected() == Some(id) {
+            tr {
+                td { colspan: "2", class: "block sm:table-cell",
+                    div { class: "flex gap-2",
+                        NavButton {
+                            on_click: {
+                                let name = tag.name.clone();
+                                move |_| {
+                                    navigator
+                                        .push(Route::ContactList {
+                                            dialog: contacts::ListDialogReference::Idle,
+                                            q: SearchQuery::default(),
+                                            tag: name.clone(),
+                                            sort: ContactSort::default(),
+                                            before_id: None,
+                                            before_name: None,
+                                            before_name_null: false,
+                                            before_count: None,
+                                            before_ts: None,
+                                            before_phone_number: None,
+                                        });
+                                }
+                            },
+                            "Contacts"
+                        }
+                        ChangeButton {
+                            on_click: move |_| {
+                                navigator
+                                    .push(Route::TagList {
+                                        dialog: ListDialogReference::Update { tag_id: id },
+                                    });
+                            },
+                            "Edit"
+                        }
+                        ChangeButton {
+                            on_click: move |_| {
+                                navigator
+                                    .push(Route::TagList {
+                                        dialog: ListDialogReference::Delete { tag_id: id },
+                                    });
+                            },
+                            "Delete"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+pub fn TagList(dialog: ReadSignal<Option<ListDialogReference>>) -> Element {
+    let user = use_user().ok().flatten();
+
+    let Some(_user) = user.as_ref() else {
+        return rsx! {
+            p { class: "alert alert-error", "You are not logged in." }
+        };
+    };
+
+    let selected: Signal<Option<TagId>> = use_signal(|| None);
+
+    let dialog: Resource<Result<ActiveDialog, ServerFnError>> = use_resource(move || async move {
+        let Some(dialog) = dialog() else {
+            return Ok(ActiveDialog::Idle);
+        };
+        match dialog {
+            ListDialogReference::Create => ActiveDialog::Change(Operation::Create).pipe(Ok),
+            ListDialogReference::Update { tag_id } => {
+                let tag = get_tag_by_id(tag_id)
+                    .await?
+                    .ok_or(ServerFnError::new("Cannot find tag"))?;
+                ActiveDialog::Change(Operation::Update { tag }).pipe(Ok)
+            }
+            ListDialogReference::Delete { tag_id } => {
+                let tag = get_tag_by_id(tag_id)
+                    .await?
+                    .ok_or(ServerFnError::new("Cannot find tag"))?;
+                ActiveDialog::Delete(tag).pipe(Ok)
+            }
+            ListDialogReference::Idle => Ok(ActiveDialog::Idle),
+        }
+    });
+
+    let navigator = navigator();
+    let mut list: Resource<Result<Vec<Tag>, ServerFnError>> =
+        use_resource(move || async move { get_all_tags().await });
+
+    rsx! {
+        div { class: "ml-2 mr-2",
+            div { class: "mb-2",
+                CreateButton {
+                    on_click: move |_| {
+                        navigator
+                            .push(Route::TagList {
+                                dialog: ListDialogReference::Create,
+                            });
+                    },
+                    "Create"
+                }
+            }
+        }
+
+        match list.read().deref() {
+            Some(Err(err)) => rsx! {
+                div { class: "alert alert-error",
+                    "Error loading tags: "
+                    {err.to_string()}
+                }
+            },
+            Some(Ok(list)) if list.is_empty() => rsx! {
+                p { class: "alert alert-info", "No entries found." }
+            },
+            Some(Ok(list)) => rsx! {
+                div { class: "ml-2 mr-2 sm:ml-0 sm:mr-0",
+                    table { class: "block sm:table",
+                        thead { class: "hidden sm:table-header-group",
+                            tr {
+                                th { "Name" }
+                                th { "Action" }
+                            }
+                        }
+                        tbody { class: "block sm:table-row-group",
+                            for tag in list.iter() {
+                                EntryRow { tag: tag.clone(), selected }
+                            }
+                        }
+                    }
+                }
+            },
+            None => {
+                rsx! {
+                    p { class: "alert alert-info", "Loading..." }
+                }
+            }
+        }
+
+        match dialog.read().deref() {
+            Some(Err(err)) => rsx! {
+                div { class: "alert alert-error",
+                    "Error loading dialog: "
+                    {err.to_string()}
+                }
+            },
+            Some(Ok(dialog)) => rsx! {
+                TagDialog {
+                    dialog: dialog.clone(),
+                    on_change: move |_tag: Tag| { list.restart() },
+                    on_delete: move |_tag| list.restart(),
+                    on_close: move |()| {
+                        navigator
+                            .push(Route::TagList {
+                                dialog: ListDialogReference::Idle,
+                            });
+                    },
+                }
+            },
+            None => {
+                rsx! {
+                    p { class: "alert alert-info", "Loading..." }
+                }
+            }
+        }
+    }
+}
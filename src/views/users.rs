@@ -7,12 +7,14 @@ use dioxus_router::navigator;
 use tap::Pipe;
 
 use crate::Route;
+use crate::components::audit_log::AuditLogSection;
 use crate::components::buttons::{ChangeButton, DeleteButton, NavButton};
 use crate::components::users::{
     ActiveDialog, DetailsDialogReference, ListDialogReference, UserCreate, UserDelete, UserUpdate,
     UserUpdatePassword,
 };
 use crate::functions::users::{get_user_by_id, get_users};
+use crate::models::audit_log::AuditEntityRef;
 use crate::models::users::{User, UserId};
 
 #[component]
@@ -109,6 +111,7 @@ pub fn UserDialog(
 #[component]
 pub fn UserDetail(user_id: UserId, dialog: ReadSignal<Option<DetailsDialogReference>>) -> Element {
     let mut maybe_user = use_resource(move || async move { get_user_by_id(user_id).await });
+    let mut history_version = use_signal(|| 0u32);
 
     let active_dialog: Memo<ActiveDialog> = use_memo(move || {
         let Some(dialog) = dialog() else {
@@ -215,7 +218,16 @@ pub fn UserDetail(user_id: UserId, dialog: ReadSignal<Option<DetailsDialogRefere
                                 dialog: DetailsDialogReference::Idle,
                             });
                     },
-                    reload: move |_| maybe_user.restart(),
+                    reload: move |_| {
+                        maybe_user.restart();
+                        history_version += 1;
+                    },
+                }
+
+                AuditLogSection {
+                    key: "{user_id}",
+                    entity: AuditEntityRef::from(user_id),
+                    version: history_version,
                 }
             }
         }